@@ -0,0 +1,144 @@
+//! Derive macros for rsqlite, enable them with the `derive` feature of rsqlite.
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, Index};
+
+/// implements `Collectable` for a struct, fields are collected in declaration order.
+///
+/// ```
+/// # use rsqlite::*;
+/// #[derive(Collectable, PartialEq, Debug)]
+/// struct User {
+///     name: String,
+///     age: i32,
+///     weight: Option<f64>,
+/// }
+///
+/// # let database = Database::open(":memory:")?;
+/// let user: User = database.collect("select 'amin', 29, null", ())?;
+/// assert!(user == User { name: "amin".to_owned(), age: 29, weight: None });
+/// # Ok::<(), RsqliteError>(())
+/// ```
+#[proc_macro_derive(Collectable)]
+pub fn derive_collectable(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let fields = match fields(&input) {
+        Ok(fields) => fields,
+        Err(error) => return error.to_compile_error().into(),
+    };
+    let types = fields.iter().map(|field| &field.ty).collect::<Vec<_>>();
+    let body = match &fields {
+        Fields::Named(named) => {
+            let idents = named.named.iter().map(|field| &field.ident);
+            quote! {
+                #name { #(#idents: <#types as ::rsqlite::Collectable>::collect(statement, column),)* }
+            }
+        }
+        Fields::Unnamed(_) => quote! {
+            #name ( #(<#types as ::rsqlite::Collectable>::collect(statement, column),)* )
+        },
+        Fields::Unit => quote! { #name },
+    };
+    let try_body = match &fields {
+        Fields::Named(named) => {
+            let idents = named.named.iter().map(|field| &field.ident);
+            quote! {
+                #name { #(#idents: <#types as ::rsqlite::Collectable>::try_collect(statement, column)?,)* }
+            }
+        }
+        Fields::Unnamed(_) => quote! {
+            #name ( #(<#types as ::rsqlite::Collectable>::try_collect(statement, column)?,)* )
+        },
+        Fields::Unit => quote! { #name },
+    };
+
+    let expanded = quote! {
+        impl #impl_generics ::rsqlite::Collectable for #name #ty_generics #where_clause {
+            fn collect(
+                statement: &::rsqlite::Statement,
+                column: &mut ::std::os::raw::c_int,
+            ) -> Self {
+                #body
+            }
+            fn try_collect(
+                statement: &::rsqlite::Statement,
+                column: &mut ::std::os::raw::c_int,
+            ) -> ::rsqlite::Result<Self> {
+                Ok(#try_body)
+            }
+            fn columns_needed() -> ::std::os::raw::c_int {
+                0 #(+ <#types as ::rsqlite::Collectable>::columns_needed())*
+            }
+        }
+    };
+    expanded.into()
+}
+
+/// implements `Bindable` for a struct, fields are bound in declaration order.
+///
+/// ```
+/// # use rsqlite::*;
+/// #[derive(Bindable)]
+/// struct User<'a> {
+///     name: &'a str,
+///     age: i32,
+///     weight: Option<f64>,
+/// }
+///
+/// # let database = Database::open(":memory:")?;
+/// database.execute("create table user (name text, age int, weight real)", ())?;
+/// let user = User { name: "amin", age: 29, weight: Some(69.5) };
+/// database.execute("insert into user values (?, ?, ?)", &user)?;
+/// # let age: i32 = database.collect("select age from user", ())?;
+/// # assert!(age == 29);
+/// # Ok::<(), RsqliteError>(())
+/// ```
+#[proc_macro_derive(Bindable)]
+pub fn derive_bindable(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let fields = match fields(&input) {
+        Ok(fields) => fields,
+        Err(error) => return error.to_compile_error().into(),
+    };
+    let accessors = fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| match &field.ident {
+            Some(ident) => quote! { #ident },
+            None => {
+                let index = Index::from(i);
+                quote! { #index }
+            }
+        });
+
+    let expanded = quote! {
+        impl #impl_generics ::rsqlite::Bindable for #name #ty_generics #where_clause {
+            fn bind(
+                &self,
+                statement: &mut ::rsqlite::Statement,
+                index: &mut ::std::os::raw::c_int,
+            ) -> ::rsqlite::Result<()> {
+                #(::rsqlite::Bindable::bind(&self.#accessors, statement, index)?;)*
+                Ok(())
+            }
+        }
+    };
+    expanded.into()
+}
+
+/// the fields of a struct, or an error for enums and unions
+fn fields(input: &DeriveInput) -> syn::Result<&Fields> {
+    match &input.data {
+        Data::Struct(data) => Ok(&data.fields),
+        _ => Err(syn::Error::new_spanned(
+            &input.ident,
+            "rsqlite derives are only supported on structs",
+        )),
+    }
+}
@@ -0,0 +1,53 @@
+//! Databases appended to the end of another file, such as the executable itself
+//!
+//! This module is only available with the `appendvfs` feature, and requires the linked
+//! SQLite to be compiled with `ext/misc/appendvfs.c`.
+//! Call `register()` once, then use `Database::open_appended` to open the database.
+//!
+//! Note that most operating systems do not allow writing to a running executable,
+//! open it with `SQLITE_OPEN_READONLY` and write to a copy instead.
+use super::{Database, Result};
+use core::ptr;
+use libc::{c_char, c_int, c_void};
+use sqlite3_sys as ffi;
+
+extern "C" {
+    fn sqlite3_appendvfs_init(
+        db: *mut ffi::sqlite3,
+        err: *mut *mut c_char,
+        api: *const c_void,
+    ) -> c_int;
+}
+
+/// name of the VFS registered by `register()`
+pub const APPENDVFS: &str = "apndvfs";
+
+/// registers the append VFS, it is not made the default VFS.
+pub fn register() -> Result<()> {
+    let ecode = unsafe { sqlite3_appendvfs_init(ptr::null_mut(), ptr::null_mut(), ptr::null()) };
+    match ecode {
+        ffi::SQLITE_OK | ffi::SQLITE_OK_LOAD_PERMANENTLY => Ok(()),
+        other => Err(other.into()),
+    }
+}
+
+impl Database {
+    /// open a database appended to the end of the file at `path`.
+    ///
+    /// If the file does not have a database appended yet and `SQLITE_OPEN_CREATE` is set,
+    /// a new database is appended to it on the first write.
+    ///
+    /// ```no_run
+    /// # use rsqlite::*;
+    /// appendvfs::register()?;
+    /// let executable = std::env::current_exe().unwrap();
+    /// let database = Database::open_appended(
+    ///     executable.to_str().unwrap(),
+    ///     ffi::SQLITE_OPEN_READONLY,
+    /// )?;
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn open_appended(path: &str, flags: c_int) -> Result<Database> {
+        Database::open_v2(path, flags, Some(APPENDVFS))
+    }
+}
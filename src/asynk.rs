@@ -0,0 +1,255 @@
+//! An async wrapper running the blocking sqlite calls on tokio's blocking pool
+use super::{Bindable, Collectable, Database, Iterable, Result, SerializedDatabase};
+use futures_core::Stream;
+use sqlite3_sys as ffi;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tokio::sync::{mpsc, oneshot};
+
+/// A database usable from async code, see the `tokio` feature
+///
+/// Every call runs on `tokio::task::spawn_blocking`, so the async runtime is never blocked.
+/// Cloning is cheap and shares the same connection.
+///
+/// ```
+/// # use rsqlite::*;
+/// use rsqlite::asynk::AsyncDatabase;
+///
+/// # let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+/// # runtime.block_on(async {
+/// let database = AsyncDatabase::open(":memory:").await?;
+/// database.execute("create table user (name text, age int)", ()).await?;
+/// database.execute("insert into user values (?, ?)", ("amin".to_owned(), 29)).await?;
+///
+/// let (name, age): (String, i32) = database.collect("select name, age from user", ()).await?;
+/// assert!(name == "amin" && age == 29);
+///
+/// let count = database.call(|database| database.collect::<i32>("select count(*) from user", ())).await?;
+/// assert!(count == 1);
+/// # Ok::<(), RsqliteError>(())
+/// # })?;
+/// # Ok::<(), RsqliteError>(())
+/// ```
+#[derive(Clone)]
+pub struct AsyncDatabase {
+    database: Arc<SerializedDatabase>,
+}
+
+impl AsyncDatabase {
+    /// opens the database read-write, creating it if it does not exist
+    pub async fn open(path: &str) -> Result<AsyncDatabase> {
+        let path = path.to_owned();
+        let database = blocking(move || SerializedDatabase::open(&path)).await?;
+        Ok(AsyncDatabase::from(database))
+    }
+
+    /// runs `f` with exclusive access to the database, on the blocking pool
+    ///
+    /// Callbacks registered by `f` outlive it and may run on any thread of the pool,
+    /// so they must be `Send` even when created inside `f`:
+    ///
+    /// ```compile_fail
+    /// # use rsqlite::*;
+    /// use rsqlite::asynk::AsyncDatabase;
+    /// use std::{cell::RefCell, rc::Rc};
+    ///
+    /// # async fn example(database: AsyncDatabase) -> Result<()> {
+    /// database.call(|database| {
+    ///     let log = Rc::new(RefCell::new(vec![]));
+    ///     database.update_hook(move |_, _, table, _| log.borrow_mut().push(table.to_owned()));
+    ///     Ok(())
+    /// }).await
+    /// # }
+    /// ```
+    pub async fn call<F, T>(&self, f: F) -> Result<T>
+    where
+        F: FnOnce(&Database) -> Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let database = Arc::clone(&self.database);
+        blocking(move || f(&database.lock())).await
+    }
+
+    /// see `Database::execute`
+    pub async fn execute<P>(&self, sql: &str, params: P) -> Result<i64>
+    where
+        P: Bindable + Send + 'static,
+    {
+        let sql = sql.to_owned();
+        self.call(move |database| database.execute(&sql, params))
+            .await
+    }
+
+    /// see `Database::collect`
+    pub async fn collect<R, P>(&self, sql: &str, params: P) -> Result<R>
+    where
+        R: Collectable + Send + 'static,
+        P: Bindable + Send + 'static,
+    {
+        let sql = sql.to_owned();
+        self.call(move |database| database.collect(&sql, params))
+            .await
+    }
+
+    /// see `Database::collect_vec`
+    pub async fn collect_vec<R, P>(&self, sql: &str, params: P) -> Result<Vec<R>>
+    where
+        R: Collectable + Send + 'static,
+        P: Bindable + Send + 'static,
+    {
+        let sql = sql.to_owned();
+        self.call(move |database| database.collect_vec(&sql, params))
+            .await
+    }
+
+    /// a statement whose rows can be streamed, see `AsyncStatement::query_stream`
+    pub fn prepare(&self, sql: &str) -> AsyncStatement {
+        AsyncStatement {
+            database: Arc::clone(&self.database),
+            sql: sql.to_owned(),
+            capacity: 32,
+        }
+    }
+
+    /// see `Database::for_each`, `iterable` runs on the blocking pool
+    pub async fn for_each<P, I, T>(&self, sql: &str, params: P, iterable: I) -> Result<()>
+    where
+        P: Bindable + Send + 'static,
+        I: Iterable<(), T> + Send + 'static,
+    {
+        let sql = sql.to_owned();
+        self.call(move |database| database.for_each(&sql, params, iterable))
+            .await
+    }
+}
+
+/// A statement of an `AsyncDatabase`, prepared when its rows are streamed
+pub struct AsyncStatement {
+    database: Arc<SerializedDatabase>,
+    sql: String,
+    capacity: usize,
+}
+
+/// The rows of `AsyncStatement::query_stream`
+pub struct RowStream<R> {
+    rows: mpsc::Receiver<Result<R>>,
+}
+
+impl AsyncStatement {
+    /// number of rows collected ahead of the consumer, 32 by default
+    pub fn capacity(mut self, capacity: usize) -> AsyncStatement {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    /// streams the rows, collected as `R` on the blocking pool,
+    /// once the statement is prepared and `params` are bound
+    ///
+    /// At most `capacity` rows are collected before the consumer catches up.
+    /// The database stays locked until the stream is exhausted or dropped,
+    /// so awaiting another call on the same database, or a clone of it,
+    /// while the stream is alive never completes.
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// use futures_core::Stream;
+    /// use rsqlite::asynk::AsyncDatabase;
+    /// use std::future::poll_fn;
+    /// use std::pin::Pin;
+    ///
+    /// # let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    /// # runtime.block_on(async {
+    /// let database = AsyncDatabase::open(":memory:").await?;
+    /// let statement = database.prepare(
+    ///     "with recursive n(x) as (select 1 union all select x + 1 from n where x < ?) select x from n",
+    /// );
+    /// let mut rows = statement.capacity(4).query_stream::<i64, _>(1000).await?;
+    ///
+    /// let mut sum = 0;
+    /// while let Some(x) = poll_fn(|cx| Pin::new(&mut rows).poll_next(cx)).await {
+    ///     let x = x?;
+    ///     sum += x;
+    ///     if x == 10 {
+    ///         break;
+    ///     }
+    /// }
+    /// assert!(sum == 55);
+    ///
+    /// // drop the stream to unlock the database
+    /// drop(rows);
+    /// let count: i32 = database.collect("select count(*) from sqlite_schema", ()).await?;
+    /// assert!(count == 0);
+    /// # Ok::<(), RsqliteError>(())
+    /// # })?;
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub async fn query_stream<R, P>(&self, params: P) -> Result<RowStream<R>>
+    where
+        R: Collectable + Send + 'static,
+        P: Bindable + Send + 'static,
+    {
+        let (ready, started) = oneshot::channel();
+        let (sender, receiver) = mpsc::channel(self.capacity);
+        let database = Arc::clone(&self.database);
+        let sql = self.sql.clone();
+        let task = tokio::task::spawn_blocking(move || {
+            let database = database.lock();
+            let mut statement = match database.prepare(&sql) {
+                Ok(statement) => statement,
+                Err(error) => return drop(ready.send(Err(error))),
+            };
+            let rows = match statement.query(params) {
+                Ok(rows) => rows,
+                Err(error) => return drop(ready.send(Err(error))),
+            };
+            if ready.send(Ok(())).is_err() {
+                return;
+            }
+            for row in rows {
+                let row = row.and_then(|row| row.collect::<R>());
+                // stop stepping once the stream is dropped
+                if sender.blocking_send(row).is_err() {
+                    return;
+                }
+            }
+        });
+        match started.await {
+            Ok(result) => result.map(|()| RowStream { rows: receiver }),
+            // the task ended without a word, it panicked
+            Err(_) => match task.await {
+                Err(error) if error.is_panic() => std::panic::resume_unwind(error.into_panic()),
+                _ => Err(ffi::SQLITE_ABORT.into()),
+            },
+        }
+    }
+}
+
+impl<R> Stream for RowStream<R> {
+    type Item = Result<R>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Result<R>>> {
+        self.rows.poll_recv(cx)
+    }
+}
+
+impl From<SerializedDatabase> for AsyncDatabase {
+    fn from(database: SerializedDatabase) -> AsyncDatabase {
+        AsyncDatabase {
+            database: Arc::new(database),
+        }
+    }
+}
+
+/// runs `f` on the blocking pool, a panic in `f` is resumed in the caller
+async fn blocking<F, T>(f: F) -> Result<T>
+where
+    F: FnOnce() -> Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    match tokio::task::spawn_blocking(f).await {
+        Ok(result) => result,
+        Err(error) if error.is_panic() => std::panic::resume_unwind(error.into_panic()),
+        Err(_) => Err(ffi::SQLITE_ABORT.into()),
+    }
+}
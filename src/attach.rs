@@ -0,0 +1,86 @@
+//! Attaching and detaching databases, and the file name and read-only state of each one
+use super::quote::quote_identifier;
+use super::{Database, Result};
+use sqlite3_sys as ffi;
+use std::ffi::{CStr, CString};
+
+impl Database {
+    /// attaches the database at `path` under the schema name `alias`
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// # let database = Database::open(":memory:")?;
+    /// database.attach(":memory:", "other db")?;
+    /// database.execute(r#"create table "other db".t(x integer)"#, ())?;
+    ///
+    /// let databases = database.databases()?;
+    /// assert!(databases.iter().any(|(name, _)| name == "other db"));
+    ///
+    /// database.detach("other db")?;
+    /// assert!(database.databases()?.len() == 1);
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn attach(&self, path: &str, alias: &str) -> Result<()> {
+        let sql = format!("attach database ? as {}", quote_identifier(alias));
+        self.execute(&sql, path)?;
+        Ok(())
+    }
+
+    /// detaches the database attached as `alias`
+    pub fn detach(&self, alias: &str) -> Result<()> {
+        let sql = format!("detach database {}", quote_identifier(alias));
+        self.execute(&sql, ())?;
+        Ok(())
+    }
+
+    /// the `(name, file)` of the main, temp and attached databases,
+    /// `file` is empty for in-memory and temporary databases.
+    pub fn databases(&self) -> Result<Vec<(String, String)>> {
+        let mut databases = vec![];
+        self.for_each(
+            "select name, file from pragma_database_list",
+            (),
+            |name: String, file: Option<String>| databases.push((name, file.unwrap_or_default())),
+        )?;
+        Ok(databases)
+    }
+
+    /// the absolute path of the file behind `schema`, like `main` or an attached alias,
+    /// `None` for in-memory and temporary databases
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// # let path = std::env::temp_dir().join("rsqlite-db-filename.db");
+    /// # let path = path.to_str().unwrap();
+    /// let database = Database::open(path)?;
+    /// database.attach(":memory:", "scratch")?;
+    ///
+    /// assert!(database.db_filename("main")?.unwrap().ends_with("rsqlite-db-filename.db"));
+    /// assert!(database.db_filename("scratch")?.is_none());
+    /// assert!(database.db_filename("unknown").is_err());
+    ///
+    /// assert!(!database.db_readonly("main")?);
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn db_filename(&self, schema: &str) -> Result<Option<String>> {
+        let schema = CString::new(schema)?;
+        let filename = unsafe { ffi::sqlite3_db_filename(self.db, schema.as_ptr()) };
+        if filename.is_null() {
+            return Err(ffi::SQLITE_NOTFOUND.into());
+        }
+        let filename = unsafe { CStr::from_ptr(filename) }.to_string_lossy();
+        Ok(match filename.is_empty() {
+            true => None,
+            false => Some(filename.into_owned()),
+        })
+    }
+
+    /// whether `schema`, like `main` or an attached alias, was opened read-only
+    pub fn db_readonly(&self, schema: &str) -> Result<bool> {
+        let schema = CString::new(schema)?;
+        match unsafe { ffi::sqlite3_db_readonly(self.db, schema.as_ptr()) } {
+            -1 => Err(ffi::SQLITE_NOTFOUND.into()),
+            readonly => Ok(readonly != 0),
+        }
+    }
+}
@@ -0,0 +1,175 @@
+//! Online backups of a database into another, see https://sqlite.org/backup.html
+use super::{Database, Result, RsqliteError};
+use libc::c_int;
+use sqlite3_sys as ffi;
+use std::marker::PhantomData;
+use std::path::Path;
+use std::time::Duration;
+
+/// How far a backup got
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BackupProgress {
+    /// pages left to copy
+    pub remaining: i32,
+    /// pages in the source database
+    pub total: i32,
+}
+
+/// A backup of the main database of `source` into the main database of `destination`,
+/// copied a few pages at a time while `source` stays usable
+pub struct Backup<'a, 'b> {
+    backup: *mut ffi::sqlite3_backup,
+    source: PhantomData<&'a Database>,
+    destination: &'b mut Database,
+}
+
+/// Options of `Database::backup_to_file`
+#[derive(Debug, Clone)]
+pub struct BackupOptions {
+    pages_per_step: i32,
+    pause: Duration,
+    max_retries: u32,
+}
+
+impl Default for BackupOptions {
+    fn default() -> BackupOptions {
+        BackupOptions {
+            pages_per_step: 100,
+            pause: Duration::from_millis(10),
+            max_retries: 100,
+        }
+    }
+}
+
+impl BackupOptions {
+    /// copies 100 pages at a time, pausing 10ms in between and retrying a locked source 100 times
+    pub fn new() -> BackupOptions {
+        BackupOptions::default()
+    }
+
+    /// pages copied per step, a negative number copies everything in one step
+    pub fn pages_per_step(mut self, pages: i32) -> BackupOptions {
+        self.pages_per_step = pages;
+        self
+    }
+
+    /// sleeps between steps so writers to the source get a chance to run
+    pub fn pause(mut self, pause: Duration) -> BackupOptions {
+        self.pause = pause;
+        self
+    }
+
+    /// how many steps in a row may fail with `SQLITE_BUSY` or `SQLITE_LOCKED` before giving up
+    pub fn max_retries(mut self, retries: u32) -> BackupOptions {
+        self.max_retries = retries;
+        self
+    }
+}
+
+impl<'a, 'b> Backup<'a, 'b> {
+    /// starts a backup, `destination` is borrowed until the backup is dropped
+    pub fn new(source: &'a Database, destination: &'b mut Database) -> Result<Backup<'a, 'b>> {
+        let main = b"main\0".as_ptr() as *const _;
+        let backup = unsafe { ffi::sqlite3_backup_init(destination.db, main, source.db, main) };
+        if backup.is_null() {
+            let ecode = unsafe { ffi::sqlite3_errcode(destination.db) };
+            return Err(destination.error(ecode));
+        }
+        Ok(Backup {
+            backup,
+            source: PhantomData,
+            destination,
+        })
+    }
+
+    /// copies up to `pages` pages, or every page if negative, returning whether the backup is done
+    ///
+    /// `SQLITE_BUSY` and `SQLITE_LOCKED` errors can be retried later.
+    pub fn step(&mut self, pages: i32) -> Result<bool> {
+        match self.step_raw(pages) {
+            ffi::SQLITE_DONE => Ok(true),
+            ffi::SQLITE_OK => Ok(false),
+            other => Err(self.destination.error(other)),
+        }
+    }
+
+    fn step_raw(&mut self, pages: i32) -> c_int {
+        unsafe { ffi::sqlite3_backup_step(self.backup, pages as c_int) }
+    }
+
+    /// the progress as of the last step
+    pub fn progress(&self) -> BackupProgress {
+        BackupProgress {
+            remaining: unsafe { ffi::sqlite3_backup_remaining(self.backup) },
+            total: unsafe { ffi::sqlite3_backup_pagecount(self.backup) },
+        }
+    }
+}
+
+impl<'a, 'b> Drop for Backup<'a, 'b> {
+    fn drop(&mut self) {
+        unsafe { ffi::sqlite3_backup_finish(self.backup) };
+    }
+}
+
+impl Database {
+    /// copies the main database into the file at `path`, replacing its content,
+    /// `progress` is called after every step
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// use rsqlite::backup::BackupOptions;
+    ///
+    /// # let database = Database::open(":memory:")?;
+    /// # let path = std::env::temp_dir().join("rsqlite-backup.db");
+    /// database.execute("create table user (name text)", ())?;
+    /// database.execute("insert into user values ('amin')", ())?;
+    ///
+    /// let options = BackupOptions::new().pages_per_step(1);
+    /// let mut steps = 0;
+    /// database.backup_to_file(&path, &options, |progress| {
+    ///     steps += 1;
+    ///     assert!(progress.remaining < progress.total);
+    /// })?;
+    /// assert!(steps > 1);
+    ///
+    /// let copy = Database::open(path.to_str().unwrap())?;
+    /// let name: String = copy.collect("select name from user", ())?;
+    /// assert!(name == "amin");
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn backup_to_file(
+        &self,
+        path: impl AsRef<Path>,
+        options: &BackupOptions,
+        mut progress: impl FnMut(BackupProgress),
+    ) -> Result<()> {
+        let path = path.as_ref().to_str().ok_or_else(|| {
+            RsqliteError::new(
+                ffi::SQLITE_CANTOPEN,
+                ffi::SQLITE_CANTOPEN,
+                "the backup path is not valid UTF-8".to_owned(),
+            )
+        })?;
+        let mut destination = Database::open(path)?;
+        let mut backup = Backup::new(self, &mut destination)?;
+        let mut retries = 0;
+        loop {
+            match backup.step_raw(options.pages_per_step) {
+                ffi::SQLITE_DONE => {
+                    progress(backup.progress());
+                    return Ok(());
+                }
+                ffi::SQLITE_OK => {
+                    retries = 0;
+                    progress(backup.progress());
+                }
+                ffi::SQLITE_BUSY | ffi::SQLITE_LOCKED if retries < options.max_retries => {
+                    retries += 1;
+                }
+                other => return Err(backup.destination.error(other)),
+            }
+            std::thread::sleep(options.pause);
+        }
+    }
+}
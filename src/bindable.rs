@@ -1,6 +1,14 @@
+use super::collectable::out_of_range;
 use super::{Result, Statement};
 use libc::{c_char, c_double, c_int, c_void};
 use sqlite3_sys as ffi;
+use std::borrow::Cow;
+use std::convert::TryFrom;
+
+/// `ffi::SQLITE_TRANSIENT`, sqlite makes its own copy of the data before `bind` returns
+pub(crate) fn transient() -> ffi::sqlite3_destructor_type {
+    Some(unsafe { std::mem::transmute::<isize, unsafe extern "C" fn(*mut c_void)>(-1) })
+}
 
 /// Bindable types can bind themselves to a sqlite statement
 pub trait Bindable {
@@ -23,6 +31,18 @@ impl Bindable for () {
         Ok(())
     }
 }
+/// binds `None` as NULL and `Some(t)` as `t`
+///
+/// ```
+/// # use rsqlite::*;
+/// # let database = Database::open(":memory:")?;
+/// database.execute("create table user (name text, age int)", ())?;
+/// database.execute("insert into user values (?, ?)", (None::<&str>, Some(29)))?;
+///
+/// let user: (Option<String>, Option<i32>) = database.collect("select name, age from user", ())?;
+/// assert!(user == (None, Some(29)));
+/// # Ok::<(), RsqliteError>(())
+/// ```
 impl<T> Bindable for Option<T>
 where
     T: Bindable,
@@ -31,11 +51,10 @@ where
         match &self {
             None => {
                 let ecode = unsafe { ffi::sqlite3_bind_null(statement.stmt, *index) };
-                dbg!(&index);
                 *index += 1;
                 match ecode {
                     ffi::SQLITE_OK => Ok(()),
-                    other => Err(other.into()),
+                    other => Err(statement.database.error(other)),
                 }
             }
             Some(t) => t.bind(statement, index),
@@ -48,17 +67,69 @@ impl Bindable for i32 {
         *index += 1;
         match ecode {
             ffi::SQLITE_OK => Ok(()),
-            other => Err(other.into()),
+            other => Err(statement.database.error(other)),
         }
     }
 }
+/// small integers widen to `int`, `u32` and `isize` widen to `int64`
+macro_rules! bindable_small_int {
+    ($($t:ty as $wide:ty),+) => ($(
+        impl Bindable for $t {
+            fn bind(&self, statement: &mut Statement, index: &mut c_int) -> Result<()> {
+                (*self as $wide).bind(statement, index)
+            }
+        }
+    )+);
+}
+
+bindable_small_int!(
+    i8 as i32,
+    i16 as i32,
+    u8 as i32,
+    u16 as i32,
+    u32 as i64,
+    isize as i64
+);
+
+/// `u64` and `usize` bind as `int64`, values above `i64::MAX` fail with `Mismatch`
+macro_rules! bindable_unsigned {
+    ($($t:ty),+) => ($(
+        impl Bindable for $t {
+            fn bind(&self, statement: &mut Statement, index: &mut c_int) -> Result<()> {
+                match i64::try_from(*self) {
+                    Ok(value) => value.bind(statement, index),
+                    Err(_) => Err(out_of_range(self, "int64")),
+                }
+            }
+        }
+    )+);
+}
+
+bindable_unsigned!(u64, usize);
+
+/// `bool` is stored as the integer `0` or `1`
+///
+/// ```
+/// # use rsqlite::*;
+/// # let database = Database::open(":memory:")?;
+/// database.execute("create table user(name text, admin integer)", ())?;
+/// database.execute("insert into user values (?, ?)", ("amin", true))?;
+/// let admin: bool = database.collect("select admin from user", ())?;
+/// assert!(admin);
+/// # Ok::<(), RsqliteError>(())
+/// ```
+impl Bindable for bool {
+    fn bind(&self, statement: &mut Statement, index: &mut c_int) -> Result<()> {
+        (*self as i32).bind(statement, index)
+    }
+}
 impl Bindable for c_double {
     fn bind(&self, statement: &mut Statement, index: &mut c_int) -> Result<()> {
         let ecode = unsafe { ffi::sqlite3_bind_double(statement.stmt, *index, *self) };
         *index += 1;
         match ecode {
             ffi::SQLITE_OK => Ok(()),
-            other => Err(other.into()),
+            other => Err(statement.database.error(other)),
         }
     }
 }
@@ -68,32 +139,32 @@ impl Bindable for ffi::sqlite3_int64 {
         *index += 1;
         match ecode {
             ffi::SQLITE_OK => Ok(()),
-            other => Err(other.into()),
+            other => Err(statement.database.error(other)),
         }
     }
 }
 /// sqlite3_bind_text() expects a pointer to well-formed UTF8 text (i.e `&str`)
-impl<'a> Bindable for &'a str {
+impl Bindable for &str {
     fn bind(&self, statement: &mut Statement, index: &mut c_int) -> Result<()> {
-        let len = self.as_bytes().len() as c_int;
+        let len = self.len() as c_int;
         let ecode = unsafe {
             ffi::sqlite3_bind_text(
                 statement.stmt,
                 *index,
                 self.as_ptr() as *const c_char,
                 len,
-                Some(std::mem::transmute(-1isize)), // ffi::SQLITE_TRANSIENT
+                transient(),
             )
         };
         *index += 1;
         match ecode {
             ffi::SQLITE_OK => Ok(()),
-            other => Err(other.into()),
+            other => Err(statement.database.error(other)),
         }
     }
 }
 /// `&[u8]` is treated as sqlite `blob` data type
-impl<'a> Bindable for &'a [u8] {
+impl Bindable for &[u8] {
     fn bind(&self, statement: &mut Statement, index: &mut c_int) -> Result<()> {
         let ecode = unsafe {
             ffi::sqlite3_bind_blob(
@@ -101,16 +172,180 @@ impl<'a> Bindable for &'a [u8] {
                 *index,
                 self.as_ptr() as *const c_void,
                 self.len() as c_int,
-                Some(std::mem::transmute(-1isize)), // ffi::SQLITE_TRANSIENT
+                transient(),
+            )
+        };
+        *index += 1;
+        match ecode {
+            ffi::SQLITE_OK => Ok(()),
+            other => Err(statement.database.error(other)),
+        }
+    }
+}
+
+/// owned and borrowed-or-owned text and blobs bind like `&str` and `&[u8]`
+///
+/// ```
+/// # use rsqlite::*;
+/// use std::borrow::Cow;
+/// # let database = Database::open(":memory:")?;
+/// database.execute("create table file(name text, content blob)", ())?;
+/// database.execute("insert into file values (?, ?)", (String::from("a.txt"), vec![1u8, 2]))?;
+/// database.execute("insert into file values (?, ?)", (Cow::from("b.txt"), Cow::from(&[3u8][..])))?;
+/// let count: i32 = database.collect("select count(*) from file", ())?;
+/// assert!(count == 2);
+/// # Ok::<(), RsqliteError>(())
+/// ```
+impl Bindable for String {
+    fn bind(&self, statement: &mut Statement, index: &mut c_int) -> Result<()> {
+        self.as_str().bind(statement, index)
+    }
+}
+impl Bindable for Vec<u8> {
+    fn bind(&self, statement: &mut Statement, index: &mut c_int) -> Result<()> {
+        self.as_slice().bind(statement, index)
+    }
+}
+impl Bindable for Box<[u8]> {
+    fn bind(&self, statement: &mut Statement, index: &mut c_int) -> Result<()> {
+        (&**self).bind(statement, index)
+    }
+}
+impl Bindable for Cow<'_, str> {
+    fn bind(&self, statement: &mut Statement, index: &mut c_int) -> Result<()> {
+        (&**self).bind(statement, index)
+    }
+}
+impl Bindable for Cow<'_, [u8]> {
+    fn bind(&self, statement: &mut Statement, index: &mut c_int) -> Result<()> {
+        (&**self).bind(statement, index)
+    }
+}
+
+/// Binds `&str` and `&[u8]` without copying them (`SQLITE_STATIC`)
+///
+/// The data only has to outlive the call to `execute`, `collect`, `collect_vec` or
+/// `for_each` it is passed to, the binding is cleared before they return.
+/// Anywhere else, like `Statement::query`, the data is copied as usual.
+///
+/// ```
+/// # use rsqlite::*;
+/// use rsqlite::bindable::Static;
+/// # let database = Database::open(":memory:")?;
+/// database.execute("create table file(content blob)", ())?;
+/// let content = vec![0u8; 1 << 20];
+/// database.execute("insert into file values (?)", Static(content.as_slice()))?;
+/// let size: i32 = database.collect("select length(content) from file", ())?;
+/// assert!(size == 1 << 20);
+/// # Ok::<(), RsqliteError>(())
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Static<T>(pub T);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum StaticBindings {
+    /// bindings outlive the borrowed data, `Static` has to copy
+    Disallowed,
+    /// bindings are cleared before the borrowed data goes away
+    Allowed,
+}
+
+/// clears the bindings of a statement and disallows `Static` bindings again when dropped,
+/// even if a panic unwinds
+pub(crate) struct ClearBindings<'s, 'a>(pub(crate) &'s mut Statement<'a>);
+
+impl Drop for ClearBindings<'_, '_> {
+    fn drop(&mut self) {
+        unsafe { ffi::sqlite3_clear_bindings(self.0.stmt) };
+        self.0.static_bindings.set(StaticBindings::Disallowed);
+    }
+}
+
+/// `SQLITE_STATIC` when allowed, `SQLITE_TRANSIENT` otherwise
+fn static_destructor(statement: &Statement) -> ffi::sqlite3_destructor_type {
+    match statement.static_bindings.get() {
+        StaticBindings::Disallowed => transient(),
+        StaticBindings::Allowed => None,
+    }
+}
+
+impl Bindable for Static<&str> {
+    fn bind(&self, statement: &mut Statement, index: &mut c_int) -> Result<()> {
+        let ecode = unsafe {
+            ffi::sqlite3_bind_text(
+                statement.stmt,
+                *index,
+                self.0.as_ptr() as *const c_char,
+                self.0.len() as c_int,
+                static_destructor(statement),
             )
         };
         *index += 1;
         match ecode {
             ffi::SQLITE_OK => Ok(()),
-            other => Err(other.into()),
+            other => Err(statement.database.error(other)),
         }
     }
 }
+impl Bindable for Static<&[u8]> {
+    fn bind(&self, statement: &mut Statement, index: &mut c_int) -> Result<()> {
+        let ecode = unsafe {
+            ffi::sqlite3_bind_blob(
+                statement.stmt,
+                *index,
+                self.0.as_ptr() as *const c_void,
+                self.0.len() as c_int,
+                static_destructor(statement),
+            )
+        };
+        *index += 1;
+        match ecode {
+            ffi::SQLITE_OK => Ok(()),
+            other => Err(statement.database.error(other)),
+        }
+    }
+}
+
+/// Binds a date and time as the integer number of seconds since the Unix epoch,
+/// instead of ISO-8601 text. Available with the `chrono` and `time` features.
+#[cfg(any(feature = "chrono", feature = "time"))]
+#[derive(Debug, Clone, Copy)]
+pub struct UnixEpoch<T>(pub T);
+
+/// Binds every item of an iterator to consecutive parameters,
+/// for parameter lists whose length is only known at runtime
+///
+/// The iterator is cloned on every bind, pass something cheap like `slice.iter()`.
+///
+/// ```
+/// # use rsqlite::*;
+/// use rsqlite::bindable::Each;
+/// # let database = Database::open(":memory:")?;
+/// database.execute("create table user (id integer primary key, name text)", ())?;
+/// database.execute("insert into user values (1, 'amin'), (2, 'bob'), (3, 'negar')", ())?;
+///
+/// let ids = vec![1, 3];
+/// let placeholders = vec!["?"; ids.len()].join(", ");
+/// let sql = format!("select name from user where id in ({}) and name != ?", placeholders);
+/// let names: Vec<String> = database.collect_vec(&sql, (Each(ids.iter()), "bob"))?;
+/// assert!(names == vec!["amin", "negar"]);
+/// # Ok::<(), RsqliteError>(())
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Each<I>(pub I);
+
+impl<I> Bindable for Each<I>
+where
+    I: IntoIterator + Clone,
+    I::Item: Bindable,
+{
+    fn bind(&self, statement: &mut Statement, index: &mut c_int) -> Result<()> {
+        for item in self.0.clone() {
+            item.bind(statement, index)?;
+        }
+        Ok(())
+    }
+}
 
 /// binds a tuple of `(T0, T1 ...)` where each type `T?` is itself `Bindable`
 macro_rules! bindable_tuple {
@@ -0,0 +1,68 @@
+//! Waiting for locks held by other connections instead of failing with SQLITE_BUSY
+use super::{Database, Result};
+use libc::{c_int, c_void};
+use sqlite3_sys as ffi;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::time::Duration;
+
+pub(crate) type BusyHandler = Box<dyn FnMut(i32) -> bool + Send>;
+
+impl Database {
+    /// sleep and retry for up to `timeout` when a table is locked,
+    /// before giving up with `Err(Busy)`. This replaces any `busy_handler`.
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// # let database = Database::open(":memory:")?;
+    /// database.busy_timeout(std::time::Duration::from_secs(5))?;
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn busy_timeout(&self, timeout: Duration) -> Result<()> {
+        let ms = timeout.as_millis().min(c_int::MAX as u128) as c_int;
+        let ecode = unsafe { ffi::sqlite3_busy_timeout(self.db, ms) };
+        match ecode {
+            ffi::SQLITE_OK => {
+                self.busy_handler.set(None);
+                Ok(())
+            }
+            other => Err(self.error(other)),
+        }
+    }
+
+    /// register a callback invoked when a table is locked.
+    ///
+    /// It receives the number of times it was invoked for the same locking event,
+    /// returning `true` retries the operation, `false` gives up with `Err(Busy)`.
+    /// A panic in the handler is caught and also gives up.
+    /// This replaces any `busy_timeout`.
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// # let database = Database::open(":memory:")?;
+    /// database.busy_handler(|attempts| {
+    ///     std::thread::sleep(std::time::Duration::from_millis(10));
+    ///     attempts < 100
+    /// })?;
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn busy_handler<F>(&self, handler: F) -> Result<()>
+    where
+        F: FnMut(i32) -> bool + Send + 'static,
+    {
+        let mut handler: Box<BusyHandler> = Box::new(Box::new(handler));
+        let data = &mut *handler as *mut BusyHandler as *mut c_void;
+        let ecode = unsafe { ffi::sqlite3_busy_handler(self.db, Some(call_busy_handler), data) };
+        match ecode {
+            ffi::SQLITE_OK => {
+                self.busy_handler.set(Some(handler));
+                Ok(())
+            }
+            other => Err(self.error(other)),
+        }
+    }
+}
+
+unsafe extern "C" fn call_busy_handler(data: *mut c_void, count: c_int) -> c_int {
+    let handler = &mut *(data as *mut BusyHandler);
+    catch_unwind(AssertUnwindSafe(|| handler(count))).unwrap_or(false) as c_int
+}
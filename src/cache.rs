@@ -0,0 +1,144 @@
+//! A per-connection LRU cache of prepared statements, see `Database::prepare_cached`
+use super::bindable::StaticBindings;
+use super::{Database, Result, Statement};
+use sqlite3_sys as ffi;
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::ops::{Deref, DerefMut};
+use std::ptr;
+
+/// the number of statements kept by a new connection
+const DEFAULT_CAPACITY: usize = 16;
+
+/// Prepared statements that are not in use, the most recently used first
+pub(crate) struct StatementCache {
+    capacity: usize,
+    statements: VecDeque<(String, *mut ffi::sqlite3_stmt)>,
+}
+
+/// A statement from `Database::prepare_cached`, returned to the cache on drop
+pub struct CachedStatement<'a> {
+    sql: String,
+    statement: Statement<'a>,
+}
+
+impl StatementCache {
+    pub(crate) fn new() -> StatementCache {
+        StatementCache {
+            capacity: DEFAULT_CAPACITY,
+            statements: VecDeque::new(),
+        }
+    }
+
+    fn take(&mut self, sql: &str) -> Option<*mut ffi::sqlite3_stmt> {
+        let index = self
+            .statements
+            .iter()
+            .position(|(cached, _)| cached == sql)?;
+        self.statements.remove(index).map(|(_, stmt)| stmt)
+    }
+
+    fn put(&mut self, sql: String, stmt: *mut ffi::sqlite3_stmt) {
+        self.statements.push_front((sql, stmt));
+        self.evict();
+    }
+
+    fn evict(&mut self) {
+        while self.statements.len() > self.capacity {
+            if let Some((_, stmt)) = self.statements.pop_back() {
+                unsafe { ffi::sqlite3_finalize(stmt) };
+            }
+        }
+    }
+
+    /// finalizes every cached statement
+    pub(crate) fn clear(&mut self) {
+        for (_, stmt) in self.statements.drain(..) {
+            unsafe { ffi::sqlite3_finalize(stmt) };
+        }
+    }
+}
+
+impl Database {
+    /// like `prepare`, but reuses a statement prepared earlier with the same `sql`.
+    ///
+    /// The statement goes back to the cache when dropped, with its bindings cleared.
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// # let database = Database::open(":memory:")?;
+    /// database.execute("create table user(name text)", ())?;
+    /// for name in ["amin", "roosta"] {
+    ///     let mut statement = database.prepare_cached("insert into user values (?)")?;
+    ///     statement.execute(name)?;
+    /// }
+    /// let count: i32 = database.collect("select count(*) from user", ())?;
+    /// assert!(count == 2);
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn prepare_cached(&self, sql: &str) -> Result<CachedStatement<'_>> {
+        let cached = self.statement_cache.borrow_mut().take(sql);
+        let statement = match cached {
+            Some(stmt) => {
+                let statement = Statement {
+                    column_count: unsafe { ffi::sqlite3_column_count(stmt) },
+                    stmt,
+                    database: self,
+                    generation: Cell::new(0),
+                    static_bindings: Cell::new(StaticBindings::Disallowed),
+                };
+                // the read-only guard may have been enabled since it was cached
+                self.check_read_only(&statement)?;
+                statement
+            }
+            None => self.prepare_persistent(sql)?,
+        };
+        Ok(CachedStatement {
+            sql: sql.to_owned(),
+            statement,
+        })
+    }
+
+    /// sets how many unused statements `prepare_cached` keeps, 0 disables the cache
+    pub fn set_statement_cache_capacity(&self, capacity: usize) {
+        let mut cache = self.statement_cache.borrow_mut();
+        cache.capacity = capacity;
+        cache.evict();
+    }
+
+    /// finalizes every statement kept by `prepare_cached`
+    pub fn flush_statement_cache(&self) {
+        self.statement_cache.borrow_mut().clear();
+    }
+}
+
+impl<'a> Deref for CachedStatement<'a> {
+    type Target = Statement<'a>;
+
+    fn deref(&self) -> &Statement<'a> {
+        &self.statement
+    }
+}
+
+impl<'a> DerefMut for CachedStatement<'a> {
+    fn deref_mut(&mut self) -> &mut Statement<'a> {
+        &mut self.statement
+    }
+}
+
+impl<'a> Drop for CachedStatement<'a> {
+    /// resets the statement and hands it back to the cache
+    fn drop(&mut self) {
+        let stmt = std::mem::replace(&mut self.statement.stmt, ptr::null_mut());
+        unsafe {
+            ffi::sqlite3_reset(stmt);
+            ffi::sqlite3_clear_bindings(stmt);
+        }
+        let sql = std::mem::take(&mut self.sql);
+        self.statement
+            .database
+            .statement_cache
+            .borrow_mut()
+            .put(sql, stmt);
+    }
+}
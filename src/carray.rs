@@ -0,0 +1,135 @@
+//! A `carray()` table function, so a slice can be bound as a single parameter of `IN`
+//!
+//! This module is only available with the `carray` feature, which registers the function
+//! on every opened database and makes `&[i64]`, `&[f64]` and `&[&str]` bindable.
+//! Binding copies the slice, the copy lives as long as the binding.
+//!
+//! ```
+//! # use rsqlite::*;
+//! # let database = Database::open(":memory:")?;
+//! database.execute("create table users(id integer, name text)", ())?;
+//! database.execute("insert into users values (1, 'amin'), (2, 'roosta'), (3, 'sqlite')", ())?;
+//!
+//! let ids: Vec<i64> = vec![1, 3];
+//! let names: Vec<String> =
+//!     database.collect_vec("select name from users where id in carray(?)", ids.as_slice())?;
+//! assert!(names == vec!["amin", "sqlite"]);
+//!
+//! let count: i32 = database.collect(
+//!     "select count(*) from users where name in carray(?)",
+//!     &["amin", "roosta"][..],
+//! )?;
+//! assert!(count == 2);
+//! # Ok::<(), RsqliteError>(())
+//! ```
+use super::bindable::Bindable;
+use super::function::ToResult;
+use super::{Database, Result, Statement};
+use libc::{c_char, c_int, c_void};
+use sqlite3_sys as ffi;
+use std::rc::Rc;
+
+/// the pointer type given to `sqlite3_bind_pointer`
+const POINTER_TYPE: &[u8] = b"rsqlite-carray\0";
+
+enum Array {
+    Integers(Vec<i64>),
+    Floats(Vec<f64>),
+    Texts(Vec<String>),
+}
+
+#[derive(Clone)]
+enum Element {
+    Integer(i64),
+    Float(f64),
+    Text(String),
+}
+
+impl ToResult for Element {
+    unsafe fn to_result(self, context: *mut ffi::sqlite3_context) {
+        match self {
+            Element::Integer(value) => value.to_result(context),
+            Element::Float(value) => value.to_result(context),
+            Element::Text(value) => value.to_result(context),
+        }
+    }
+}
+
+struct Elements {
+    array: Rc<Array>,
+    index: usize,
+}
+
+impl Iterator for Elements {
+    type Item = Element;
+
+    fn next(&mut self) -> Option<Element> {
+        let element = match &*self.array {
+            Array::Integers(values) => values.get(self.index).map(|&v| Element::Integer(v)),
+            Array::Floats(values) => values.get(self.index).map(|&v| Element::Float(v)),
+            Array::Texts(values) => values.get(self.index).map(|v| Element::Text(v.clone())),
+        };
+        self.index += 1;
+        element
+    }
+}
+
+unsafe extern "C" fn drop_array(array: *mut c_void) {
+    drop(Rc::from_raw(array as *const Array));
+}
+
+fn bind_array(array: Array, statement: &mut Statement, index: &mut c_int) -> Result<()> {
+    let array = Rc::into_raw(Rc::new(array));
+    // sqlite calls drop_array even if binding fails
+    let ecode = unsafe {
+        ffi::sqlite3_bind_pointer(
+            statement.stmt,
+            *index,
+            array as *mut c_void,
+            POINTER_TYPE.as_ptr() as *const c_char,
+            Some(drop_array),
+        )
+    };
+    *index += 1;
+    match ecode {
+        ffi::SQLITE_OK => Ok(()),
+        other => Err(statement.database.error(other)),
+    }
+}
+
+impl Bindable for &[i64] {
+    fn bind(&self, statement: &mut Statement, index: &mut c_int) -> Result<()> {
+        bind_array(Array::Integers(self.to_vec()), statement, index)
+    }
+}
+impl Bindable for &[f64] {
+    fn bind(&self, statement: &mut Statement, index: &mut c_int) -> Result<()> {
+        bind_array(Array::Floats(self.to_vec()), statement, index)
+    }
+}
+impl Bindable for &[&str] {
+    fn bind(&self, statement: &mut Statement, index: &mut c_int) -> Result<()> {
+        let texts = self.iter().map(|&text| text.to_owned()).collect();
+        bind_array(Array::Texts(texts), statement, index)
+    }
+}
+
+impl Database {
+    /// registers `carray(pointer)` on this connection
+    pub(crate) fn register_carray(&self) -> Result<()> {
+        self.create_table_function("carray", &["value"], &["pointer"], |args| {
+            let array = unsafe {
+                ffi::sqlite3_value_pointer(args.raw(0)?, POINTER_TYPE.as_ptr() as *const c_char)
+            } as *const Array;
+            // a NULL or a pointer of another type is an empty array
+            let array = match array.is_null() {
+                true => Rc::new(Array::Integers(Vec::new())),
+                false => unsafe {
+                    Rc::increment_strong_count(array);
+                    Rc::from_raw(array)
+                },
+            };
+            Ok(Elements { array, index: 0 })
+        })
+    }
+}
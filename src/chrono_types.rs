@@ -0,0 +1,133 @@
+//! `Bindable` and `Collectable` for the `chrono` date and time types
+//!
+//! This module is only available with the `chrono` feature.
+//! Values bind as ISO-8601 text in the format of SQLite's date functions
+//! (`YYYY-MM-DD HH:MM:SS.SSS`, always UTC for `DateTime<Utc>`),
+//! wrap them in `UnixEpoch` to store integer seconds instead.
+//! Collecting accepts both ISO-8601 text and Unix epoch integers.
+//!
+//! ```
+//! # use rsqlite::*;
+//! use chrono::{NaiveDate, TimeZone, Utc};
+//! use rsqlite::bindable::UnixEpoch;
+//! # let database = Database::open(":memory:")?;
+//! let date = NaiveDate::from_ymd_opt(2021, 3, 14).unwrap();
+//! let next_day: NaiveDate = database.collect("select date(?, '+1 day')", date)?;
+//! assert!(next_day == date.succ_opt().unwrap());
+//!
+//! let time = Utc.with_ymd_and_hms(2021, 3, 14, 15, 9, 26).unwrap();
+//! let text: String = database.collect("select ?", time)?;
+//! assert!(text == "2021-03-14 15:09:26");
+//! let epoch: i64 = database.collect("select ?", UnixEpoch(time))?;
+//! assert!(epoch == time.timestamp());
+//! let collected: chrono::DateTime<Utc> = database.collect("select ?", epoch)?;
+//! assert!(collected == time);
+//! # Ok::<(), RsqliteError>(())
+//! ```
+use super::bindable::UnixEpoch;
+use super::datetime::{invalid, stored, Stored};
+use super::{Bindable, Collectable, Result, Statement};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use libc::c_int;
+
+const DATETIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.f";
+const DATE_FORMAT: &str = "%Y-%m-%d";
+const TIME_FORMAT: &str = "%H:%M:%S%.f";
+
+fn parse_datetime(text: &str) -> Result<NaiveDateTime> {
+    let text = text.trim();
+    [
+        DATETIME_FORMAT,
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y-%m-%d %H:%M",
+        "%Y-%m-%dT%H:%M",
+    ]
+    .iter()
+    .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+    .or_else(|| {
+        NaiveDate::parse_from_str(text, DATE_FORMAT)
+            .ok()
+            .map(|date| date.and_time(NaiveTime::MIN))
+    })
+    .ok_or_else(|| invalid(text))
+}
+
+fn from_epoch(seconds: i64) -> Result<DateTime<Utc>> {
+    DateTime::from_timestamp(seconds, 0).ok_or_else(|| invalid(&seconds.to_string()))
+}
+
+impl Bindable for NaiveDateTime {
+    fn bind(&self, statement: &mut Statement, index: &mut c_int) -> Result<()> {
+        self.format(DATETIME_FORMAT)
+            .to_string()
+            .bind(statement, index)
+    }
+}
+impl Bindable for DateTime<Utc> {
+    fn bind(&self, statement: &mut Statement, index: &mut c_int) -> Result<()> {
+        self.naive_utc().bind(statement, index)
+    }
+}
+impl Bindable for NaiveDate {
+    fn bind(&self, statement: &mut Statement, index: &mut c_int) -> Result<()> {
+        self.format(DATE_FORMAT).to_string().bind(statement, index)
+    }
+}
+impl Bindable for NaiveTime {
+    fn bind(&self, statement: &mut Statement, index: &mut c_int) -> Result<()> {
+        self.format(TIME_FORMAT).to_string().bind(statement, index)
+    }
+}
+impl Bindable for UnixEpoch<NaiveDateTime> {
+    fn bind(&self, statement: &mut Statement, index: &mut c_int) -> Result<()> {
+        self.0.and_utc().timestamp().bind(statement, index)
+    }
+}
+impl Bindable for UnixEpoch<DateTime<Utc>> {
+    fn bind(&self, statement: &mut Statement, index: &mut c_int) -> Result<()> {
+        self.0.timestamp().bind(statement, index)
+    }
+}
+
+/// `collect` falls back to the default value (the Unix epoch, or midnight) on invalid input
+macro_rules! collectable_chrono {
+    ($($t:ty => |$stored:ident| $parse:expr),+) => ($(
+        impl Collectable for $t {
+            fn collect(statement: &Statement, column: &mut c_int) -> Self {
+                Self::try_collect(statement, column).unwrap_or_default()
+            }
+            fn try_collect(statement: &Statement, column: &mut c_int) -> Result<Self> {
+                let $stored = stored(statement, column)?;
+                $parse
+            }
+            fn columns_needed() -> c_int {
+                1
+            }
+        }
+    )+);
+}
+
+collectable_chrono!(
+    NaiveDateTime => |stored| match stored {
+        Stored::Text(text) => parse_datetime(&text),
+        Stored::Epoch(seconds) => from_epoch(seconds).map(|datetime| datetime.naive_utc()),
+    },
+    DateTime<Utc> => |stored| match stored {
+        Stored::Text(text) => match DateTime::parse_from_rfc3339(text.trim()) {
+            Ok(datetime) => Ok(datetime.with_timezone(&Utc)),
+            Err(_) => parse_datetime(&text).map(|datetime| datetime.and_utc()),
+        },
+        Stored::Epoch(seconds) => from_epoch(seconds),
+    },
+    NaiveDate => |stored| match stored {
+        Stored::Text(text) => parse_datetime(&text).map(|datetime| datetime.date()),
+        Stored::Epoch(seconds) => from_epoch(seconds).map(|datetime| datetime.date_naive()),
+    },
+    NaiveTime => |stored| match stored {
+        Stored::Text(text) => NaiveTime::parse_from_str(text.trim(), TIME_FORMAT)
+            .or_else(|_| NaiveTime::parse_from_str(text.trim(), "%H:%M"))
+            .or_else(|_| parse_datetime(&text).map(|datetime| datetime.time()).map_err(|_| ()))
+            .map_err(|_| invalid(&text)),
+        Stored::Epoch(seconds) => from_epoch(seconds).map(|datetime| datetime.time()),
+    }
+);
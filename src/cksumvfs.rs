@@ -0,0 +1,58 @@
+//! Page-level checksums via the checksum VFS (cksumvfs)
+//!
+//! This module is only available with the `cksumvfs` feature, and requires the linked
+//! SQLite to be compiled with `ext/misc/cksumvfs.c`.
+//! Call `register()` once, before opening any database that should be checksummed.
+//! Pages that fail verification on read are reported as `Err(ChecksumMismatch)`.
+use super::{Database, Result};
+use core::ptr;
+use libc::{c_char, c_int, c_void};
+use sqlite3_sys as ffi;
+
+extern "C" {
+    fn sqlite3_register_cksumvfs(unused: *const c_char) -> c_int;
+}
+
+/// registers the checksum VFS as the default VFS
+///
+/// ```no_run
+/// # use rsqlite::*;
+/// cksumvfs::register()?;
+/// let database = Database::open("archive.db")?;
+/// database.enable_checksums()?;
+/// # Ok::<(), RsqliteError>(())
+/// ```
+pub fn register() -> Result<()> {
+    let ecode = unsafe { sqlite3_register_cksumvfs(ptr::null()) };
+    match ecode {
+        ffi::SQLITE_OK => Ok(()),
+        other => Err(other.into()),
+    }
+}
+
+impl Database {
+    /// reserves room for checksums in every page of the main database.
+    ///
+    /// New databases get checksums right away, existing ones are rewritten with a `vacuum`.
+    pub fn enable_checksums(&self) -> Result<()> {
+        let mut reserve: c_int = 8;
+        let ecode = unsafe {
+            ffi::sqlite3_file_control(
+                self.db,
+                ptr::null(),
+                ffi::SQLITE_FCNTL_RESERVE_BYTES,
+                &mut reserve as *mut c_int as *mut c_void,
+            )
+        };
+        match ecode {
+            ffi::SQLITE_OK => self.execute("vacuum", ()).map(|_| ()),
+            other => Err(self.error(other)),
+        }
+    }
+
+    /// turn checksum verification on reads on or off, it is on by default.
+    pub fn set_checksum_verification(&self, enabled: bool) -> Result<()> {
+        let sql = format!("pragma checksum_verification = {}", enabled as i32);
+        self.collect(&sql, ())
+    }
+}
@@ -0,0 +1,89 @@
+//! User-defined collating sequences
+use super::{Database, Result};
+use libc::{c_int, c_void};
+use sqlite3_sys as ffi;
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::ffi::CString;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+type Collation = Box<dyn FnMut(&str, &str) -> Ordering + Send>;
+
+impl Database {
+    /// registers a collating sequence, usable with `COLLATE name` in queries and indexes.
+    ///
+    /// Invalid UTF-8 is replaced with `U+FFFD` before comparing.
+    /// A panic in `compare` is caught and the two strings compare as equal.
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// # let database = Database::open(":memory:")?;
+    /// database.create_collation("length", |a, b| a.len().cmp(&b.len()))?;
+    /// let names: Vec<String> = database.collect_vec(
+    ///     "select name from (select 'negar' as name union select 'amin')
+    ///      order by name collate length",
+    ///     (),
+    /// )?;
+    /// assert!(names == vec!["amin", "negar"]);
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn create_collation<F>(&self, name: &str, compare: F) -> Result<()>
+    where
+        F: FnMut(&str, &str) -> Ordering + Send + 'static,
+    {
+        let name = CString::new(name)?;
+        let collation: Box<Collation> = Box::new(Box::new(compare));
+        let data = Box::into_raw(collation) as *mut c_void;
+        // sqlite calls drop_collation when the collation is replaced or the connection closes
+        let ecode = unsafe {
+            ffi::sqlite3_create_collation_v2(
+                self.db,
+                name.as_ptr(),
+                ffi::SQLITE_UTF8,
+                data,
+                Some(call_collation),
+                Some(drop_collation),
+            )
+        };
+        match ecode {
+            ffi::SQLITE_OK => Ok(()),
+            other => {
+                // unlike other interfaces, the destructor is not called when this fails
+                unsafe { drop_collation(data) };
+                Err(self.error(other))
+            }
+        }
+    }
+}
+
+unsafe extern "C" fn call_collation(
+    data: *mut c_void,
+    len_a: c_int,
+    a: *const c_void,
+    len_b: c_int,
+    b: *const c_void,
+) -> c_int {
+    let collation = &mut *(data as *mut Collation);
+    let ordering = catch_unwind(AssertUnwindSafe(|| {
+        collation(&text(a, len_a), &text(b, len_b))
+    }));
+    match ordering.unwrap_or(Ordering::Equal) {
+        Ordering::Less => -1,
+        Ordering::Equal => 0,
+        Ordering::Greater => 1,
+    }
+}
+
+unsafe extern "C" fn drop_collation(data: *mut c_void) {
+    let collation = Box::from_raw(data as *mut Collation);
+    let _ = catch_unwind(AssertUnwindSafe(|| drop(collation)));
+}
+
+unsafe fn text<'a>(ptr: *const c_void, len: c_int) -> Cow<'a, str> {
+    match len == 0 {
+        true => Cow::Borrowed(""),
+        false => {
+            String::from_utf8_lossy(std::slice::from_raw_parts(ptr as *const u8, len as usize))
+        }
+    }
+}
@@ -1,7 +1,9 @@
-use super::{Result, Statement};
+use super::strict::check_type;
+use super::{Result, RsqliteError, Statement};
 
 use libc::{c_double, c_int};
 use sqlite3_sys as ffi;
+use std::convert::TryFrom;
 
 /// Collectable types can be parsed from the columns of the sqlite result row
 pub trait Collectable
@@ -11,12 +13,18 @@ where
     /// collects itself and increments to next column
     fn collect(statement: &Statement, column: &mut c_int) -> Self;
 
+    /// like `collect`, but fails on values that do not fit the type.
+    /// `collect`, `for_each` and `Row` go through this method.
+    fn try_collect(statement: &Statement, column: &mut c_int) -> Result<Self> {
+        Ok(Self::collect(statement, column))
+    }
+
     fn step_and_collect(statement: &mut Statement) -> Result<Self> {
         let retcode = unsafe { ffi::sqlite3_step(statement.stmt) };
 
         match retcode {
-            ffi::SQLITE_ROW => Ok(Self::collect(statement, &mut 0)),
-            other => Err(other.into()),
+            ffi::SQLITE_ROW => Self::try_collect(statement, &mut 0),
+            other => Err(statement.database.error(other)),
         }
     }
 
@@ -44,13 +52,23 @@ where
             _ => Some(T::collect(statement, column)),
         }
     }
+    fn try_collect(statement: &Statement, column: &mut c_int) -> Result<Self> {
+        let sqlite_type = unsafe { ffi::sqlite3_column_type(statement.stmt, *column) };
+        match sqlite_type {
+            ffi::SQLITE_NULL => {
+                *column += 1;
+                Ok(None)
+            }
+            _ => T::try_collect(statement, column).map(Some),
+        }
+    }
     fn step_and_collect(statement: &mut Statement) -> Result<Self> {
         let retcode = unsafe { ffi::sqlite3_step(statement.stmt) };
 
         match retcode {
-            ffi::SQLITE_ROW => Ok(Self::collect(statement, &mut 0)),
+            ffi::SQLITE_ROW => Self::try_collect(statement, &mut 0),
             ffi::SQLITE_DONE => Ok(None),
-            other => Err(other.into()),
+            other => Err(statement.database.error(other)),
         }
     }
     fn columns_needed() -> c_int {
@@ -63,6 +81,100 @@ impl Collectable for c_int {
         *column += 1;
         result
     }
+    fn try_collect(statement: &Statement, column: &mut c_int) -> Result<Self> {
+        try_collect_int(statement, column, "i32")
+    }
+    fn columns_needed() -> c_int {
+        1
+    }
+}
+/// integers other than `i64` are read as `int64`, `try_collect` fails on values
+/// that do not fit while `collect` wraps them like `as`
+///
+/// ```
+/// # use rsqlite::*;
+/// # let database = Database::open(":memory:")?;
+/// let (a, b, c): (u8, i16, u32) = database.collect("select ?, ?, ?", (200u8, -300i16, 4_000_000_000u32))?;
+/// assert!((a, b, c) == (200, -300, 4_000_000_000));
+///
+/// let big: i64 = database.collect("select 1 << 40", ())?;
+/// assert!(big == 1 << 40);
+///
+/// let count: u64 = database.collect("select ?", u64::MAX / 2)?;
+/// assert!(count == u64::MAX / 2);
+///
+/// assert!(database.execute("select ?", u64::MAX).is_err());
+/// assert!(database.collect::<usize>("select -1", ()).is_err());
+/// assert!(database.collect::<u8>("select 300", ()).is_err());
+/// assert!(database.collect::<i32>("select 1 << 40", ()).is_err());
+/// # Ok::<(), RsqliteError>(())
+/// ```
+macro_rules! collectable_checked_int {
+    ($($t:ty),+) => ($(
+        impl Collectable for $t {
+            fn collect(statement: &Statement, column: &mut c_int) -> Self {
+                let result = unsafe { ffi::sqlite3_column_int64(statement.stmt, *column) };
+                *column += 1;
+                result as $t
+            }
+            fn try_collect(statement: &Statement, column: &mut c_int) -> Result<Self> {
+                try_collect_int(statement, column, stringify!($t))
+            }
+            fn columns_needed() -> c_int {
+                1
+            }
+        }
+    )+);
+}
+
+collectable_checked_int!(i8, i16, u8, u16, u32, u64, usize, isize);
+
+/// reads an INTEGER column, which must fit `T`
+fn try_collect_int<T: TryFrom<i64>>(
+    statement: &Statement,
+    column: &mut c_int,
+    target: &str,
+) -> Result<T> {
+    check_type(statement, *column, &[ffi::SQLITE_INTEGER], target)?;
+    let result = unsafe { ffi::sqlite3_column_int64(statement.stmt, *column) };
+    *column += 1;
+    T::try_from(result).map_err(|_| out_of_range(result, target))
+}
+
+/// the error for integers that do not fit the requested type
+pub(crate) fn out_of_range(value: impl std::fmt::Display, target: &str) -> RsqliteError {
+    RsqliteError::new(
+        ffi::SQLITE_MISMATCH,
+        ffi::SQLITE_MISMATCH,
+        format!("{} is out of range for {}", value, target),
+    )
+}
+
+/// any non-zero integer is `true`, NULL is `false`
+impl Collectable for bool {
+    fn collect(statement: &Statement, column: &mut c_int) -> Self {
+        let result = unsafe { ffi::sqlite3_column_int64(statement.stmt, *column) };
+        *column += 1;
+        result != 0
+    }
+    fn try_collect(statement: &Statement, column: &mut c_int) -> Result<Self> {
+        check_type(statement, *column, &[ffi::SQLITE_INTEGER], "bool")?;
+        Ok(Self::collect(statement, column))
+    }
+    fn columns_needed() -> c_int {
+        1
+    }
+}
+impl Collectable for ffi::sqlite3_int64 {
+    fn collect(statement: &Statement, column: &mut c_int) -> Self {
+        let result = unsafe { ffi::sqlite3_column_int64(statement.stmt, *column) };
+        *column += 1;
+        result
+    }
+    fn try_collect(statement: &Statement, column: &mut c_int) -> Result<Self> {
+        check_type(statement, *column, &[ffi::SQLITE_INTEGER], "i64")?;
+        Ok(Self::collect(statement, column))
+    }
     fn columns_needed() -> c_int {
         1
     }
@@ -73,24 +185,81 @@ impl Collectable for c_double {
         *column += 1;
         result
     }
+    fn try_collect(statement: &Statement, column: &mut c_int) -> Result<Self> {
+        let expected = [ffi::SQLITE_INTEGER, ffi::SQLITE_FLOAT];
+        check_type(statement, *column, &expected, "f64")?;
+        Ok(Self::collect(statement, column))
+    }
     fn columns_needed() -> c_int {
         1
     }
 }
+/// the text of `column`, without the terminating NUL
+fn column_text<'s>(statement: &'s Statement, column: c_int) -> &'s [u8] {
+    let ptr = unsafe { ffi::sqlite3_column_text(statement.stmt, column) };
+    let bytes = unsafe { ffi::sqlite3_column_bytes(statement.stmt, column) };
+    match ptr.is_null() || bytes == 0 {
+        true => &[],
+        false => unsafe { std::slice::from_raw_parts(ptr, bytes as usize) },
+    }
+}
+
+/// `try_collect` fails with `Mismatch` on invalid UTF-8 (e.g. a BLOB read as TEXT),
+/// `collect` replaces it with `U+FFFD`. See `Lossy` and `Database::set_unchecked_utf8`.
+///
+/// ```
+/// # use rsqlite::*;
+/// # let database = Database::open(":memory:")?;
+/// assert!(database.collect::<String>("select cast(x'ff' as text)", ()).is_err());
+/// # Ok::<(), RsqliteError>(())
+/// ```
 impl Collectable for String {
     fn collect(statement: &Statement, column: &mut c_int) -> Self {
-        let ptr = unsafe { ffi::sqlite3_column_text(statement.stmt, *column) };
-        let bytes = unsafe { ffi::sqlite3_column_bytes(statement.stmt, *column) };
-
+        let text = column_text(statement, *column);
         *column += 1;
-
-        match bytes == 0 {
-            true => String::new(),
-            false => unsafe {
-                let slice = std::slice::from_raw_parts(ptr as *const u8, bytes as usize);
-                String::from_utf8_unchecked(slice.to_owned())
-            },
+        String::from_utf8_lossy(text).into_owned()
+    }
+    fn try_collect(statement: &Statement, column: &mut c_int) -> Result<Self> {
+        check_type(statement, *column, &[ffi::SQLITE_TEXT], "String")?;
+        let text = column_text(statement, *column).to_owned();
+        *column += 1;
+        if statement.database.unchecked_utf8.get() {
+            // the caller of `set_unchecked_utf8` guarantees the text is valid
+            return Ok(unsafe { String::from_utf8_unchecked(text) });
         }
+        String::from_utf8(text).map_err(|error| {
+            RsqliteError::new(
+                ffi::SQLITE_MISMATCH,
+                ffi::SQLITE_MISMATCH,
+                format!("column {} is not valid UTF-8: {}", *column - 1, error),
+            )
+        })
+    }
+    fn columns_needed() -> c_int {
+        1
+    }
+}
+
+/// Collects text, replacing invalid UTF-8 with `U+FFFD` instead of failing
+///
+/// ```
+/// # use rsqlite::*;
+/// use rsqlite::collectable::Lossy;
+/// # let database = Database::open(":memory:")?;
+/// let Lossy(text) = database.collect("select cast(x'61ff' as text)", ())?;
+/// assert!(text == "a\u{fffd}");
+/// # Ok::<(), RsqliteError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Lossy(pub String);
+
+impl Collectable for Lossy {
+    fn collect(statement: &Statement, column: &mut c_int) -> Self {
+        Lossy(String::collect(statement, column))
+    }
+    fn try_collect(statement: &Statement, column: &mut c_int) -> Result<Self> {
+        check_type(statement, *column, &[ffi::SQLITE_TEXT], "Lossy")?;
+        Ok(Self::collect(statement, column))
     }
     fn columns_needed() -> c_int {
         1
@@ -111,6 +280,10 @@ impl Collectable for Box<[u8]> {
             },
         }
     }
+    fn try_collect(statement: &Statement, column: &mut c_int) -> Result<Self> {
+        check_type(statement, *column, &[ffi::SQLITE_BLOB], "Box<[u8]>")?;
+        Ok(Self::collect(statement, column))
+    }
     fn columns_needed() -> c_int {
         1
     }
@@ -127,6 +300,11 @@ macro_rules! collectable_tuple {
                     $($name::collect(statement, column),)+
                 )
             }
+            fn try_collect(statement: &Statement, column: &mut c_int) -> Result<Self> {
+                Ok((
+                    $($name::try_collect(statement, column)?,)+
+                ))
+            }
             fn columns_needed() -> c_int { $columns_needed }
         }
     );
@@ -0,0 +1,91 @@
+use super::Statement;
+use libc::{c_char, c_int};
+use sqlite3_sys as ffi;
+use std::ffi::CStr;
+
+impl<'a> Statement<'a> {
+    /// number of columns in the result set, 0 for statements like UPDATE
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// # let database = Database::open(":memory:")?;
+    /// database.execute("create table user (name TEXT, age INTEGER)", ())?;
+    /// let statement = database.prepare("select name, age + 1 as next_age from user")?;
+    ///
+    /// assert!(statement.column_count() == 2);
+    /// assert!(statement.column_names() == vec!["name", "next_age"]);
+    /// assert!(statement.column_decltype(0) == Some("TEXT"));
+    /// assert!(statement.column_decltype(1) == None);
+    /// assert!(statement.column_name(2) == None);
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn column_count(&self) -> c_int {
+        self.column_count
+    }
+
+    /// name of the column at `index`, the `AS` alias if there is one
+    pub fn column_name(&self, index: c_int) -> Option<&str> {
+        self.column_str(index, ffi::sqlite3_column_name)
+    }
+
+    /// names of all the columns, in order
+    pub fn column_names(&self) -> Vec<&str> {
+        (0..self.column_count)
+            .map(|index| self.column_name(index).unwrap_or(""))
+            .collect()
+    }
+
+    /// declared type of the column at `index`,
+    /// `None` if it is an expression rather than a table column
+    pub fn column_decltype(&self, index: c_int) -> Option<&str> {
+        self.column_str(index, ffi::sqlite3_column_decltype)
+    }
+
+    /// name of the table column the column at `index` reads, requires `SQLITE_ENABLE_COLUMN_METADATA`
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// # let database = Database::open(":memory:")?;
+    /// database.execute("create table user (name text)", ())?;
+    /// let statement = database.prepare("select name as n, 1 from user")?;
+    ///
+    /// assert!(statement.column_origin_name(0) == Some("name"));
+    /// assert!(statement.column_table_name(0) == Some("user"));
+    /// assert!(statement.column_database_name(0) == Some("main"));
+    /// assert!(statement.column_origin_name(1) == None);
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    #[cfg(feature = "column_metadata")]
+    pub fn column_origin_name(&self, index: c_int) -> Option<&str> {
+        self.column_str(index, ffi::sqlite3_column_origin_name)
+    }
+
+    /// name of the table the column at `index` reads, requires `SQLITE_ENABLE_COLUMN_METADATA`
+    #[cfg(feature = "column_metadata")]
+    pub fn column_table_name(&self, index: c_int) -> Option<&str> {
+        self.column_str(index, ffi::sqlite3_column_table_name)
+    }
+
+    /// name of the database, e.g. `main`, the column at `index` reads,
+    /// requires `SQLITE_ENABLE_COLUMN_METADATA`
+    #[cfg(feature = "column_metadata")]
+    pub fn column_database_name(&self, index: c_int) -> Option<&str> {
+        self.column_str(index, ffi::sqlite3_column_database_name)
+    }
+
+    /// the string returned by `f`, which lives as long as the prepared statement
+    fn column_str(
+        &self,
+        index: c_int,
+        f: unsafe extern "C" fn(*mut ffi::sqlite3_stmt, c_int) -> *const c_char,
+    ) -> Option<&str> {
+        if index < 0 || index >= self.column_count {
+            return None;
+        }
+        let ptr = unsafe { f(self.stmt, index) };
+        match ptr.is_null() {
+            true => None,
+            false => unsafe { CStr::from_ptr(ptr) }.to_str().ok(),
+        }
+    }
+}
@@ -0,0 +1,99 @@
+//! Boolean options of a connection, see `Database::set_db_config`
+use super::{Database, Result};
+use libc::c_int;
+use sqlite3_sys as ffi;
+
+/// The boolean options of `sqlite3_db_config`,
+/// see https://sqlite.org/c3ref/c_dbconfig_defensive.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DbConfig {
+    /// enforces foreign key constraints, like `pragma foreign_keys`
+    EnableFkey,
+    /// runs triggers
+    EnableTrigger,
+    /// allows views
+    EnableView,
+    /// allows the two-argument `fts3_tokenizer()`
+    EnableFts3Tokenizer,
+    /// allows `sqlite3_load_extension`, but not the `load_extension()` SQL function
+    EnableLoadExtension,
+    /// skips the checkpoint and the deletion of the WAL file on close
+    NoCkptOnClose,
+    /// keeps query plans stable across `analyze` and schema changes
+    EnableQpsg,
+    /// includes triggers in `explain query plan`
+    TriggerEqp,
+    /// prepares `vacuum` to reset the database to empty
+    ResetDatabase,
+    /// rejects SQL that could corrupt the database file, like writing to `sqlite_schema`
+    Defensive,
+    /// allows writing to `sqlite_schema`
+    WritableSchema,
+    /// `alter table rename` does not rewrite references in triggers and views
+    LegacyAlterTable,
+    /// accepts double-quoted string literals in DML statements
+    DqsDml,
+    /// accepts double-quoted string literals in DDL statements
+    DqsDdl,
+    /// writes new databases in the legacy file format
+    LegacyFileFormat,
+    /// lets the schema call functions and virtual tables not marked innocuous
+    TrustedSchema,
+}
+
+impl DbConfig {
+    fn as_raw(self) -> c_int {
+        match self {
+            DbConfig::EnableFkey => ffi::SQLITE_DBCONFIG_ENABLE_FKEY,
+            DbConfig::EnableTrigger => ffi::SQLITE_DBCONFIG_ENABLE_TRIGGER,
+            DbConfig::EnableView => ffi::SQLITE_DBCONFIG_ENABLE_VIEW,
+            DbConfig::EnableFts3Tokenizer => ffi::SQLITE_DBCONFIG_ENABLE_FTS3_TOKENIZER,
+            DbConfig::EnableLoadExtension => ffi::SQLITE_DBCONFIG_ENABLE_LOAD_EXTENSION,
+            DbConfig::NoCkptOnClose => ffi::SQLITE_DBCONFIG_NO_CKPT_ON_CLOSE,
+            DbConfig::EnableQpsg => ffi::SQLITE_DBCONFIG_ENABLE_QPSG,
+            DbConfig::TriggerEqp => ffi::SQLITE_DBCONFIG_TRIGGER_EQP,
+            DbConfig::ResetDatabase => ffi::SQLITE_DBCONFIG_RESET_DATABASE,
+            DbConfig::Defensive => ffi::SQLITE_DBCONFIG_DEFENSIVE,
+            DbConfig::WritableSchema => ffi::SQLITE_DBCONFIG_WRITABLE_SCHEMA,
+            DbConfig::LegacyAlterTable => ffi::SQLITE_DBCONFIG_LEGACY_ALTER_TABLE,
+            DbConfig::DqsDml => ffi::SQLITE_DBCONFIG_DQS_DML,
+            DbConfig::DqsDdl => ffi::SQLITE_DBCONFIG_DQS_DDL,
+            DbConfig::LegacyFileFormat => ffi::SQLITE_DBCONFIG_LEGACY_FILE_FORMAT,
+            DbConfig::TrustedSchema => ffi::SQLITE_DBCONFIG_TRUSTED_SCHEMA,
+        }
+    }
+}
+
+impl Database {
+    /// enables or disables `flag`, returning whether it is now enabled
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// use rsqlite::config::DbConfig;
+    /// # let database = Database::open(":memory:")?;
+    /// assert!(!database.set_db_config(DbConfig::DqsDml, false)?);
+    /// assert!(database.collect::<String>("select \"not a column\"", ()).is_err());
+    ///
+    /// assert!(database.set_db_config(DbConfig::EnableFkey, true)?);
+    /// assert!(database.db_config(DbConfig::EnableFkey)?);
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn set_db_config(&self, flag: DbConfig, enabled: bool) -> Result<bool> {
+        self.db_config_raw(flag, enabled as c_int)
+    }
+
+    /// whether `flag` is enabled
+    pub fn db_config(&self, flag: DbConfig) -> Result<bool> {
+        self.db_config_raw(flag, -1)
+    }
+
+    /// `value` is 1 to enable, 0 to disable and -1 to leave the flag unchanged
+    fn db_config_raw(&self, flag: DbConfig, value: c_int) -> Result<bool> {
+        let mut enabled: c_int = 0;
+        let ecode = unsafe { ffi::sqlite3_db_config(self.db, flag.as_raw(), value, &mut enabled) };
+        match ecode {
+            ffi::SQLITE_OK => Ok(enabled != 0),
+            other => Err(self.error(other)),
+        }
+    }
+}
@@ -0,0 +1,30 @@
+//! Reading dates and times, shared by the `chrono` and `time` features
+use super::{Collectable, Result, RsqliteError, Statement};
+use libc::c_int;
+use sqlite3_sys as ffi;
+
+/// a column is either ISO-8601 text or an integer number of seconds since the Unix epoch
+pub(crate) enum Stored {
+    Text(String),
+    Epoch(i64),
+}
+
+pub(crate) fn stored(statement: &Statement, column: &mut c_int) -> Result<Stored> {
+    let sqlite_type = unsafe { ffi::sqlite3_column_type(statement.stmt, *column) };
+    match sqlite_type {
+        ffi::SQLITE_INTEGER => i64::try_collect(statement, column).map(Stored::Epoch),
+        ffi::SQLITE_TEXT => String::try_collect(statement, column).map(Stored::Text),
+        _ => {
+            *column += 1;
+            Err(invalid("a NULL, REAL or BLOB value"))
+        }
+    }
+}
+
+pub(crate) fn invalid(value: &str) -> RsqliteError {
+    RsqliteError::new(
+        ffi::SQLITE_MISMATCH,
+        ffi::SQLITE_MISMATCH,
+        format!("{} is not a valid date or time", value),
+    )
+}
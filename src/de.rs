@@ -0,0 +1,350 @@
+//! Deserializing rows with `serde`
+//!
+//! This module is only available with the `serde` feature.
+//! Structs and maps are filled by column name, tuples and sequences by column position,
+//! anything else from the first column.
+//! NULL deserializes as `None` or `()`, INTEGER as `i64` (or `bool` when asked for one),
+//! REAL as `f64`, TEXT as a string (or a unit enum variant) and BLOB as bytes.
+//!
+//! ```
+//! # use rsqlite::*;
+//! use serde::Deserialize;
+//!
+//! #[derive(Deserialize, Debug, PartialEq)]
+//! struct User {
+//!     name: String,
+//!     age: u8,
+//!     admin: bool,
+//!     weight: Option<f64>,
+//! }
+//!
+//! # let database = Database::open(":memory:")?;
+//! database.execute("create table user(name text, age integer, admin integer, weight real)", ())?;
+//! database.execute("insert into user values ('amin', 29, 1, null)", ())?;
+//!
+//! let users: Vec<User> = database.query_de("select * from user where age > ?", 18)?;
+//! assert!(users == vec![User { name: "amin".to_owned(), age: 29, admin: true, weight: None }]);
+//! # Ok::<(), RsqliteError>(())
+//! ```
+use super::{Bindable, Database, Result, RsqliteError, Statement};
+use libc::c_int;
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, IntoDeserializer, MapAccess, SeqAccess, Visitor,
+};
+use serde::forward_to_deserialize_any;
+use sqlite3_sys as ffi;
+use std::ffi::CStr;
+
+impl de::Error for RsqliteError {
+    fn custom<T: std::fmt::Display>(message: T) -> Self {
+        RsqliteError::new(
+            ffi::SQLITE_MISMATCH,
+            ffi::SQLITE_MISMATCH,
+            message.to_string(),
+        )
+    }
+}
+
+impl Database {
+    /// runs the query and deserializes every row into `T`
+    pub fn query_de<T: DeserializeOwned>(
+        &self,
+        sql: &str,
+        params: impl Bindable,
+    ) -> Result<Vec<T>> {
+        let mut statement = self.prepare(sql)?;
+        statement.query_de(params)
+    }
+}
+
+impl<'a> Statement<'a> {
+    /// runs the statement and deserializes every row into `T`
+    pub fn query_de<T: DeserializeOwned>(&mut self, params: impl Bindable) -> Result<Vec<T>> {
+        self.with_params(params, |statement| {
+            let mut rows = vec![];
+            let result = loop {
+                let retcode = unsafe { ffi::sqlite3_step(statement.stmt) };
+                match retcode {
+                    ffi::SQLITE_ROW => {
+                        let row = RowDeserializer {
+                            stmt: statement.stmt,
+                            columns: statement.column_count,
+                        };
+                        match T::deserialize(row) {
+                            Ok(value) => rows.push(value),
+                            Err(error) => break Err(error),
+                        }
+                    }
+                    ffi::SQLITE_DONE => break Ok(rows),
+                    other => break Err(statement.database.error(other)),
+                }
+            };
+
+            let _ = unsafe { ffi::sqlite3_reset(statement.stmt) };
+            result
+        })
+    }
+}
+
+/// the current row of a statement
+#[derive(Clone, Copy)]
+struct RowDeserializer {
+    stmt: *mut ffi::sqlite3_stmt,
+    columns: c_int,
+}
+
+/// a single column of the current row
+struct ColumnDeserializer {
+    stmt: *mut ffi::sqlite3_stmt,
+    column: c_int,
+}
+
+/// the columns of a row, as a map of names to values or as a sequence of values
+struct Columns {
+    row: RowDeserializer,
+    next: c_int,
+}
+
+impl RowDeserializer {
+    fn first_column(self) -> Result<ColumnDeserializer> {
+        match self.columns {
+            0 => Err(ffi::SQLITE_RANGE.into()),
+            _ => Ok(ColumnDeserializer {
+                stmt: self.stmt,
+                column: 0,
+            }),
+        }
+    }
+
+    fn columns(self) -> Columns {
+        Columns { row: self, next: 0 }
+    }
+}
+
+/// forwards primitive requests to the first column
+macro_rules! forward_to_first_column {
+    ($($method:ident),+) => ($(
+        fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+            self.first_column()?.$method(visitor)
+        }
+    )+);
+}
+
+impl<'de> de::Deserializer<'de> for RowDeserializer {
+    type Error = RsqliteError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_map(self.columns())
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_map(self.columns())
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_map(self.columns())
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_seq(self.columns())
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value> {
+        visitor.visit_seq(self.columns())
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_seq(self.columns())
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        self.first_column()?
+            .deserialize_enum(name, variants, visitor)
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_unit()
+    }
+
+    forward_to_first_column!(
+        deserialize_bool,
+        deserialize_i8,
+        deserialize_i16,
+        deserialize_i32,
+        deserialize_i64,
+        deserialize_i128,
+        deserialize_u8,
+        deserialize_u16,
+        deserialize_u32,
+        deserialize_u64,
+        deserialize_u128,
+        deserialize_f32,
+        deserialize_f64,
+        deserialize_char,
+        deserialize_str,
+        deserialize_string,
+        deserialize_bytes,
+        deserialize_byte_buf,
+        deserialize_option,
+        deserialize_unit,
+        deserialize_identifier,
+        deserialize_ignored_any
+    );
+}
+
+impl<'de> MapAccess<'de> for Columns {
+    type Error = RsqliteError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
+        if self.next >= self.row.columns {
+            return Ok(None);
+        }
+        let name = unsafe { ffi::sqlite3_column_name(self.row.stmt, self.next) };
+        if name.is_null() {
+            return Err(ffi::SQLITE_NOMEM.into());
+        }
+        let name = unsafe { CStr::from_ptr(name) }
+            .to_str()
+            .map_err(|_| <RsqliteError as de::Error>::custom("column name is not valid UTF-8"))?;
+        seed.deserialize(name.into_deserializer()).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+        let column = ColumnDeserializer {
+            stmt: self.row.stmt,
+            column: self.next,
+        };
+        self.next += 1;
+        seed.deserialize(column)
+    }
+}
+
+impl<'de> SeqAccess<'de> for Columns {
+    type Error = RsqliteError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>> {
+        if self.next >= self.row.columns {
+            return Ok(None);
+        }
+        let column = ColumnDeserializer {
+            stmt: self.row.stmt,
+            column: self.next,
+        };
+        self.next += 1;
+        seed.deserialize(column).map(Some)
+    }
+}
+
+impl ColumnDeserializer {
+    fn column_type(&self) -> c_int {
+        unsafe { ffi::sqlite3_column_type(self.stmt, self.column) }
+    }
+
+    fn text(&self) -> Result<String> {
+        let text = unsafe { ffi::sqlite3_column_text(self.stmt, self.column) };
+        let bytes = unsafe { ffi::sqlite3_column_bytes(self.stmt, self.column) };
+        match text.is_null() {
+            true => Ok(String::new()),
+            false => {
+                let slice = unsafe { std::slice::from_raw_parts(text, bytes as usize) };
+                String::from_utf8(slice.to_owned())
+                    .map_err(|_| <RsqliteError as de::Error>::custom("column is not valid UTF-8"))
+            }
+        }
+    }
+
+    fn blob(&self) -> Vec<u8> {
+        let blob = unsafe { ffi::sqlite3_column_blob(self.stmt, self.column) };
+        let bytes = unsafe { ffi::sqlite3_column_bytes(self.stmt, self.column) };
+        match blob.is_null() {
+            true => Vec::new(),
+            false => unsafe {
+                std::slice::from_raw_parts(blob as *const u8, bytes as usize).to_owned()
+            },
+        }
+    }
+}
+
+impl<'de> de::Deserializer<'de> for ColumnDeserializer {
+    type Error = RsqliteError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.column_type() {
+            ffi::SQLITE_NULL => visitor.visit_unit(),
+            ffi::SQLITE_INTEGER => {
+                visitor.visit_i64(unsafe { ffi::sqlite3_column_int64(self.stmt, self.column) })
+            }
+            ffi::SQLITE_FLOAT => {
+                visitor.visit_f64(unsafe { ffi::sqlite3_column_double(self.stmt, self.column) })
+            }
+            ffi::SQLITE_BLOB => visitor.visit_byte_buf(self.blob()),
+            _ => visitor.visit_string(self.text()?),
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.column_type() {
+            ffi::SQLITE_INTEGER => {
+                let value = unsafe { ffi::sqlite3_column_int64(self.stmt, self.column) };
+                visitor.visit_bool(value != 0)
+            }
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.column_type() {
+            ffi::SQLITE_NULL => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_enum(self.text()?.into_deserializer())
+    }
+
+    forward_to_deserialize_any! {
+        i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
@@ -0,0 +1,165 @@
+use super::quote::{quote_identifier, quote_literal};
+use super::{Database, Result, Statement};
+use libc::c_int;
+use sqlite3_sys as ffi;
+use std::fmt::Write as _;
+use std::io::Write;
+
+impl Database {
+    /// writes the main database as an SQL script, like the `.dump` command of the sqlite3 shell
+    ///
+    /// The script creates the tables, inserts their rows, then creates the indexes,
+    /// triggers and views, all in one transaction.
+    /// The shadow tables of virtual tables are skipped, the virtual table recreates them,
+    /// and so are generated columns. Text that is not valid UTF-8 is written as a cast blob.
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// # let database = Database::open(":memory:")?;
+    /// database.execute(
+    ///     "create table user (name text, age int, avatar blob, days int as (age * 365))",
+    ///     (),
+    /// )?;
+    /// database.execute("insert into user values ('it''s me', 29, x'00ff'), (null, 1.5, null)", ())?;
+    /// database.execute("insert into user values (cast(x'ff' as text), 1, null)", ())?;
+    ///
+    /// let mut script = Vec::new();
+    /// database.dump(&mut script)?;
+    /// let script = String::from_utf8(script).unwrap();
+    /// let insert = r#"INSERT INTO "user"("name","age","avatar") VALUES"#;
+    /// assert!(script.contains(&format!("{}('it''s me',29,X'00FF');", insert)));
+    /// assert!(script.contains(&format!("{}(NULL,1.5,NULL);", insert)));
+    /// assert!(script.contains(&format!("{}(CAST(X'FF' AS TEXT),1,NULL);", insert)));
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn dump(&self, mut writer: impl Write) -> Result<()> {
+        let shadow_tables: Vec<String> = self.collect_vec(
+            "select name from pragma_table_list where schema = 'main' and type = 'shadow'",
+            (),
+        )?;
+        let schema: Vec<(String, String, String)> = self.collect_vec(
+            "select type, name, sql from main.sqlite_schema where sql is not null order by rowid",
+            (),
+        )?;
+
+        writer.write_all(b"PRAGMA foreign_keys=OFF;\nBEGIN TRANSACTION;\n")?;
+        for (kind, name, sql) in schema.iter() {
+            if kind != "table" || shadow_tables.contains(name) {
+                continue;
+            }
+            match name.as_str() {
+                "sqlite_sequence" => writer.write_all(b"DELETE FROM sqlite_sequence;\n")?,
+                name if name.starts_with("sqlite_") => continue,
+                _ => writeln!(writer, "{};", sql)?,
+            }
+            if !sql.starts_with("CREATE VIRTUAL TABLE") {
+                self.dump_rows(name, &mut writer)?;
+            }
+        }
+        for (kind, name, sql) in schema.iter() {
+            if kind != "table" && !name.starts_with("sqlite_") {
+                writeln!(writer, "{};", sql)?;
+            }
+        }
+        writer.write_all(b"COMMIT;\n")?;
+        Ok(())
+    }
+
+    fn dump_rows(&self, table: &str, writer: &mut impl Write) -> Result<()> {
+        // hidden and generated columns can not be inserted
+        let columns: Vec<String> = self.collect_vec(
+            "select name from pragma_table_xinfo(?, 'main') where hidden = 0 order by cid",
+            table,
+        )?;
+        let columns = columns
+            .iter()
+            .map(|column| quote_identifier(column))
+            .collect::<Vec<_>>()
+            .join(",");
+        let table = quote_identifier(table);
+        let statement = self.prepare(&format!("select {} from {}", columns, table))?;
+        let mut line = String::new();
+        let result = loop {
+            match unsafe { ffi::sqlite3_step(statement.stmt) } {
+                ffi::SQLITE_ROW => {
+                    line.clear();
+                    let _ = write!(line, "INSERT INTO {}({}) VALUES(", table, columns);
+                    for column in 0..statement.column_count {
+                        if column > 0 {
+                            line.push(',');
+                        }
+                        write_literal(&statement, column, &mut line);
+                    }
+                    line.push_str(");\n");
+                    if let Err(error) = writer.write_all(line.as_bytes()) {
+                        break Err(error.into());
+                    }
+                }
+                ffi::SQLITE_DONE => break Ok(()),
+                other => break Err(self.error(other)),
+            }
+        };
+        unsafe { ffi::sqlite3_reset(statement.stmt) };
+        result
+    }
+}
+
+/// appends the value of `column` as an SQL literal
+fn write_literal(statement: &Statement, column: c_int, line: &mut String) {
+    let stmt = statement.stmt;
+    match unsafe { ffi::sqlite3_column_type(stmt, column) } {
+        ffi::SQLITE_INTEGER => {
+            let _ = write!(line, "{}", unsafe {
+                ffi::sqlite3_column_int64(stmt, column)
+            });
+        }
+        ffi::SQLITE_FLOAT => {
+            let real = unsafe { ffi::sqlite3_column_double(stmt, column) };
+            match real {
+                real if real.is_nan() => line.push_str("NULL"),
+                real if real == f64::INFINITY => line.push_str("1e999"),
+                real if real == f64::NEG_INFINITY => line.push_str("-1e999"),
+                // `Debug` is the shortest text that reads back as the same f64, and keeps the `.0`
+                real => {
+                    let _ = write!(line, "{:?}", real);
+                }
+            }
+        }
+        ffi::SQLITE_TEXT => {
+            let text = unsafe { ffi::sqlite3_column_text(stmt, column) };
+            let bytes = unsafe { ffi::sqlite3_column_bytes(stmt, column) };
+            let text = match text.is_null() {
+                true => &[][..],
+                false => unsafe { std::slice::from_raw_parts(text, bytes as usize) },
+            };
+            match std::str::from_utf8(text) {
+                Ok(text) => line.push_str(&quote_literal(text)),
+                // keeps the exact bytes, a lossy conversion would not read back the same
+                Err(_) => {
+                    line.push_str("CAST(");
+                    write_blob(text, line);
+                    line.push_str(" AS TEXT)");
+                }
+            }
+        }
+        ffi::SQLITE_BLOB => {
+            let blob = unsafe { ffi::sqlite3_column_blob(stmt, column) } as *const u8;
+            let bytes = unsafe { ffi::sqlite3_column_bytes(stmt, column) };
+            let blob = match blob.is_null() {
+                true => &[][..],
+                false => unsafe { std::slice::from_raw_parts(blob, bytes as usize) },
+            };
+            write_blob(blob, line);
+        }
+        _ => line.push_str("NULL"),
+    }
+}
+
+/// appends `blob` as an `X'..'` literal
+fn write_blob(blob: &[u8], line: &mut String) {
+    line.push_str("X'");
+    for byte in blob {
+        let _ = write!(line, "{:02X}", byte);
+    }
+    line.push('\'');
+}
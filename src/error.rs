@@ -1,5 +1,6 @@
 use libc::c_int;
-use std::ffi::NulError;
+use sqlite3_sys as ffi;
+use std::ffi::{CStr, NulError};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -7,145 +8,421 @@ pub enum RsqliteError {
     /// Can not convert the provided String into a CString
     #[error("Can not convert the provided String into a CString - {0}")]
     InvalidCString(#[from] NulError),
+    /// An I/O error while reading or writing outside of sqlite, e.g. by `import::csv`
+    #[error("I/O error - {0}")]
+    Io(#[from] std::io::Error),
+    /// A statement of a script failed, see `Database::execute_script`
+    #[error("statement {statement} at byte {offset} of the script failed - {source}")]
+    Script {
+        /// the index of the failed statement, starting from 0
+        statement: usize,
+        /// the byte offset where the failed statement starts
+        offset: u64,
+        source: Box<RsqliteError>,
+    },
     /// SQLITE_ABORT: An operation was aborted prior to completion
-    #[error("SQLITE_ABORT: An operation was aborted prior to completion.")]
-    Abort,
+    #[error("SQLITE_ABORT: An operation was aborted prior to completion. - {message}")]
+    Abort {
+        extended_code: c_int,
+        message: String,
+    },
     /// SQLITE_AUTH: An SQL statement being prepared is not authorized.
-    #[error("SQLITE_AUTH: An SQL statement being prepared is not authorized.")]
-    Auth,
+    #[error("SQLITE_AUTH: An SQL statement being prepared is not authorized. - {message}")]
+    Auth {
+        extended_code: c_int,
+        message: String,
+    },
     /// SQLITE_BUSY: The database file could not be written (or in some cases read).
-    #[error("SQLITE_BUSY: The database file could not be written (or in some cases read).")]
-    Busy,
+    #[error(
+        "SQLITE_BUSY: The database file could not be written (or in some cases read). - {message}"
+    )]
+    Busy {
+        extended_code: c_int,
+        message: String,
+    },
     /// SQLITE_CANTOPEN: SQLite was unable to open a file.
-    #[error("SQLITE_CANTOPEN: SQLite was unable to open a file.")]
-    CantOpen,
+    #[error("SQLITE_CANTOPEN: SQLite was unable to open a file. - {message}")]
+    CantOpen {
+        extended_code: c_int,
+        message: String,
+    },
     /// SQLITE_CONSTRAINT: An SQL constraint violation occurred.
-    #[error("SQLITE_CONSTRAINT: An SQL constraint violation occurred.")]
-    Constraint,
+    #[error("SQLITE_CONSTRAINT: An SQL constraint violation occurred. - {message}")]
+    Constraint {
+        extended_code: c_int,
+        message: String,
+    },
     /// SQLITE_CORRUPT: The database file has been corrupted.
-    #[error("SQLITE_CORRUPT: The database file has been corrupted.")]
-    Corrupt,
+    #[error("SQLITE_CORRUPT: The database file has been corrupted. - {message}")]
+    Corrupt {
+        extended_code: c_int,
+        message: String,
+    },
     /// SQLITE_ERROR: Sqlite generic error code.
-    #[error("SQLITE_ERROR: Sqlite generic error code.")]
-    Error,
+    #[error("SQLITE_ERROR: Sqlite generic error code. - {message}")]
+    Error {
+        extended_code: c_int,
+        message: String,
+    },
     /// SQLITE_FULL: A write could not complete because the disk is full.
-    #[error("SQLITE_FULL: A write could not complete because the disk is full.")]
-    Full,
+    #[error("SQLITE_FULL: A write could not complete because the disk is full. - {message}")]
+    Full {
+        extended_code: c_int,
+        message: String,
+    },
     /// SQLITE_INTERNAL: An internal malfunction. you should never see this.
-    #[error("SQLITE_INTERNAL: An internal malfunction. you should never see this.")]
-    Internal,
+    #[error("SQLITE_INTERNAL: An internal malfunction. you should never see this. - {message}")]
+    Internal {
+        extended_code: c_int,
+        message: String,
+    },
     /// SQLITE_INTERRUPT: An operation was interrupted.
-    #[error("SQLITE_INTERRUPT: An operation was interrupted.")]
-    Interrupt,
+    #[error("SQLITE_INTERRUPT: An operation was interrupted. - {message}")]
+    Interrupt {
+        extended_code: c_int,
+        message: String,
+    },
     /// SQLITE_IOERR: Operation did not finish because the OS reported an I/O error.
-    #[error("SQLITE_IOERR: Operation could not finish because the OS reported an I/O error.")]
-    IOerr,
+    #[error("SQLITE_IOERR: Operation could not finish because the OS reported an I/O error. - {message}")]
+    IOerr {
+        extended_code: c_int,
+        message: String,
+    },
     /// SQLITE_LOCKED: A write operation could not continue because of a conflict
     /// within the same database connection or a conflict
     /// with a different database connection that uses a shared cache.
     #[error(
         "SQLITE_LOCKED: A write operation could not continue because of a conflict \
-    within the same database connection or a conflict with a different database connection."
+    within the same database connection or a conflict with a different database connection. - {message}"
     )]
-    Locked,
+    Locked {
+        extended_code: c_int,
+        message: String,
+    },
     /// SQLITE_MISMATCH: This error code indicates a datatype mismatch.
-    #[error("SQLITE_MISMATCH: This error code indicates a datatype mismatch.")]
-    Mismatch,
+    #[error("SQLITE_MISMATCH: This error code indicates a datatype mismatch. - {message}")]
+    Mismatch {
+        extended_code: c_int,
+        message: String,
+    },
     /// SQLITE_MISUSE: SQLite interface was used in an undefined or unsupported way.
-    #[error("SQLITE_MISUSE: SQLite interface was used in an undefined or unsupported way.")]
-    Misuse,
+    #[error(
+        "SQLITE_MISUSE: SQLite interface was used in an undefined or unsupported way. - {message}"
+    )]
+    Misuse {
+        extended_code: c_int,
+        message: String,
+    },
     /// SQLITE_NOLFS: System does not support large files,
     /// or the database grew to be larger than what the filesystem can handle.
     #[error(
         "SQLITE_NOLFS: System does not support large files, \
-    or the database grew to be larger than what the filesystem can handle."
+    or the database grew to be larger than what the filesystem can handle. - {message}"
     )]
-    Nolfs,
+    Nolfs {
+        extended_code: c_int,
+        message: String,
+    },
     /// SQLITE_NOMEM: SQLite was unable to allocate all the memory \
     /// it needed to complete the operation.
     #[error(
         "SQLITE_NOMEM: SQLite was unable to allocate all the memory 
-    it needed to complete the operation."
+    it needed to complete the operation. - {message}"
     )]
-    Nomem,
+    Nomem {
+        extended_code: c_int,
+        message: String,
+    },
     /// SQLITE_NOTADB: The file being opened does not appear to be an SQLite database file.
     #[error(
         "SQLITE_NOTADB: The file being opened does not appear to be \
-    an SQLite database file."
+    an SQLite database file. - {message}"
     )]
-    Notadb,
+    Notadb {
+        extended_code: c_int,
+        message: String,
+    },
     /// SQLITE_NOTFOUND: See https://sqlite.org/rescode.html
-    #[error("SQLITE_NOTFOUND: See https://sqlite.org/rescode.html")]
-    Notfound,
+    #[error("SQLITE_NOTFOUND: See https://sqlite.org/rescode.html - {message}")]
+    Notfound {
+        extended_code: c_int,
+        message: String,
+    },
     /// SQLITE_PERM: The requested access mode for a newly created database could not
     /// be provided.
     #[error(
         "SQLITE_PERM: The requested access mode for a \
-    newly created database could not be provided."
+    newly created database could not be provided. - {message}"
     )]
-    Perm,
+    Perm {
+        extended_code: c_int,
+        message: String,
+    },
     /// SQLITE_PROTOCOL: A problem with the file locking protocol used by SQLite.
-    #[error("SQLITE_PROTOCOL: A problem with the file locking protocol used by SQLite.")]
-    Protocol,
+    #[error(
+        "SQLITE_PROTOCOL: A problem with the file locking protocol used by SQLite. - {message}"
+    )]
+    Protocol {
+        extended_code: c_int,
+        message: String,
+    },
     /// SQLITE_RANGE: The parameter number argument to one of the sqlite3_bind routines
     /// or the column number in one of the sqlite3_column routines is out of range.
     #[error(
         "SQLITE_RANGE: The parameter number argument to one of the sqlite3_bind \
-    routines or the column number in one of the sqlite3_column routines is out of range."
+    routines or the column number in one of the sqlite3_column routines is out of range. - {message}"
     )]
-    Range,
+    Range {
+        extended_code: c_int,
+        message: String,
+    },
     /// SQLITE_READONLY: An attempt was made to alter some data for which the current
     /// database connection does not have write permission.
     #[error(
         "SQLITE_READONLY: An attempt was made to alter some data for which the \
-    current database connection does not have write permission."
+    current database connection does not have write permission. - {message}"
     )]
-    Readonly,
+    Readonly {
+        extended_code: c_int,
+        message: String,
+    },
     /// SQLITE_SCHEMA: The database schema was changed by some other process in between
     /// the time that the statement was prepared and the time the statement was run.
     #[error(
         "SQLITE_SCHEMA: The database schema was changed by some other process in \
-    between the time that the statement was prepared and the time the statement was run."
+    between the time that the statement was prepared and the time the statement was run. - {message}"
     )]
-    Schema,
+    Schema {
+        extended_code: c_int,
+        message: String,
+    },
     /// SQLITE_TOOBIG: A string or BLOB was too large.
-    #[error("SQLITE_TOOBIG: A string or BLOB was too large.")]
-    Toobig,
+    #[error("SQLITE_TOOBIG: A string or BLOB was too large. - {message}")]
+    Toobig {
+        extended_code: c_int,
+        message: String,
+    },
+    /// The read-only guard rejected a statement that would modify the database.
+    #[error("The read-only guard rejected a statement that would modify the database.")]
+    ReadOnlyViolation,
+    /// A write was rejected because the database reached the size set by `set_max_size`.
+    #[error("The database reached its maximum size, set by `set_max_size`.")]
+    QuotaExceeded,
+    /// SQLITE_IOERR_DATA: A page failed checksum verification, see the `cksumvfs` feature.
+    #[error("SQLITE_IOERR_DATA: A page failed checksum verification.")]
+    ChecksumMismatch,
+    /// The result set has no column with the given name.
+    #[error("The result set has no column named `{0}`.")]
+    InvalidColumnName(String),
+    /// `collect_one` expected exactly one row, the query returned none.
+    #[error("The query returned no rows, expected exactly one.")]
+    NoRows,
+    /// `collect_one` expected exactly one row, the query returned more.
+    #[error("The query returned more than one row, expected exactly one.")]
+    TooManyRows,
     /// Unknown SQLITE error, See https://sqlite.org/rescode.html
-    #[error("Unknown SQLITE error({0}), See https://sqlite.org/rescode.html")]
-    Unknown(c_int),
+    #[error(
+        "Unknown SQLITE error({extended_code}), See https://sqlite.org/rescode.html - {message}"
+    )]
+    Unknown {
+        extended_code: c_int,
+        message: String,
+    },
 }
 
-impl From<c_int> for RsqliteError {
-    fn from(ecode: c_int) -> Self {
+impl RsqliteError {
+    /// builds the error for a primary or extended result code,
+    /// along with the text returned by `sqlite3_errmsg` or `sqlite3_errstr`.
+    pub fn new(ecode: c_int, extended_code: c_int, message: String) -> Self {
         use RsqliteError::*;
         let primary_error = (ecode & 255) as u8;
         match primary_error {
-            4 => Abort,
-            23 => Auth,
-            5 => Busy,
-            14 => CantOpen,
-            19 => Constraint,
-            11 => Corrupt,
-            1 => Error,
-            13 => Full,
-            2 => Internal,
-            9 => Interrupt,
-            10 => IOerr,
-            6 => Locked,
-            20 => Mismatch,
-            21 => Misuse,
-            22 => Nolfs,
-            7 => Nomem,
-            26 => Notadb,
-            12 => Notfound,
-            3 => Perm,
-            15 => Protocol,
-            25 => Range,
-            8 => Readonly,
-            17 => Schema,
-            18 => Toobig,
-            _ => Unknown(ecode),
+            4 => Abort {
+                extended_code,
+                message,
+            },
+            23 => Auth {
+                extended_code,
+                message,
+            },
+            5 => Busy {
+                extended_code,
+                message,
+            },
+            14 => CantOpen {
+                extended_code,
+                message,
+            },
+            19 => Constraint {
+                extended_code,
+                message,
+            },
+            11 => Corrupt {
+                extended_code,
+                message,
+            },
+            1 => Error {
+                extended_code,
+                message,
+            },
+            13 => Full {
+                extended_code,
+                message,
+            },
+            2 => Internal {
+                extended_code,
+                message,
+            },
+            9 => Interrupt {
+                extended_code,
+                message,
+            },
+            10 => IOerr {
+                extended_code,
+                message,
+            },
+            6 => Locked {
+                extended_code,
+                message,
+            },
+            20 => Mismatch {
+                extended_code,
+                message,
+            },
+            21 => Misuse {
+                extended_code,
+                message,
+            },
+            22 => Nolfs {
+                extended_code,
+                message,
+            },
+            7 => Nomem {
+                extended_code,
+                message,
+            },
+            26 => Notadb {
+                extended_code,
+                message,
+            },
+            12 => Notfound {
+                extended_code,
+                message,
+            },
+            3 => Perm {
+                extended_code,
+                message,
+            },
+            15 => Protocol {
+                extended_code,
+                message,
+            },
+            25 => Range {
+                extended_code,
+                message,
+            },
+            8 => Readonly {
+                extended_code,
+                message,
+            },
+            17 => Schema {
+                extended_code,
+                message,
+            },
+            18 => Toobig {
+                extended_code,
+                message,
+            },
+            _ => Unknown {
+                extended_code,
+                message,
+            },
         }
     }
+
+    /// the extended result code, e.g. `SQLITE_CONSTRAINT_UNIQUE`
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// # let database = Database::open(":memory:")?;
+    /// database.execute("create table user (name text unique)", ())?;
+    /// database.execute("insert into user values ('amin')", ())?;
+    ///
+    /// let error = database.execute("insert into user values ('amin')", ()).unwrap_err();
+    /// assert!(error.extended_code() == Some(ffi::SQLITE_CONSTRAINT_UNIQUE));
+    /// assert!(error.message() == Some("UNIQUE constraint failed: user.name"));
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn extended_code(&self) -> Option<c_int> {
+        use RsqliteError::*;
+        match self {
+            Abort { extended_code, .. } => Some(*extended_code),
+            Auth { extended_code, .. } => Some(*extended_code),
+            Busy { extended_code, .. } => Some(*extended_code),
+            CantOpen { extended_code, .. } => Some(*extended_code),
+            Constraint { extended_code, .. } => Some(*extended_code),
+            Corrupt { extended_code, .. } => Some(*extended_code),
+            Error { extended_code, .. } => Some(*extended_code),
+            Full { extended_code, .. } => Some(*extended_code),
+            Internal { extended_code, .. } => Some(*extended_code),
+            Interrupt { extended_code, .. } => Some(*extended_code),
+            IOerr { extended_code, .. } => Some(*extended_code),
+            Locked { extended_code, .. } => Some(*extended_code),
+            Mismatch { extended_code, .. } => Some(*extended_code),
+            Misuse { extended_code, .. } => Some(*extended_code),
+            Nolfs { extended_code, .. } => Some(*extended_code),
+            Nomem { extended_code, .. } => Some(*extended_code),
+            Notadb { extended_code, .. } => Some(*extended_code),
+            Notfound { extended_code, .. } => Some(*extended_code),
+            Perm { extended_code, .. } => Some(*extended_code),
+            Protocol { extended_code, .. } => Some(*extended_code),
+            Range { extended_code, .. } => Some(*extended_code),
+            Readonly { extended_code, .. } => Some(*extended_code),
+            Schema { extended_code, .. } => Some(*extended_code),
+            Toobig { extended_code, .. } => Some(*extended_code),
+            Unknown { extended_code, .. } => Some(*extended_code),
+            _ => None,
+        }
+    }
+
+    /// the error message reported by sqlite
+    pub fn message(&self) -> Option<&str> {
+        use RsqliteError::*;
+        match self {
+            Abort { message, .. } => Some(message),
+            Auth { message, .. } => Some(message),
+            Busy { message, .. } => Some(message),
+            CantOpen { message, .. } => Some(message),
+            Constraint { message, .. } => Some(message),
+            Corrupt { message, .. } => Some(message),
+            Error { message, .. } => Some(message),
+            Full { message, .. } => Some(message),
+            Internal { message, .. } => Some(message),
+            Interrupt { message, .. } => Some(message),
+            IOerr { message, .. } => Some(message),
+            Locked { message, .. } => Some(message),
+            Mismatch { message, .. } => Some(message),
+            Misuse { message, .. } => Some(message),
+            Nolfs { message, .. } => Some(message),
+            Nomem { message, .. } => Some(message),
+            Notadb { message, .. } => Some(message),
+            Notfound { message, .. } => Some(message),
+            Perm { message, .. } => Some(message),
+            Protocol { message, .. } => Some(message),
+            Range { message, .. } => Some(message),
+            Readonly { message, .. } => Some(message),
+            Schema { message, .. } => Some(message),
+            Toobig { message, .. } => Some(message),
+            Unknown { message, .. } => Some(message),
+            _ => None,
+        }
+    }
+}
+
+impl From<c_int> for RsqliteError {
+    /// an error without a connection at hand, described by `sqlite3_errstr`
+    fn from(ecode: c_int) -> Self {
+        let message = unsafe { CStr::from_ptr(ffi::sqlite3_errstr(ecode)) };
+        RsqliteError::new(ecode, ecode, message.to_string_lossy().into_owned())
+    }
 }
@@ -0,0 +1,562 @@
+//! Full-text search with FTS5, see the `fts5` feature and https://sqlite.org/fts5.html
+use super::function::ToResult;
+use super::quote::{quote_identifier, quote_literal};
+use super::vtab::{catch_panic, Values};
+use super::{Database, Result, RsqliteError};
+use libc::{c_char, c_int, c_void};
+use sqlite3_sys as ffi;
+use std::ffi::CString;
+use std::marker::PhantomData;
+use std::ptr;
+
+/// Describes an FTS5 table, created with `create`
+///
+/// ```
+/// # use rsqlite::*;
+/// use rsqlite::fts::{self, Fts5Table, Snippet};
+///
+/// # let database = Database::open(":memory:")?;
+/// database.execute("create table post (id integer primary key, title text, body text)", ())?;
+/// database.execute("insert into post values (1, 'sqlite', 'small, fast and reliable')", ())?;
+///
+/// let search = Fts5Table::new("post_search", &["title", "body"])
+///     .external_content("post", "id")
+///     .tokenize("porter unicode61");
+/// search.create(&database)?;
+/// database.execute("insert into post values (2, 'rust', 'fast and reliable software')", ())?;
+/// database.execute("delete from post where id = 1", ())?;
+///
+/// let snippet = Snippet::new().markers("[", "]");
+/// let hits = fts::search(&database, "post_search", "reliable", &snippet, 10)?;
+/// assert!(hits.len() == 1);
+/// assert!(hits[0].0 == 2 && hits[0].2 == "fast and [reliable] software");
+/// # Ok::<(), RsqliteError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Fts5Table {
+    name: String,
+    columns: Vec<String>,
+    content: Option<(String, String)>,
+    tokenize: Option<String>,
+    prefix: Vec<u32>,
+}
+
+/// How `search` marks the matched terms of each hit
+#[derive(Debug, Clone)]
+pub struct Snippet {
+    column: i32,
+    tokens: Option<u32>,
+    open: String,
+    close: String,
+    ellipsis: String,
+}
+
+impl Fts5Table {
+    /// a table named `name` indexing `columns`
+    pub fn new(name: &str, columns: &[&str]) -> Fts5Table {
+        Fts5Table {
+            name: name.to_owned(),
+            columns: columns.iter().map(|&column| column.to_owned()).collect(),
+            content: None,
+            tokenize: None,
+            prefix: Vec::new(),
+        }
+    }
+
+    /// indexes the rows of `table` instead of storing a copy of them, `rowid` is its
+    /// INTEGER PRIMARY KEY column. `create` adds triggers keeping the index in sync.
+    pub fn external_content(mut self, table: &str, rowid: &str) -> Fts5Table {
+        self.content = Some((table.to_owned(), rowid.to_owned()));
+        self
+    }
+
+    /// the tokenizer and its arguments, like `porter unicode61 remove_diacritics 2`
+    pub fn tokenize(mut self, tokenize: &str) -> Fts5Table {
+        self.tokenize = Some(tokenize.to_owned());
+        self
+    }
+
+    /// builds prefix indexes for terms of these lengths, to speed up `term*` queries
+    pub fn prefix(mut self, lengths: &[u32]) -> Fts5Table {
+        self.prefix = lengths.to_vec();
+        self
+    }
+
+    /// creates the table if it does not exist. For external content a new table also gets
+    /// the sync triggers and an index of the rows already in the content table
+    pub fn create(&self, database: &Database) -> Result<()> {
+        let mut arguments: Vec<String> = self
+            .columns
+            .iter()
+            .map(|column| quote_identifier(column))
+            .collect();
+        if let Some((table, rowid)) = &self.content {
+            arguments.push(format!("content={}", quote_literal(table)));
+            arguments.push(format!("content_rowid={}", quote_literal(rowid)));
+        }
+        if let Some(tokenize) = &self.tokenize {
+            arguments.push(format!("tokenize={}", quote_literal(tokenize)));
+        }
+        if !self.prefix.is_empty() {
+            let lengths: Vec<String> = self.prefix.iter().map(u32::to_string).collect();
+            arguments.push(format!("prefix={}", quote_literal(&lengths.join(" "))));
+        }
+        let sql = format!(
+            "create virtual table if not exists {} using fts5({})",
+            quote_identifier(&self.name),
+            arguments.join(", ")
+        );
+        let existed: bool = database.collect(
+            "select count(*) > 0 from sqlite_master where type = 'table' and name = ?",
+            self.name.as_str(),
+        )?;
+        database.execute(&sql, ())?;
+        if self.content.is_some() && !existed {
+            database.execute_batch(&self.triggers())?;
+            self.rebuild(database)?;
+        }
+        Ok(())
+    }
+
+    /// rebuilds the whole index from the content table
+    pub fn rebuild(&self, database: &Database) -> Result<()> {
+        let name = quote_identifier(&self.name);
+        let sql = format!("insert into {}({}) values ('rebuild')", name, name);
+        database.execute(&sql, ()).map(|_| ())
+    }
+
+    /// the triggers forwarding the changes of the content table to the index
+    fn triggers(&self) -> String {
+        let (table, rowid) = match &self.content {
+            Some(content) => content,
+            None => return String::new(),
+        };
+        let name = quote_identifier(&self.name);
+        let columns: Vec<String> = self
+            .columns
+            .iter()
+            .map(|column| quote_identifier(column))
+            .collect();
+        let values = |row: &str| {
+            let mut values = vec![format!("{}.{}", row, quote_identifier(rowid))];
+            values.extend(columns.iter().map(|column| format!("{}.{}", row, column)));
+            values.join(", ")
+        };
+        let insert = format!(
+            "insert into {}(rowid, {}) values ({});",
+            name,
+            columns.join(", "),
+            values("new")
+        );
+        let delete = format!(
+            "insert into {}({}, rowid, {}) values ('delete', {});",
+            name,
+            name,
+            columns.join(", "),
+            values("old")
+        );
+        let trigger = |suffix: &str, event: &str, body: &str| {
+            format!(
+                "create trigger if not exists {} after {} on {} begin {} end;\n",
+                quote_identifier(&format!("{}_{}", self.name, suffix)),
+                event,
+                quote_identifier(table),
+                body
+            )
+        };
+        trigger("ai", "insert", &insert)
+            + &trigger("ad", "delete", &delete)
+            + &trigger("au", "update", &format!("{} {}", delete, insert))
+    }
+}
+
+impl Default for Snippet {
+    fn default() -> Snippet {
+        Snippet {
+            column: -1,
+            tokens: Some(16),
+            open: "<b>".to_owned(),
+            close: "</b>".to_owned(),
+            ellipsis: "...".to_owned(),
+        }
+    }
+}
+
+impl Snippet {
+    /// a snippet of up to 16 tokens from the best matching column, terms marked with `<b>` and `</b>`
+    pub fn new() -> Snippet {
+        Snippet::default()
+    }
+
+    /// takes the snippet from `column`, by index
+    pub fn column(mut self, column: i32) -> Snippet {
+        self.column = column;
+        self
+    }
+
+    /// the maximum number of tokens in the snippet, at most 64
+    pub fn tokens(mut self, tokens: u32) -> Snippet {
+        self.tokens = Some(tokens);
+        self
+    }
+
+    /// returns the whole text of `column` with the terms marked, instead of a snippet
+    pub fn highlight(mut self, column: i32) -> Snippet {
+        self.column = column;
+        self.tokens = None;
+        self
+    }
+
+    /// the text inserted before and after each matched term
+    pub fn markers(mut self, open: &str, close: &str) -> Snippet {
+        self.open = open.to_owned();
+        self.close = close.to_owned();
+        self
+    }
+
+    /// the text marking where a snippet was cut
+    pub fn ellipsis(mut self, ellipsis: &str) -> Snippet {
+        self.ellipsis = ellipsis.to_owned();
+        self
+    }
+}
+
+/// runs a `MATCH` query against the FTS5 table `table`,
+/// returning the `(rowid, rank, snippet)` of the best `limit` hits, best first
+///
+/// The rank is the bm25 score, lower is better.
+///
+/// ```
+/// # use rsqlite::*;
+/// use rsqlite::fts::{self, Fts5Table, Snippet};
+///
+/// # let database = Database::open(":memory:")?;
+/// Fts5Table::new("note", &["text"]).create(&database)?;
+/// database.execute("insert into note values ('one fish'), ('red fish, blue fish')", ())?;
+///
+/// let hits = fts::search(&database, "note", "fish", &Snippet::new().highlight(0), 10)?;
+/// assert!(hits[0] == (2, hits[0].1, "red <b>fish</b>, blue <b>fish</b>".to_owned()));
+/// assert!(hits[0].1 < hits[1].1);
+/// # Ok::<(), RsqliteError>(())
+/// ```
+pub fn search(
+    database: &Database,
+    table: &str,
+    query: &str,
+    snippet: &Snippet,
+    limit: u32,
+) -> Result<Vec<(i64, f64, String)>> {
+    let table = quote_identifier(table);
+    match snippet.tokens {
+        Some(tokens) => database.collect_vec(
+            &format!(
+                "select rowid, rank, snippet({}, ?, ?, ?, ?, ?) from {} where {} match ? order by rank limit ?",
+                table, table, table
+            ),
+            (
+                snippet.column,
+                snippet.open.as_str(),
+                snippet.close.as_str(),
+                snippet.ellipsis.as_str(),
+                tokens,
+                query,
+                limit,
+            ),
+        ),
+        None => database.collect_vec(
+            &format!(
+                "select rowid, rank, highlight({}, ?, ?, ?) from {} where {} match ? order by rank limit ?",
+                table, table, table
+            ),
+            (
+                snippet.column,
+                snippet.open.as_str(),
+                snippet.close.as_str(),
+                query,
+                limit,
+            ),
+        ),
+    }
+}
+
+/// The current row and query of an FTS5 auxiliary function, see `create_fts5_function`
+pub struct AuxContext<'a> {
+    api: &'a ffi::Fts5ExtensionApi,
+    fts: *mut ffi::Fts5Context,
+    _marker: PhantomData<&'a mut ffi::Fts5Context>,
+}
+
+/// A match of a query phrase in the current row, see `AuxContext::instance`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Instance {
+    /// the phrase of the query, by index
+    pub phrase: usize,
+    /// the column it matched in
+    pub column: usize,
+    /// the token offset of the match within the column
+    pub offset: usize,
+}
+
+type AuxFunction = Box<dyn Fn(&AuxContext, &Values) -> Result<Box<dyn ToResultBoxed>> + Send>;
+
+impl<'a> AuxContext<'a> {
+    /// the rowid of the current row
+    pub fn rowid(&self) -> Result<i64> {
+        let rowid = available(self.api.xRowid)?;
+        Ok(unsafe { rowid(self.fts) })
+    }
+
+    /// the number of columns of the table
+    pub fn column_count(&self) -> Result<usize> {
+        let column_count = available(self.api.xColumnCount)?;
+        Ok(unsafe { column_count(self.fts) as usize })
+    }
+
+    /// the number of rows in the table
+    pub fn row_count(&self) -> Result<i64> {
+        let row_count = available(self.api.xRowCount)?;
+        let mut count = 0;
+        check(unsafe { row_count(self.fts, &mut count) }).map(|_| count)
+    }
+
+    /// the text of `column` in the current row
+    pub fn column_text(&self, column: usize) -> Result<String> {
+        let column_text = available(self.api.xColumnText)?;
+        let mut text: *const c_char = ptr::null();
+        let mut bytes = 0;
+        check(unsafe { column_text(self.fts, column as c_int, &mut text, &mut bytes) })?;
+        Ok(match text.is_null() {
+            true => String::new(),
+            false => {
+                let text = unsafe { std::slice::from_raw_parts(text as *const u8, bytes as usize) };
+                String::from_utf8_lossy(text).into_owned()
+            }
+        })
+    }
+
+    /// the number of tokens in `column` of the current row, or in every column if `None`
+    pub fn column_size(&self, column: Option<usize>) -> Result<i64> {
+        let column_size = available(self.api.xColumnSize)?;
+        let column = column.map_or(-1, |column| column as c_int);
+        let mut size = 0;
+        check(unsafe { column_size(self.fts, column, &mut size) }).map(|_| size as i64)
+    }
+
+    /// the number of tokens in `column` of every row, or in every column if `None`
+    pub fn column_total_size(&self, column: Option<usize>) -> Result<i64> {
+        let column_total_size = available(self.api.xColumnTotalSize)?;
+        let column = column.map_or(-1, |column| column as c_int);
+        let mut size = 0;
+        check(unsafe { column_total_size(self.fts, column, &mut size) }).map(|_| size)
+    }
+
+    /// the number of phrases in the query
+    pub fn phrase_count(&self) -> Result<usize> {
+        let phrase_count = available(self.api.xPhraseCount)?;
+        Ok(unsafe { phrase_count(self.fts) as usize })
+    }
+
+    /// the number of tokens in `phrase`
+    pub fn phrase_size(&self, phrase: usize) -> Result<usize> {
+        let phrase_size = available(self.api.xPhraseSize)?;
+        Ok(unsafe { phrase_size(self.fts, phrase as c_int).max(0) as usize })
+    }
+
+    /// the number of phrase matches in the current row
+    pub fn instance_count(&self) -> Result<usize> {
+        let instance_count = available(self.api.xInstCount)?;
+        let mut count = 0;
+        check(unsafe { instance_count(self.fts, &mut count) }).map(|_| count as usize)
+    }
+
+    /// the phrase match at `index`, below `instance_count`
+    pub fn instance(&self, index: usize) -> Result<Instance> {
+        let instance = available(self.api.xInst)?;
+        let (mut phrase, mut column, mut offset) = (0, 0, 0);
+        let ecode = unsafe {
+            instance(
+                self.fts,
+                index as c_int,
+                &mut phrase,
+                &mut column,
+                &mut offset,
+            )
+        };
+        check(ecode).map(|_| Instance {
+            phrase: phrase as usize,
+            column: column as usize,
+            offset: offset as usize,
+        })
+    }
+}
+
+fn check(ecode: c_int) -> Result<()> {
+    match ecode {
+        ffi::SQLITE_OK => Ok(()),
+        other => Err(other.into()),
+    }
+}
+
+/// a function of the FTS5 API, missing ones are an error rather than a crash
+fn available<F>(function: Option<F>) -> Result<F> {
+    function.ok_or_else(|| {
+        RsqliteError::new(
+            ffi::SQLITE_ERROR,
+            ffi::SQLITE_ERROR,
+            "the FTS5 API lacks a function".to_owned(),
+        )
+    })
+}
+
+/// lets `AuxFunction` return any `ToResult` through a trait object
+trait ToResultBoxed {
+    unsafe fn to_result_boxed(self: Box<Self>, context: *mut ffi::sqlite3_context);
+}
+
+impl<T: ToResult> ToResultBoxed for T {
+    unsafe fn to_result_boxed(self: Box<Self>, context: *mut ffi::sqlite3_context) {
+        (*self).to_result(context)
+    }
+}
+
+impl Database {
+    /// registers an FTS5 auxiliary function, like a custom rank function or highlighter
+    ///
+    /// It is called for every matched row with the `AuxContext` of the row,
+    /// and the arguments following the table name in SQL.
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// use rsqlite::fts::Fts5Table;
+    ///
+    /// # let database = Database::open(":memory:")?;
+    /// Fts5Table::new("note", &["title", "text"]).create(&database)?;
+    /// database.execute("insert into note values ('fish', 'one fish'), ('birds', 'red fish, blue fish')", ())?;
+    ///
+    /// // counts the matches, those in the title weighted by the first argument
+    /// database.create_fts5_function("weighted_hits", |context, args| {
+    ///     let title_weight: f64 = args.get(0)?;
+    ///     let mut score = 0.0;
+    ///     for index in 0..context.instance_count()? {
+    ///         score += match context.instance(index)?.column {
+    ///             0 => title_weight,
+    ///             _ => 1.0,
+    ///         };
+    ///     }
+    ///     Ok(score)
+    /// })?;
+    ///
+    /// let sql = "select rowid, weighted_hits(note, ?) as score from note
+    ///            where note match 'fish' order by score desc";
+    /// let scores: Vec<(i64, f64)> = database.collect_vec(sql, 10.0)?;
+    /// assert!(scores == vec![(1, 11.0), (2, 2.0)]);
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn create_fts5_function<F, R>(&self, name: &str, function: F) -> Result<()>
+    where
+        F: Fn(&AuxContext, &Values) -> Result<R> + Send + 'static,
+        R: ToResult + 'static,
+    {
+        let api = self.fts5_api()?;
+        // checked once here, the version 2 api is the one the bindings describe
+        let create_function = match unsafe { ((*api).iVersion, (*api).xCreateFunction) } {
+            (version, Some(create_function)) if version >= 2 => create_function,
+            _ => {
+                return Err(RsqliteError::new(
+                    ffi::SQLITE_ERROR,
+                    ffi::SQLITE_ERROR,
+                    "unsupported FTS5 API version".to_owned(),
+                ))
+            }
+        };
+        let name = CString::new(name)?;
+        let function: AuxFunction = Box::new(move |context, args| {
+            function(context, args).map(|result| Box::new(result) as Box<dyn ToResultBoxed>)
+        });
+        let data = Box::into_raw(Box::new(function)) as *mut c_void;
+        let ecode = unsafe {
+            create_function(
+                api,
+                name.as_ptr(),
+                data,
+                Some(call_aux_function),
+                Some(drop_aux_function),
+            )
+        };
+        match ecode {
+            ffi::SQLITE_OK => Ok(()),
+            other => {
+                // fts5 only takes ownership once the function is registered
+                unsafe { drop_aux_function(data) };
+                Err(other.into())
+            }
+        }
+    }
+
+    /// the `fts5_api` of this connection, see https://sqlite.org/fts5.html#extending_fts5
+    fn fts5_api(&self) -> Result<*mut ffi::fts5_api> {
+        let mut api: *mut ffi::fts5_api = ptr::null_mut();
+        let statement = self.prepare("select fts5(?)")?;
+        let ecode = unsafe {
+            ffi::sqlite3_bind_pointer(
+                statement.stmt,
+                1,
+                &mut api as *mut *mut ffi::fts5_api as *mut c_void,
+                b"fts5_api_ptr\0".as_ptr() as *const c_char,
+                None,
+            )
+        };
+        if ecode != ffi::SQLITE_OK {
+            return Err(self.error(ecode));
+        }
+        match unsafe { ffi::sqlite3_step(statement.stmt) } {
+            ffi::SQLITE_ROW | ffi::SQLITE_DONE => {}
+            other => return Err(self.error(other)),
+        }
+        match api.is_null() {
+            false => Ok(api),
+            true => Err(RsqliteError::new(
+                ffi::SQLITE_ERROR,
+                ffi::SQLITE_ERROR,
+                "FTS5 is not available".to_owned(),
+            )),
+        }
+    }
+}
+
+unsafe extern "C" fn call_aux_function(
+    api: *const ffi::Fts5ExtensionApi,
+    fts: *mut ffi::Fts5Context,
+    context: *mut ffi::sqlite3_context,
+    argc: c_int,
+    argv: *mut *mut ffi::sqlite3_value,
+) {
+    let aux = AuxContext {
+        api: &*api,
+        fts,
+        _marker: PhantomData,
+    };
+    let values = match argc {
+        0 => &[][..],
+        _ => std::slice::from_raw_parts(argv, argc as usize),
+    };
+    let result = available((*api).xUserData).and_then(|user_data| {
+        let function = &*(user_data(fts) as *const AuxFunction);
+        catch_panic(|| function(&aux, &Values { values }))
+    });
+    match result {
+        Ok(result) => result.to_result_boxed(context),
+        Err(error) => {
+            let message = error.to_string();
+            ffi::sqlite3_result_error(
+                context,
+                message.as_ptr() as *const c_char,
+                message.len() as c_int,
+            );
+        }
+    }
+}
+
+unsafe extern "C" fn drop_aux_function(data: *mut c_void) {
+    drop(Box::from_raw(data as *mut AuxFunction));
+}
@@ -0,0 +1,391 @@
+//! User-defined aggregate and window functions
+//!
+//! Arguments are read with `FromValue` and results are returned with `ToResult`,
+//! each function receives its arguments as a tuple, e.g. `(f64,)` or `(String, i64)`.
+use super::bindable::transient;
+use super::vtab::catch_panic;
+use super::{Database, Result, RsqliteError};
+use libc::{c_char, c_int, c_void};
+use sqlite3_sys as ffi;
+use std::ffi::CString;
+
+/// Types that can be read from an argument passed to a user-defined function
+pub trait FromValue: Sized {
+    /// # Safety
+    /// `value` must be a valid `sqlite3_value` pointer
+    unsafe fn from_value(value: *mut ffi::sqlite3_value) -> Self;
+}
+
+/// Argument lists of user-defined functions, implemented for tuples of `FromValue`
+pub trait FromArgs: Sized {
+    /// number of arguments the function takes
+    fn arg_count() -> c_int;
+
+    /// # Safety
+    /// `args` must hold `arg_count()` valid `sqlite3_value` pointers
+    unsafe fn from_args(args: &[*mut ffi::sqlite3_value]) -> Self;
+}
+
+/// Types that can be returned from a user-defined function
+pub trait ToResult {
+    /// # Safety
+    /// `context` must be a valid `sqlite3_context` pointer
+    unsafe fn to_result(self, context: *mut ffi::sqlite3_context);
+}
+
+/// An aggregate function, a fresh `Default` instance is created for each group
+pub trait Aggregate: Default {
+    type Args: FromArgs;
+    type Output: ToResult;
+
+    /// adds a row to the aggregate
+    fn step(&mut self, args: Self::Args);
+
+    /// the current value of the aggregate
+    fn value(&self) -> Self::Output;
+}
+
+/// An aggregate function that can also be used as a window function
+pub trait WindowFunction: Aggregate {
+    /// removes a row that left the window, undoing a previous `step`
+    fn inverse(&mut self, args: Self::Args);
+}
+
+impl Database {
+    /// registers an aggregate function
+    ///
+    /// A panic in `step` or `value` fails the query with an error instead of unwinding into SQLite.
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// use rsqlite::function::Aggregate;
+    ///
+    /// #[derive(Default)]
+    /// struct Product(f64);
+    ///
+    /// impl Aggregate for Product {
+    ///     type Args = (f64,);
+    ///     type Output = f64;
+    ///     fn step(&mut self, (x,): (f64,)) {
+    ///         assert!(x >= 0.0, "negative factor");
+    ///         self.0 = if self.0 == 0.0 { x } else { self.0 * x };
+    ///     }
+    ///     fn value(&self) -> f64 {
+    ///         self.0
+    ///     }
+    /// }
+    ///
+    /// # let database = Database::open(":memory:")?;
+    /// database.create_aggregate::<Product>("product")?;
+    /// let product: f64 = database.collect("select product(x) from (select 2 as x union select 3)", ())?;
+    /// assert!(product == 6.0);
+    /// assert!(database.collect::<f64>("select product(-1)", ()).is_err());
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn create_aggregate<A>(&self, name: &str) -> Result<()>
+    where
+        A: Aggregate,
+    {
+        self.create_window_function_v2::<A>(name, None, None)
+    }
+
+    /// registers a window function, it can also be used as a plain aggregate
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// use rsqlite::function::{Aggregate, WindowFunction};
+    ///
+    /// #[derive(Default)]
+    /// struct MovingSum(f64);
+    ///
+    /// impl Aggregate for MovingSum {
+    ///     type Args = (f64,);
+    ///     type Output = f64;
+    ///     fn step(&mut self, (x,): (f64,)) {
+    ///         self.0 += x;
+    ///     }
+    ///     fn value(&self) -> f64 {
+    ///         self.0
+    ///     }
+    /// }
+    /// impl WindowFunction for MovingSum {
+    ///     fn inverse(&mut self, (x,): (f64,)) {
+    ///         self.0 -= x;
+    ///     }
+    /// }
+    ///
+    /// # let database = Database::open(":memory:")?;
+    /// database.create_window_function::<MovingSum>("moving_sum")?;
+    /// let sums: Vec<f64> = database.collect_vec(
+    ///     "select moving_sum(x) over (order by x rows between 1 preceding and current row)
+    ///      from (select 1 as x union select 2 union select 3)",
+    ///     (),
+    /// )?;
+    /// assert!(sums == vec![1.0, 3.0, 5.0]);
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn create_window_function<W>(&self, name: &str) -> Result<()>
+    where
+        W: WindowFunction,
+    {
+        self.create_window_function_v2::<W>(name, Some(call_value::<W>), Some(call_inverse::<W>))
+    }
+
+    fn create_window_function_v2<A>(
+        &self,
+        name: &str,
+        value: Option<unsafe extern "C" fn(*mut ffi::sqlite3_context)>,
+        inverse: Option<StepFn>,
+    ) -> Result<()>
+    where
+        A: Aggregate,
+    {
+        let name = CString::new(name)?;
+        let ecode = unsafe {
+            ffi::sqlite3_create_window_function(
+                self.db,
+                name.as_ptr(),
+                A::Args::arg_count(),
+                ffi::SQLITE_UTF8,
+                std::ptr::null_mut(),
+                Some(call_step::<A>),
+                Some(call_final::<A>),
+                value,
+                inverse,
+                None,
+            )
+        };
+        match ecode {
+            ffi::SQLITE_OK => Ok(()),
+            other => Err(self.error(other)),
+        }
+    }
+}
+
+type StepFn = unsafe extern "C" fn(*mut ffi::sqlite3_context, c_int, *mut *mut ffi::sqlite3_value);
+
+/// the aggregate instance of the current group, created on first use
+unsafe fn aggregate<'a, A: Aggregate>(context: *mut ffi::sqlite3_context) -> Option<&'a mut A> {
+    let size = std::mem::size_of::<*mut A>() as c_int;
+    let slot = ffi::sqlite3_aggregate_context(context, size) as *mut *mut A;
+    if slot.is_null() {
+        ffi::sqlite3_result_error_nomem(context);
+        return None;
+    }
+    if (*slot).is_null() {
+        *slot = Box::into_raw(Box::new(A::default()));
+    }
+    Some(&mut **slot)
+}
+
+unsafe fn args<'a>(
+    argc: c_int,
+    argv: *mut *mut ffi::sqlite3_value,
+) -> &'a [*mut ffi::sqlite3_value] {
+    match argc {
+        0 => &[],
+        argc => std::slice::from_raw_parts(argv, argc as usize),
+    }
+}
+
+unsafe extern "C" fn call_step<A: Aggregate>(
+    context: *mut ffi::sqlite3_context,
+    argc: c_int,
+    argv: *mut *mut ffi::sqlite3_value,
+) {
+    let result = catch_panic(|| {
+        if let Some(aggregate) = aggregate::<A>(context) {
+            aggregate.step(A::Args::from_args(args(argc, argv)));
+        }
+        Ok(())
+    });
+    if let Err(error) = result {
+        result_error(context, &error);
+    }
+}
+
+unsafe extern "C" fn call_inverse<W: WindowFunction>(
+    context: *mut ffi::sqlite3_context,
+    argc: c_int,
+    argv: *mut *mut ffi::sqlite3_value,
+) {
+    let result = catch_panic(|| {
+        if let Some(window) = aggregate::<W>(context) {
+            window.inverse(W::Args::from_args(args(argc, argv)));
+        }
+        Ok(())
+    });
+    if let Err(error) = result {
+        result_error(context, &error);
+    }
+}
+
+unsafe extern "C" fn call_value<W: WindowFunction>(context: *mut ffi::sqlite3_context) {
+    match catch_panic(|| Ok(aggregate::<W>(context).map(|window| window.value()))) {
+        Ok(Some(value)) => value.to_result(context),
+        Ok(None) => {}
+        Err(error) => result_error(context, &error),
+    }
+}
+
+unsafe extern "C" fn call_final<A: Aggregate>(context: *mut ffi::sqlite3_context) {
+    let result = catch_panic(|| {
+        // a zero size does not allocate, so groups without any rows get a null slot
+        let slot = ffi::sqlite3_aggregate_context(context, 0) as *mut *mut A;
+        Ok(match slot.is_null() || (*slot).is_null() {
+            true => A::default().value(),
+            false => {
+                let aggregate = Box::from_raw(*slot);
+                *slot = std::ptr::null_mut();
+                aggregate.value()
+            }
+        })
+    });
+    match result {
+        Ok(value) => value.to_result(context),
+        Err(error) => result_error(context, &error),
+    }
+}
+
+/// reports a panic of the aggregate as the error of the query
+unsafe fn result_error(context: *mut ffi::sqlite3_context, error: &RsqliteError) {
+    let message = error.to_string();
+    ffi::sqlite3_result_error(
+        context,
+        message.as_ptr() as *const c_char,
+        message.len() as c_int,
+    );
+}
+
+impl FromValue for i32 {
+    unsafe fn from_value(value: *mut ffi::sqlite3_value) -> Self {
+        ffi::sqlite3_value_int(value)
+    }
+}
+impl FromValue for i64 {
+    unsafe fn from_value(value: *mut ffi::sqlite3_value) -> Self {
+        ffi::sqlite3_value_int64(value)
+    }
+}
+impl FromValue for f64 {
+    unsafe fn from_value(value: *mut ffi::sqlite3_value) -> Self {
+        ffi::sqlite3_value_double(value)
+    }
+}
+/// invalid UTF-8 is replaced with `U+FFFD`
+impl FromValue for String {
+    unsafe fn from_value(value: *mut ffi::sqlite3_value) -> Self {
+        let ptr = ffi::sqlite3_value_text(value);
+        let bytes = ffi::sqlite3_value_bytes(value);
+        match bytes == 0 {
+            true => String::new(),
+            false => {
+                let slice = std::slice::from_raw_parts(ptr, bytes as usize);
+                String::from_utf8_lossy(slice).into_owned()
+            }
+        }
+    }
+}
+impl FromValue for Box<[u8]> {
+    unsafe fn from_value(value: *mut ffi::sqlite3_value) -> Self {
+        let ptr = ffi::sqlite3_value_blob(value);
+        let bytes = ffi::sqlite3_value_bytes(value);
+        match bytes == 0 {
+            true => Box::new([]),
+            false => std::slice::from_raw_parts(ptr as *const u8, bytes as usize).into(),
+        }
+    }
+}
+impl<T> FromValue for Option<T>
+where
+    T: FromValue,
+{
+    unsafe fn from_value(value: *mut ffi::sqlite3_value) -> Self {
+        match ffi::sqlite3_value_type(value) {
+            ffi::SQLITE_NULL => None,
+            _ => Some(T::from_value(value)),
+        }
+    }
+}
+
+impl FromArgs for () {
+    fn arg_count() -> c_int {
+        0
+    }
+    unsafe fn from_args(_args: &[*mut ffi::sqlite3_value]) -> Self {}
+}
+
+macro_rules! from_args_tuple {
+    ($count:expr, $($name:ident as $idx:tt),+) => (
+        impl<$($name),+> FromArgs for ($($name,)+)
+        where
+            $($name: FromValue,)+
+        {
+            fn arg_count() -> c_int { $count }
+            unsafe fn from_args(args: &[*mut ffi::sqlite3_value]) -> Self {
+                ($($name::from_value(args[$idx]),)+)
+            }
+        }
+    );
+}
+
+from_args_tuple!(1, T0 as 0);
+from_args_tuple!(2, T0 as 0, T1 as 1);
+from_args_tuple!(3, T0 as 0, T1 as 1, T2 as 2);
+from_args_tuple!(4, T0 as 0, T1 as 1, T2 as 2, T3 as 3);
+from_args_tuple!(5, T0 as 0, T1 as 1, T2 as 2, T3 as 3, T4 as 4);
+from_args_tuple!(6, T0 as 0, T1 as 1, T2 as 2, T3 as 3, T4 as 4, T5 as 5);
+
+impl ToResult for () {
+    unsafe fn to_result(self, context: *mut ffi::sqlite3_context) {
+        ffi::sqlite3_result_null(context);
+    }
+}
+impl ToResult for i32 {
+    unsafe fn to_result(self, context: *mut ffi::sqlite3_context) {
+        ffi::sqlite3_result_int(context, self);
+    }
+}
+impl ToResult for i64 {
+    unsafe fn to_result(self, context: *mut ffi::sqlite3_context) {
+        ffi::sqlite3_result_int64(context, self);
+    }
+}
+impl ToResult for f64 {
+    unsafe fn to_result(self, context: *mut ffi::sqlite3_context) {
+        ffi::sqlite3_result_double(context, self);
+    }
+}
+impl ToResult for &str {
+    unsafe fn to_result(self, context: *mut ffi::sqlite3_context) {
+        let ptr = self.as_ptr() as *const c_char;
+        ffi::sqlite3_result_text(context, ptr, self.len() as c_int, transient());
+    }
+}
+impl ToResult for String {
+    unsafe fn to_result(self, context: *mut ffi::sqlite3_context) {
+        self.as_str().to_result(context);
+    }
+}
+impl ToResult for &[u8] {
+    unsafe fn to_result(self, context: *mut ffi::sqlite3_context) {
+        let ptr = self.as_ptr() as *const c_void;
+        ffi::sqlite3_result_blob(context, ptr, self.len() as c_int, transient());
+    }
+}
+impl ToResult for Box<[u8]> {
+    unsafe fn to_result(self, context: *mut ffi::sqlite3_context) {
+        self.as_ref().to_result(context);
+    }
+}
+impl<T> ToResult for Option<T>
+where
+    T: ToResult,
+{
+    unsafe fn to_result(self, context: *mut ffi::sqlite3_context) {
+        match self {
+            None => ffi::sqlite3_result_null(context),
+            Some(t) => t.to_result(context),
+        }
+    }
+}
@@ -0,0 +1,226 @@
+//! Polygons and polygon tables of the Geopoly extension, see the `geopoly` feature
+//! and https://sqlite.org/geopoly.html
+use super::quote::quote_identifier;
+use super::{Bindable, Collectable, Database, Result, RsqliteError, Statement};
+use libc::c_int;
+use sqlite3_sys as ffi;
+use std::convert::TryInto;
+
+/// A simple polygon, its vertices listed counter-clockwise
+///
+/// Binds as GeoJSON-style text, `[[x,y],[x,y],...]`, and collects from that text
+/// or from the binary format stored in geopoly tables.
+///
+/// The SQL functions need SQLite built with `SQLITE_ENABLE_GEOPOLY`,
+/// the examples return early when it is missing.
+///
+/// ```
+/// # use rsqlite::*;
+/// use rsqlite::geopoly::Polygon;
+///
+/// # let database = Database::open(":memory:")?;
+/// # if !compile_option_used("ENABLE_GEOPOLY") {
+/// #     return Ok(());
+/// # }
+/// let square = Polygon::new(vec![(0.0, 0.0), (2.0, 0.0), (2.0, 2.0), (0.0, 2.0)]);
+/// let area: f64 = database.collect("select geopoly_area(?)", &square)?;
+/// assert!(area == 4.0);
+///
+/// let moved: Polygon = database.collect("select geopoly_xform(?, 1, 0, 0, 1, 10, 0)", &square)?;
+/// assert!(moved.points()[1] == (12.0, 0.0));
+/// # Ok::<(), RsqliteError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polygon(Vec<(f64, f64)>);
+
+/// Describes a geopoly table of polygons, created with `create`
+///
+/// Like `Polygon`, the table needs SQLite built with `SQLITE_ENABLE_GEOPOLY`.
+///
+/// ```
+/// # use rsqlite::*;
+/// use rsqlite::geopoly::{Geopoly, Polygon};
+///
+/// # let database = Database::open(":memory:")?;
+/// # if !compile_option_used("ENABLE_GEOPOLY") {
+/// #     return Ok(());
+/// # }
+/// let zones = Geopoly::new("zones").auxiliary(&["name"]);
+/// zones.create(&database)?;
+/// let small = zones.insert(&database, &Polygon::new(vec![(0.0, 0.0), (1.0, 0.0), (1.0, 1.0)]))?;
+/// let far = zones.insert(&database, &Polygon::new(vec![(9.0, 9.0), (10.0, 9.0), (10.0, 10.0)]))?;
+///
+/// let area = Polygon::new(vec![(-1.0, -1.0), (5.0, -1.0), (5.0, 5.0), (-1.0, 5.0)]);
+/// assert!(zones.within(&database, &area)? == vec![small]);
+/// let line = Polygon::new(vec![(0.5, -1.0), (10.0, 9.5), (0.5, 5.0)]);
+/// assert!(zones.overlapping(&database, &line)? == vec![small, far]);
+/// # Ok::<(), RsqliteError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Geopoly {
+    name: String,
+    auxiliary: Vec<String>,
+}
+
+impl Polygon {
+    /// a polygon of `points`, at least 3 of them
+    pub fn new(points: Vec<(f64, f64)>) -> Polygon {
+        Polygon(points)
+    }
+
+    /// the vertices of the polygon
+    pub fn points(&self) -> &[(f64, f64)] {
+        &self.0
+    }
+
+    /// the polygon as `[[x,y],[x,y],...]`, ending with the first vertex again as geopoly expects
+    pub fn to_json(&self) -> String {
+        let mut points: Vec<String> = self
+            .0
+            .iter()
+            .map(|(x, y)| format!("[{:?},{:?}]", x, y))
+            .collect();
+        if self.0.len() > 1 && self.0.first() != self.0.last() {
+            points.push(points[0].clone());
+        }
+        format!("[{}]", points.join(","))
+    }
+
+    /// parses `[[x,y],[x,y],...]`, as returned by `geopoly_json`, without the closing vertex
+    pub fn from_json(json: &str) -> Result<Polygon> {
+        let numbers = json
+            .split(|c: char| c == '[' || c == ']' || c == ',' || c.is_whitespace())
+            .filter(|number| !number.is_empty())
+            .map(|number| number.parse::<f64>().map_err(|_| invalid_polygon()))
+            .collect::<Result<Vec<f64>>>()?;
+        let nested = json.trim_start().starts_with("[[") && json.trim_end().ends_with("]]");
+        if !nested || numbers.len() % 2 != 0 {
+            return Err(invalid_polygon());
+        }
+        let mut points: Vec<(f64, f64)> = numbers.chunks(2).map(|xy| (xy[0], xy[1])).collect();
+        // the closing vertex repeats the first one
+        if points.len() > 1 && points.first() == points.last() {
+            points.pop();
+        }
+        Ok(Polygon(points))
+    }
+
+    /// parses the binary format of geopoly: a flag byte whose lowest bit is set for little-endian,
+    /// 3 bytes for the number of vertices, then their coordinates as 32-bit floats
+    fn from_blob(blob: &[u8]) -> Result<Polygon> {
+        if blob.len() < 4 {
+            return Err(invalid_polygon());
+        }
+        let count = u32::from_be_bytes([0, blob[1], blob[2], blob[3]]) as usize;
+        if blob.len() != 4 + count * 8 {
+            return Err(invalid_polygon());
+        }
+        let coordinate = |bytes: &[u8]| -> Result<f64> {
+            let bytes: [u8; 4] = bytes.try_into().map_err(|_| invalid_polygon())?;
+            Ok(match blob[0] & 1 {
+                1 => f32::from_le_bytes(bytes) as f64,
+                _ => f32::from_be_bytes(bytes) as f64,
+            })
+        };
+        let points = blob[4..]
+            .chunks(8)
+            .map(|xy| Ok((coordinate(&xy[..4])?, coordinate(&xy[4..])?)))
+            .collect::<Result<Vec<(f64, f64)>>>()?;
+        Ok(Polygon(points))
+    }
+}
+
+fn invalid_polygon() -> RsqliteError {
+    RsqliteError::new(
+        ffi::SQLITE_MISMATCH,
+        ffi::SQLITE_MISMATCH,
+        "value is not a valid polygon".to_owned(),
+    )
+}
+
+impl Bindable for Polygon {
+    fn bind(&self, statement: &mut Statement, index: &mut c_int) -> Result<()> {
+        self.to_json().bind(statement, index)
+    }
+}
+
+/// `collect` falls back to an empty polygon on invalid input
+impl Collectable for Polygon {
+    fn collect(statement: &Statement, column: &mut c_int) -> Self {
+        Self::try_collect(statement, column).unwrap_or(Polygon(Vec::new()))
+    }
+    fn try_collect(statement: &Statement, column: &mut c_int) -> Result<Self> {
+        let sqlite_type = unsafe { ffi::sqlite3_column_type(statement.stmt, *column) };
+        match sqlite_type {
+            ffi::SQLITE_BLOB => Polygon::from_blob(&<Box<[u8]>>::try_collect(statement, column)?),
+            ffi::SQLITE_TEXT => Polygon::from_json(&String::try_collect(statement, column)?),
+            _ => {
+                *column += 1;
+                Err(invalid_polygon())
+            }
+        }
+    }
+    fn columns_needed() -> c_int {
+        1
+    }
+}
+
+impl Geopoly {
+    /// a table named `name`, its polygons are in the `_shape` column
+    pub fn new(name: &str) -> Geopoly {
+        Geopoly {
+            name: name.to_owned(),
+            auxiliary: Vec::new(),
+        }
+    }
+
+    /// adds auxiliary columns, stored alongside each polygon
+    pub fn auxiliary(mut self, columns: &[&str]) -> Geopoly {
+        self.auxiliary = columns.iter().map(|&column| column.to_owned()).collect();
+        self
+    }
+
+    /// creates the table if it does not exist
+    pub fn create(&self, database: &Database) -> Result<()> {
+        let columns: Vec<String> = self
+            .auxiliary
+            .iter()
+            .map(|column| quote_identifier(column))
+            .collect();
+        let sql = format!(
+            "create virtual table if not exists {} using geopoly({})",
+            quote_identifier(&self.name),
+            columns.join(", ")
+        );
+        database.execute(&sql, ()).map(|_| ())
+    }
+
+    /// inserts `polygon`, returning its rowid
+    pub fn insert(&self, database: &Database, polygon: &Polygon) -> Result<i64> {
+        let sql = format!(
+            "insert into {}(_shape) values (?)",
+            quote_identifier(&self.name)
+        );
+        database.execute(&sql, polygon)?;
+        Ok(database.last_insert_rowid())
+    }
+
+    /// the rowids of the polygons overlapping `polygon`
+    pub fn overlapping(&self, database: &Database, polygon: &Polygon) -> Result<Vec<i64>> {
+        self.query(database, "geopoly_overlap", polygon)
+    }
+
+    /// the rowids of the polygons entirely inside `polygon`
+    pub fn within(&self, database: &Database, polygon: &Polygon) -> Result<Vec<i64>> {
+        self.query(database, "geopoly_within", polygon)
+    }
+
+    fn query(&self, database: &Database, function: &str, polygon: &Polygon) -> Result<Vec<i64>> {
+        let sql = format!(
+            "select rowid from {} where {}(_shape, ?) order by rowid",
+            quote_identifier(&self.name),
+            function
+        );
+        database.collect_vec(&sql, polygon)
+    }
+}
@@ -0,0 +1,84 @@
+//! A per-connection guard that only lets read-only statements through
+use super::{Database, Result, RsqliteError, Statement};
+use core::ptr;
+use libc::{c_char, c_int, c_void};
+use sqlite3_sys as ffi;
+
+impl Database {
+    /// enable or disable the read-only guard on this connection.
+    ///
+    /// While enabled, statements that write, change the schema, attach databases,
+    /// open transactions or set pragmas are rejected with `Err(ReadOnlyViolation)`.
+    /// This is checked both by an authorizer and by `sqlite3_stmt_readonly`,
+    /// and also covers writes denied while a statement runs, like the ANALYZE of `pragma optimize`.
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// # let database = Database::open(":memory:")?;
+    /// database.execute("create table user (name text)", ())?;
+    /// database.set_read_only_guard(true)?;
+    ///
+    /// let count: i32 = database.collect("select count(*) from user", ())?;
+    /// # assert!(count == 0);
+    /// let result = database.execute("delete from user", ());
+    /// assert!(matches!(result, Err(RsqliteError::ReadOnlyViolation)));
+    /// # database.set_read_only_guard(false)?;
+    /// # database.execute("create index user_name on user (name)", ())?;
+    /// # database.execute("insert into user values ('amin')", ())?;
+    /// # database.collect::<i32>("select count(*) from user where name = 'amin'", ())?;
+    /// # database.set_read_only_guard(true)?;
+    /// # let result = database.execute("pragma optimize", ());
+    /// # assert!(matches!(result, Err(RsqliteError::ReadOnlyViolation)));
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn set_read_only_guard(&self, enabled: bool) -> Result<()> {
+        let authorizer = match enabled {
+            true => Some(read_only_authorizer as Authorizer),
+            false => None,
+        };
+        let ecode = unsafe { ffi::sqlite3_set_authorizer(self.db, authorizer, ptr::null_mut()) };
+        match ecode {
+            ffi::SQLITE_OK => {
+                self.read_only_guard.set(enabled);
+                Ok(())
+            }
+            other => Err(self.error(other)),
+        }
+    }
+
+    /// rejects a freshly prepared statement if the guard is on and it is not read-only
+    pub(crate) fn check_read_only(&self, statement: &Statement) -> Result<()> {
+        let readonly = unsafe { ffi::sqlite3_stmt_readonly(statement.stmt) } != 0;
+        match self.read_only_guard.get() && !readonly {
+            true => Err(RsqliteError::ReadOnlyViolation),
+            false => Ok(()),
+        }
+    }
+}
+
+type Authorizer = unsafe extern "C" fn(
+    *mut c_void,
+    c_int,
+    *const c_char,
+    *const c_char,
+    *const c_char,
+    *const c_char,
+) -> c_int;
+
+unsafe extern "C" fn read_only_authorizer(
+    _data: *mut c_void,
+    action: c_int,
+    _arg1: *const c_char,
+    arg2: *const c_char,
+    _database: *const c_char,
+    _trigger: *const c_char,
+) -> c_int {
+    match action {
+        ffi::SQLITE_SELECT | ffi::SQLITE_READ | ffi::SQLITE_FUNCTION | ffi::SQLITE_RECURSIVE => {
+            ffi::SQLITE_OK
+        }
+        // `pragma name` only reads, `pragma name = value` or `pragma name(value)` may write
+        ffi::SQLITE_PRAGMA if arg2.is_null() => ffi::SQLITE_OK,
+        _ => ffi::SQLITE_DENY,
+    }
+}
@@ -0,0 +1,253 @@
+//! Callbacks invoked by sqlite as the connection changes data
+use super::{Database, Result};
+use libc::{c_char, c_int, c_uint, c_void};
+use sqlite3_sys as ffi;
+use std::ffi::CStr;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::time::Duration;
+
+/// The kind of change reported to an `update_hook`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Insert,
+    Update,
+    Delete,
+}
+
+/// An event reported to a `trace` callback
+#[derive(Debug, Clone, PartialEq)]
+pub enum TraceEvent<'a> {
+    /// a statement started running, `sql` is the unexpanded SQL text
+    /// or a `-- comment` for statements inside triggers
+    Statement { sql: &'a str },
+    /// a statement finished running
+    Profile { sql: &'a str, duration: Duration },
+    /// a statement produced a row
+    Row { sql: &'a str },
+    /// the database connection is closing
+    Close,
+}
+
+pub(crate) type UpdateHook = Box<dyn FnMut(Action, &str, &str, i64) + Send>;
+pub(crate) type TraceHook = Box<dyn FnMut(TraceEvent) + Send>;
+pub(crate) type ProgressHandler = Box<dyn FnMut() -> bool + Send>;
+pub(crate) type WalHook = Box<dyn FnMut(&str, i32) + Send>;
+
+impl Database {
+    /// register a callback invoked for every row inserted, updated or deleted in a rowid table.
+    ///
+    /// It receives the action, the database name, the table name and the rowid.
+    /// The callback must not modify the database connection. A panic in it is caught and ignored.
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// use rsqlite::hooks::Action;
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// # let database = Database::open(":memory:")?;
+    /// database.execute("create table user (name text)", ())?;
+    ///
+    /// let changes = Arc::new(Mutex::new(vec![]));
+    /// let sink = changes.clone();
+    /// database.update_hook(move |action, _database, table, rowid| {
+    ///     sink.lock().unwrap().push((action, table.to_owned(), rowid));
+    /// });
+    /// database.execute("insert into user values ('amin')", ())?;
+    /// assert!(*changes.lock().unwrap() == vec![(Action::Insert, "user".to_owned(), 1)]);
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn update_hook<F>(&self, hook: F)
+    where
+        F: FnMut(Action, &str, &str, i64) + Send + 'static,
+    {
+        let mut hook: Box<UpdateHook> = Box::new(Box::new(hook));
+        let data = &mut *hook as *mut UpdateHook as *mut c_void;
+        unsafe { ffi::sqlite3_update_hook(self.db, Some(call_update_hook), data) };
+        self.update_hook.set(Some(hook));
+    }
+}
+
+unsafe extern "C" fn call_update_hook(
+    data: *mut c_void,
+    action: c_int,
+    database: *const c_char,
+    table: *const c_char,
+    rowid: ffi::sqlite3_int64,
+) {
+    let hook = &mut *(data as *mut UpdateHook);
+    let action = match action {
+        ffi::SQLITE_INSERT => Action::Insert,
+        ffi::SQLITE_UPDATE => Action::Update,
+        _ => Action::Delete,
+    };
+    let database = CStr::from_ptr(database).to_string_lossy();
+    let table = CStr::from_ptr(table).to_string_lossy();
+    let _ = catch_unwind(AssertUnwindSafe(|| hook(action, &database, &table, rowid)));
+}
+
+impl Database {
+    /// register a callback for the events selected by `mask`, a combination of
+    /// `ffi::SQLITE_TRACE_STMT`, `SQLITE_TRACE_PROFILE`, `SQLITE_TRACE_ROW` and `SQLITE_TRACE_CLOSE`.
+    /// A panic in the callback is caught and ignored.
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// use rsqlite::hooks::TraceEvent;
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// # let database = Database::open(":memory:")?;
+    /// let log = Arc::new(Mutex::new(vec![]));
+    /// let sink = log.clone();
+    /// database.trace(ffi::SQLITE_TRACE_PROFILE, move |event| {
+    ///     if let TraceEvent::Profile { sql, duration } = event {
+    ///         sink.lock().unwrap().push((sql.to_owned(), duration));
+    ///     }
+    /// })?;
+    /// database.execute("create table user (name text)", ())?;
+    /// let log = log.lock().unwrap();
+    /// assert!(log.len() == 1 && log[0].0 == "create table user (name text)");
+    /// assert!(log[0].1 < std::time::Duration::from_secs(1));
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn trace<F>(&self, mask: c_int, callback: F) -> Result<()>
+    where
+        F: FnMut(TraceEvent) + Send + 'static,
+    {
+        let mut callback: Box<TraceHook> = Box::new(Box::new(callback));
+        let data = &mut *callback as *mut TraceHook as *mut c_void;
+        let ecode =
+            unsafe { ffi::sqlite3_trace_v2(self.db, mask as c_uint, Some(call_trace), data) };
+        match ecode {
+            ffi::SQLITE_OK => {
+                self.trace_hook.set(Some(callback));
+                Ok(())
+            }
+            other => Err(self.error(other)),
+        }
+    }
+}
+
+unsafe extern "C" fn call_trace(
+    event: c_uint,
+    data: *mut c_void,
+    p: *mut c_void,
+    x: *mut c_void,
+) -> c_int {
+    let callback = &mut *(data as *mut TraceHook);
+    let sql = |stmt: *mut c_void| {
+        let sql = ffi::sqlite3_sql(stmt as *mut ffi::sqlite3_stmt);
+        match sql.is_null() {
+            true => Default::default(),
+            false => CStr::from_ptr(sql).to_string_lossy(),
+        }
+    };
+    let _ = catch_unwind(AssertUnwindSafe(|| match event as c_int {
+        ffi::SQLITE_TRACE_STMT => {
+            let sql = CStr::from_ptr(x as *const c_char).to_string_lossy();
+            callback(TraceEvent::Statement { sql: &sql })
+        }
+        ffi::SQLITE_TRACE_PROFILE => {
+            let nanos = *(x as *const i64);
+            let duration = Duration::from_nanos(nanos.max(0) as u64);
+            callback(TraceEvent::Profile {
+                sql: &sql(p),
+                duration,
+            })
+        }
+        ffi::SQLITE_TRACE_ROW => callback(TraceEvent::Row { sql: &sql(p) }),
+        ffi::SQLITE_TRACE_CLOSE => callback(TraceEvent::Close),
+        _ => {}
+    }));
+    0
+}
+
+impl Database {
+    /// register a callback invoked every `n_ops` virtual machine instructions
+    /// of a running statement. Returning `true` cancels the statement with `Err(Interrupt)`,
+    /// and so does a panic in the handler.
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// # let database = Database::open(":memory:")?;
+    /// let forever = "with recursive forever(x) as (select 1 union all select x + 1 from forever)
+    ///                select count(*) from forever";
+    /// let started = std::time::Instant::now();
+    /// database.progress_handler(1000, move || started.elapsed().as_secs() > 1);
+    /// let result: Result<i32> = database.collect(forever, ());
+    /// assert!(matches!(result, Err(RsqliteError::Interrupt { .. })));
+    ///
+    /// database.progress_handler(1000, || panic!("stop"));
+    /// let result: Result<i32> = database.collect(forever, ());
+    /// assert!(matches!(result, Err(RsqliteError::Interrupt { .. })));
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn progress_handler<F>(&self, n_ops: i32, handler: F)
+    where
+        F: FnMut() -> bool + Send + 'static,
+    {
+        let mut handler: Box<ProgressHandler> = Box::new(Box::new(handler));
+        let data = &mut *handler as *mut ProgressHandler as *mut c_void;
+        unsafe { ffi::sqlite3_progress_handler(self.db, n_ops, Some(call_progress_handler), data) };
+        self.progress_handler.set(Some(handler));
+    }
+}
+
+unsafe extern "C" fn call_progress_handler(data: *mut c_void) -> c_int {
+    let handler = &mut *(data as *mut ProgressHandler);
+    catch_unwind(AssertUnwindSafe(handler)).unwrap_or(true) as c_int
+}
+
+impl Database {
+    /// register a callback invoked after every commit in WAL mode,
+    /// with the database name and the number of frames in the write-ahead log.
+    ///
+    /// This replaces the automatic checkpoints of `wal_autocheckpoint`,
+    /// the callback is expected to schedule checkpoints itself.
+    /// A panic in the callback makes the committing statement fail with `SQLITE_ERROR`.
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// use rsqlite::wal::JournalMode;
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// # let path = std::env::temp_dir().join("rsqlite-wal-hook.db");
+    /// # let path = path.to_str().unwrap();
+    /// # let _ = std::fs::remove_file(path);
+    /// let database = Database::open(path)?;
+    /// database.set_journal_mode(JournalMode::Wal)?;
+    ///
+    /// let commits = Arc::new(Mutex::new(vec![]));
+    /// let sink = commits.clone();
+    /// database.wal_hook(move |database, frames| sink.lock().unwrap().push((database.to_owned(), frames)));
+    /// database.execute("create table user (name text)", ())?;
+    /// database.execute("insert into user values ('amin')", ())?;
+    ///
+    /// let commits = commits.lock().unwrap();
+    /// assert!(commits.len() == 2 && commits[0].0 == "main");
+    /// assert!(commits[1].1 > commits[0].1);
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn wal_hook<F>(&self, hook: F)
+    where
+        F: FnMut(&str, i32) + Send + 'static,
+    {
+        let mut hook: Box<WalHook> = Box::new(Box::new(hook));
+        let data = &mut *hook as *mut WalHook as *mut c_void;
+        unsafe { ffi::sqlite3_wal_hook(self.db, Some(call_wal_hook), data) };
+        self.wal_hook.set(Some(hook));
+    }
+}
+
+unsafe extern "C" fn call_wal_hook(
+    data: *mut c_void,
+    _db: *mut ffi::sqlite3,
+    database: *const c_char,
+    frames: c_int,
+) -> c_int {
+    let hook = &mut *(data as *mut WalHook);
+    let database = CStr::from_ptr(database).to_string_lossy();
+    match catch_unwind(AssertUnwindSafe(|| hook(&database, frames))) {
+        Ok(()) => ffi::SQLITE_OK,
+        Err(_) => ffi::SQLITE_ERROR,
+    }
+}
@@ -0,0 +1,264 @@
+//! Loading CSV files into tables, like the `.import` command of the sqlite3 shell
+use super::quote::quote_identifier;
+use super::{Bindable, Database, Result, RsqliteError, Statement};
+use libc::c_int;
+use sqlite3_sys as ffi;
+use std::io::{BufRead, BufReader, Read};
+
+/// Options of `import::csv`
+#[derive(Debug, Clone)]
+pub struct CsvOptions {
+    delimiter: u8,
+    header: bool,
+    infer_types: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> CsvOptions {
+        CsvOptions {
+            delimiter: b',',
+            header: true,
+            infer_types: false,
+        }
+    }
+}
+
+impl CsvOptions {
+    /// comma separated, with a header row and every value imported as TEXT
+    pub fn new() -> CsvOptions {
+        CsvOptions::default()
+    }
+
+    /// separates the fields by `delimiter`, like `b'\t'` or `b';'`
+    pub fn delimiter(mut self, delimiter: u8) -> CsvOptions {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// whether the first row holds the column names, otherwise they are `c1`, `c2`, ...
+    pub fn header(mut self, header: bool) -> CsvOptions {
+        self.header = header;
+        self
+    }
+
+    /// imports integers and reals as numbers and empty fields as NULL,
+    /// and creates the table with untyped columns so they are stored as such
+    pub fn infer_types(mut self, infer_types: bool) -> CsvOptions {
+        self.infer_types = infer_types;
+        self
+    }
+}
+
+/// imports the CSV from `reader` into `table`, returning the number of rows inserted
+///
+/// The table is created from the header if it does not exist,
+/// otherwise it must have as many columns as the CSV. Blank lines are skipped.
+/// Everything is inserted in a single transaction, which is rolled back on error.
+///
+/// ```
+/// # use rsqlite::*;
+/// use rsqlite::import::{self, CsvOptions};
+/// # let database = Database::open(":memory:")?;
+/// let csv = "name,age,bio\namin,29,\"likes \"\"sqlite\"\",\nand rust\"\nbob,,\n";
+///
+/// let options = CsvOptions::new().infer_types(true);
+/// let rows = import::csv(&database, csv.as_bytes(), "user", &options)?;
+/// assert!(rows == 2);
+///
+/// let users: Vec<(String, Option<i64>, Option<String>)> =
+///     database.collect_vec("select name, age, bio from user", ())?;
+/// assert!(users[0] == ("amin".to_owned(), Some(29), Some("likes \"sqlite\",\nand rust".to_owned())));
+/// assert!(users[1] == ("bob".to_owned(), None, None));
+/// # let rows = import::csv(&database, "name,age,bio\ncarl,31,\n\n".as_bytes(), "user", &options)?;
+/// # assert!(rows == 1);
+/// # Ok::<(), RsqliteError>(())
+/// ```
+pub fn csv(
+    database: &Database,
+    reader: impl Read,
+    table: &str,
+    options: &CsvOptions,
+) -> Result<usize> {
+    let mut records = Records {
+        reader: BufReader::new(reader),
+        delimiter: options.delimiter,
+    };
+    let first = match records.next()? {
+        Some(record) => record,
+        None => return Ok(0),
+    };
+    let columns = match options.header {
+        true => first.clone(),
+        false => (1..=first.len()).map(|i| format!("c{}", i)).collect(),
+    };
+
+    database.execute("begin immediate", ())?;
+    let result = import(database, &mut records, table, options, &columns, first)
+        .and_then(|rows| database.execute("commit", ()).map(|_| rows));
+    if result.is_err() && !database.is_autocommit() {
+        let _ = database.execute("rollback", ());
+    }
+    result
+}
+
+fn import(
+    database: &Database,
+    records: &mut Records<impl BufRead>,
+    table: &str,
+    options: &CsvOptions,
+    columns: &[String],
+    first: Vec<String>,
+) -> Result<usize> {
+    let exists: bool = database.collect(
+        "select count(*) > 0 from pragma_table_list where name = ?",
+        table,
+    )?;
+    let table = quote_identifier(table);
+    match exists {
+        true => {
+            let statement = database.prepare(&format!("select * from {}", table))?;
+            if statement.column_count() as usize != columns.len() {
+                return Err(RsqliteError::new(
+                    ffi::SQLITE_MISMATCH,
+                    ffi::SQLITE_MISMATCH,
+                    format!(
+                        "{} has {} columns, the CSV has {}",
+                        table,
+                        statement.column_count(),
+                        columns.len()
+                    ),
+                ));
+            }
+        }
+        false => {
+            let declared_type = match options.infer_types {
+                true => "",
+                false => " text",
+            };
+            let columns = columns
+                .iter()
+                .map(|column| format!("{}{}", quote_identifier(column), declared_type))
+                .collect::<Vec<_>>();
+            database.execute(
+                &format!("create table {} ({})", table, columns.join(", ")),
+                (),
+            )?;
+        }
+    }
+
+    let placeholders = vec!["?"; columns.len()].join(", ");
+    let mut insert =
+        database.prepare_cached(&format!("insert into {} values ({})", table, placeholders))?;
+    let mut rows = 0;
+    let mut record = match options.header {
+        true => records.next()?,
+        false => Some(first),
+    };
+    while let Some(fields) = record {
+        if fields.len() != columns.len() {
+            return Err(RsqliteError::new(
+                ffi::SQLITE_MISMATCH,
+                ffi::SQLITE_MISMATCH,
+                format!(
+                    "row {} has {} fields, expected {}",
+                    rows + 1,
+                    fields.len(),
+                    columns.len()
+                ),
+            ));
+        }
+        let mut index = 1;
+        for field in fields {
+            bind_field(&mut insert, &mut index, field, options.infer_types)?;
+        }
+        insert.execute(())?;
+        rows += 1;
+        record = records.next()?;
+    }
+    Ok(rows)
+}
+
+fn bind_field(
+    statement: &mut Statement,
+    index: &mut c_int,
+    field: String,
+    infer_types: bool,
+) -> Result<()> {
+    if !infer_types {
+        return field.bind(statement, index);
+    }
+    if field.is_empty() {
+        return None::<i64>.bind(statement, index);
+    }
+    if let Ok(integer) = field.parse::<i64>() {
+        return integer.bind(statement, index);
+    }
+    match field.parse::<f64>() {
+        Ok(real) if real.is_finite() => real.bind(statement, index),
+        _ => field.bind(statement, index),
+    }
+}
+
+/// reads RFC 4180 records, fields may be quoted to hold delimiters, quotes and newlines
+struct Records<R> {
+    reader: R,
+    delimiter: u8,
+}
+
+impl<R: BufRead> Records<R> {
+    fn next(&mut self) -> Result<Option<Vec<String>>> {
+        let mut line = Vec::new();
+        loop {
+            if self.reader.read_until(b'\n', &mut line)? == 0 {
+                return Ok(None);
+            }
+            // blank lines hold no record, like a trailing newline at the end of the file
+            match line.as_slice() {
+                b"\n" | b"\r\n" => line.clear(),
+                _ => break,
+            }
+        }
+        let mut fields = Vec::new();
+        let mut field = Vec::new();
+        let mut quoted = false;
+        let mut position = 0;
+        loop {
+            if position == line.len() {
+                // a newline inside quotes belongs to the field, keep reading
+                if quoted && self.reader.read_until(b'\n', &mut line)? > 0 {
+                    continue;
+                }
+                break;
+            }
+            let byte = line[position];
+            position += 1;
+            match (quoted, byte) {
+                (true, b'"') if line.get(position) == Some(&b'"') => {
+                    field.push(b'"');
+                    position += 1;
+                }
+                (true, b'"') => quoted = false,
+                (true, byte) => field.push(byte),
+                (false, b'"') if field.is_empty() => quoted = true,
+                (false, byte) if byte == self.delimiter => {
+                    fields.push(field_string(std::mem::take(&mut field))?);
+                }
+                (false, b'\n') => break,
+                (false, b'\r') if line.get(position) == Some(&b'\n') => {}
+                (false, byte) => field.push(byte),
+            }
+        }
+        fields.push(field_string(field)?);
+        Ok(Some(fields))
+    }
+}
+
+fn field_string(field: Vec<u8>) -> Result<String> {
+    String::from_utf8(field).map_err(|error| {
+        RsqliteError::new(
+            ffi::SQLITE_MISMATCH,
+            ffi::SQLITE_MISMATCH,
+            format!("CSV field is not valid UTF-8: {}", error),
+        )
+    })
+}
@@ -0,0 +1,103 @@
+//! Verifying the database file, see `Database::integrity_check`
+use super::{Database, Result};
+
+/// A problem reported by `pragma integrity_check` or `pragma quick_check`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IntegrityError {
+    /// the row is in the table but not in the index
+    MissingFromIndex { rowid: i64, index: String },
+    /// the index and its table have a different number of entries
+    WrongEntryCount { index: String },
+    /// a b-tree page is malformed
+    Page { page: u32, message: String },
+    /// any other message, like a NOT NULL or CHECK constraint violated by existing rows
+    Other(String),
+}
+
+impl IntegrityError {
+    fn parse(message: &str) -> IntegrityError {
+        // with attached databases, messages start with "*** in database main ***\n"
+        let message = match message.strip_prefix("*** in database ") {
+            Some(rest) => rest
+                .split_once("***\n")
+                .map_or(message, |(_, message)| message),
+            None => message,
+        };
+        if let Some(rest) = message.strip_prefix("row ") {
+            if let Some((rowid, index)) = rest.split_once(" missing from index ") {
+                if let Ok(rowid) = rowid.parse() {
+                    let index = index.to_owned();
+                    return IntegrityError::MissingFromIndex { rowid, index };
+                }
+            }
+        }
+        if let Some(index) = message.strip_prefix("wrong # of entries in index ") {
+            let index = index.to_owned();
+            return IntegrityError::WrongEntryCount { index };
+        }
+        let page = message
+            .strip_prefix("Page ")
+            .or_else(|| message.strip_prefix("On tree page "))
+            .and_then(|rest| rest.split(|c: char| !c.is_ascii_digit()).next())
+            .and_then(|page| page.parse().ok());
+        match page {
+            Some(page) => IntegrityError::Page {
+                page,
+                message: message.to_owned(),
+            },
+            None => IntegrityError::Other(message.to_owned()),
+        }
+    }
+}
+
+impl Database {
+    /// runs `pragma integrity_check`, returning at most `max_errors` problems,
+    /// none if the database is healthy
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// use rsqlite::config::DbConfig;
+    /// use rsqlite::integrity::IntegrityError;
+    /// # let path = std::env::temp_dir().join("rsqlite-integrity-check.db");
+    /// # let path = path.to_str().unwrap();
+    /// # let _ = std::fs::remove_file(path);
+    /// let database = Database::open(path)?;
+    /// database.execute("create table t (a, b)", ())?;
+    /// database.execute("create index t_a on t (a)", ())?;
+    /// database.execute("insert into t values (1, 2)", ())?;
+    /// assert!(database.integrity_check(100)?.is_empty());
+    ///
+    /// // index `t_a` now claims to index `b` instead
+    /// database.set_db_config(DbConfig::Defensive, false)?;
+    /// database.pragma_set("writable_schema", true)?;
+    /// database.execute("update sqlite_schema set sql = 'create index t_a on t (b)' where name = 't_a'", ())?;
+    /// # drop(database);
+    ///
+    /// let database = Database::open(path)?;
+    /// let errors = database.integrity_check(100)?;
+    /// let index = "t_a".to_owned();
+    /// assert!(errors == vec![IntegrityError::MissingFromIndex { rowid: 1, index }]);
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn integrity_check(&self, max_errors: u32) -> Result<Vec<IntegrityError>> {
+        self.run_check("integrity_check", max_errors)
+    }
+
+    /// runs `pragma quick_check`, which skips the slow index and UNIQUE verification
+    /// of `integrity_check` and returns at most 100 problems
+    pub fn quick_check(&self) -> Result<Vec<IntegrityError>> {
+        self.run_check("quick_check", 100)
+    }
+
+    fn run_check(&self, pragma: &str, max_errors: u32) -> Result<Vec<IntegrityError>> {
+        let max_errors = max_errors.max(1) as i64;
+        let messages: Vec<String> = self.pragma_rows(pragma, max_errors)?;
+        Ok(match messages.as_slice() {
+            [ok] if ok == "ok" => vec![],
+            _ => messages
+                .iter()
+                .map(|message| IntegrityError::parse(message))
+                .collect(),
+        })
+    }
+}
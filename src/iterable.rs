@@ -1,11 +1,13 @@
 //! Iterable types are expected to iterate over the sqlite rows
-use super::{Collectable, Statement};
+use super::{Collectable, Result, Statement};
 use libc::c_int;
 
 /// This library implements `Iterable` for any `FnMut<T1,...> -> R`  
 /// Note that the return type `R` comes first in the generic parameter list
 pub trait Iterable<R, T> {
-    fn iterate(&mut self, statement: &mut Statement, index: &mut c_int) -> R;
+    /// collects the arguments from the current row and calls itself,
+    /// fails without calling itself if an argument can not be collected
+    fn iterate(&mut self, statement: &Statement, index: &mut c_int) -> Result<R>;
 
     /// number of columns needed by this type
     fn columns_needed() -> c_int;
@@ -18,8 +20,8 @@ macro_rules! iterable_fn_mut {
             ($($name,)+) : Collectable,
             $($name: Collectable),+
         {
-            fn iterate(&mut self, statement: &mut Statement, index: &mut c_int) -> R {
-                (*self)($($name::collect(statement, index)),+)
+            fn iterate(&mut self, statement: &Statement, index: &mut c_int) -> Result<R> {
+                Ok((*self)($($name::try_collect(statement, index)?),+))
             }
             /// number of columns needed by this type
             fn columns_needed() -> c_int {
@@ -0,0 +1,101 @@
+//! `serde_json::Value` bindings and helpers for the JSON1 functions
+//!
+//! This module is only available with the `json` feature.
+//! A `Value` binds as its JSON text. Collecting parses TEXT as JSON
+//! and maps NULL, INTEGER and REAL to the matching JSON value.
+//!
+//! ```
+//! # use rsqlite::*;
+//! use serde_json::{json, Value};
+//! # let database = Database::open(":memory:")?;
+//! database.execute("create table event(payload text)", ())?;
+//! database.execute("insert into event values (?)", json!({"user": {"name": "amin"}, "tags": [1, 2]}))?;
+//!
+//! let payload: Value = database.collect("select payload from event", ())?;
+//! assert!(payload["tags"] == json!([1, 2]));
+//! let user: Value = database.collect("select payload -> '$.user' from event", ())?;
+//! assert!(user == json!({"name": "amin"}));
+//! # Ok::<(), RsqliteError>(())
+//! ```
+use super::{Bindable, Collectable, Database, Result, RsqliteError, Statement};
+use libc::c_int;
+use serde_json::{Number, Value};
+use sqlite3_sys as ffi;
+
+impl Bindable for Value {
+    fn bind(&self, statement: &mut Statement, index: &mut c_int) -> Result<()> {
+        self.to_string().bind(statement, index)
+    }
+}
+
+/// `collect` falls back to `Value::Null` on invalid JSON
+impl Collectable for Value {
+    fn collect(statement: &Statement, column: &mut c_int) -> Self {
+        Self::try_collect(statement, column).unwrap_or_default()
+    }
+    fn try_collect(statement: &Statement, column: &mut c_int) -> Result<Self> {
+        let sqlite_type = unsafe { ffi::sqlite3_column_type(statement.stmt, *column) };
+        match sqlite_type {
+            ffi::SQLITE_NULL => {
+                *column += 1;
+                Ok(Value::Null)
+            }
+            ffi::SQLITE_INTEGER => i64::try_collect(statement, column).map(Value::from),
+            ffi::SQLITE_FLOAT => {
+                let number = f64::try_collect(statement, column)?;
+                Ok(Number::from_f64(number).map_or(Value::Null, Value::Number))
+            }
+            _ => {
+                let text = String::try_collect(statement, column)?;
+                serde_json::from_str(&text).map_err(|error| {
+                    RsqliteError::new(
+                        ffi::SQLITE_MISMATCH,
+                        ffi::SQLITE_MISMATCH,
+                        format!("invalid JSON: {}", error),
+                    )
+                })
+            }
+        }
+    }
+    fn columns_needed() -> c_int {
+        1
+    }
+}
+
+impl Database {
+    /// `json_extract(json, path)`, collected as `T`
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// use serde_json::json;
+    /// # let database = Database::open(":memory:")?;
+    /// let document = json!({"user": {"name": "amin", "age": 29}});
+    /// let name: String = database.json_extract(&document, "$.user.name")?;
+    /// let age: i64 = database.json_extract(&document, "$.user.age")?;
+    /// let missing: Option<i64> = database.json_extract(&document, "$.user.weight")?;
+    /// assert!((name.as_str(), age, missing) == ("amin", 29, None));
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn json_extract<T: Collectable>(&self, json: impl Bindable, path: &str) -> Result<T> {
+        self.collect("select json_extract(?, ?)", (json, path))
+    }
+
+    /// `json_type(json, path)`, such as `"object"`, `"integer"` or `"null"`,
+    /// `None` when `path` does not exist
+    pub fn json_type(&self, json: impl Bindable, path: &str) -> Result<Option<String>> {
+        self.collect("select json_type(?, ?)", (json, path))
+    }
+
+    /// `json_valid(text)`
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// # let database = Database::open(":memory:")?;
+    /// assert!(database.json_valid(r#"{"a": 1}"#)?);
+    /// assert!(!database.json_valid("{a: 1")?);
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn json_valid(&self, text: &str) -> Result<bool> {
+        self.collect("select json_valid(?)", text)
+    }
+}
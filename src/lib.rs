@@ -20,7 +20,7 @@
 //! // inserts a new user record.
 //! // binds the fields to '?' .
 //! // note that only these types are allowed for bindings:
-//! //     int32, i64, f64, &str, &[u8]
+//! //     int32, i64, f64, bool, &str, String, &[u8], Vec<u8>
 //! // use `&[u8]` to store blob data.
 //! database.execute(
 //!    "insert into user(age, name, weight) values(?, ?, ?)",
@@ -51,7 +51,7 @@
 //!
 //! // selects the count(*) from user table
 //! // you can extract a single culumn single row result to:
-//! // i32, i64, f64, String, Box<[u8]>
+//! // i32, i64, f64, bool, String, Box<[u8]>
 //! let count: i32 = database.collect("select count(*) from user", ())?;
 //! # assert!(count == 2);
 //!
@@ -133,21 +133,21 @@
 //!
 //! |Internal Type|Requested Type|Conversion
 //! |-------------|--------------|----------
-//! |NULL         |i32/i64 	     |Result is 0
-//! |NULL         |f64   	     |Result is 0.0
+//! |NULL         |i32/i64          |Result is 0
+//! |NULL         |f64            |Result is 0.0
 //! |NULL         |String        |Result is empty `String::new()`
 //! |NULL         |Box<[u8]>     |Result is empty `Box::new([])`
-//! |INTEGER      |f64   	     |Convert from integer to f64
+//! |INTEGER      |f64            |Convert from integer to f64
 //! |INTEGER      |String        |ASCII rendering of the integer
 //! |INTEGER      |Box<[u8]>     |Same as INTEGER->String
-//! |FLOAT        |i32/i64 	     |CAST to INTEGER
+//! |FLOAT        |i32/i64          |CAST to INTEGER
 //! |FLOAT        |String        |ASCII rendering of the float
 //! |FLOAT        |Box<[u8]>     |CAST to [u8]
-//! |TEXT         |i32/i64 	     |CAST to i32/i64
-//! |TEXT         |f64   	     |CAST to f64  
+//! |TEXT         |i32/i64          |CAST to i32/i64
+//! |TEXT         |f64            |CAST to f64  
 //! |TEXT         |Box<[u8]>     |No change
-//! |BLOB         |i32/i64 	     |CAST to i32/i64
-//! |BLOB         |f64   	     |CAST to f64
+//! |BLOB         |i32/i64          |CAST to i32/i64
+//! |BLOB         |f64            |CAST to f64
 //! |BLOB         |String        |No change
 //!
 //!
@@ -197,32 +197,150 @@
 //!
 //! MIT license - http://www.opensource.org/licenses/mit-license.php
 
+#[cfg(feature = "appendvfs")]
+pub mod appendvfs;
+#[cfg(feature = "tokio")]
+pub mod asynk;
+mod attach;
+pub mod backup;
 pub mod bindable;
+mod busy;
+pub mod cache;
+#[cfg(feature = "carray")]
+mod carray;
+#[cfg(feature = "chrono")]
+mod chrono_types;
+#[cfg(feature = "cksumvfs")]
+pub mod cksumvfs;
+mod collation;
 pub mod collectable;
+mod columns;
+pub mod config;
+#[cfg(any(feature = "chrono", feature = "time"))]
+mod datetime;
+#[cfg(feature = "serde")]
+mod de;
+mod dump;
 pub mod error;
+#[cfg(feature = "fts5")]
+pub mod fts;
+pub mod function;
+#[cfg(feature = "geopoly")]
+pub mod geopoly;
+mod guard;
+pub mod hooks;
+pub mod import;
+pub mod integrity;
 pub mod iterable;
+#[cfg(feature = "json")]
+mod json;
+pub mod limit;
+pub mod memory;
+pub mod migrations;
+mod optimize;
+pub mod options;
+#[cfg(any(feature = "r2d2", feature = "deadpool"))]
+pub mod pool;
+pub mod pragma;
+#[cfg(feature = "pretty")]
+pub mod pretty;
+mod quota;
+pub mod quote;
+pub mod recover;
+#[cfg(feature = "regexp")]
+mod regexp;
+pub mod rows;
+#[cfg(feature = "rtree")]
+pub mod rtree;
+pub mod script;
+mod serialize;
+pub mod serialized;
+#[cfg(feature = "session")]
+pub mod session;
+pub mod space;
+mod sql;
+pub mod status;
+mod strict;
+#[cfg(feature = "time")]
+mod time_types;
+pub mod transaction;
+#[cfg(feature = "unlock_notify")]
+mod unlock_notify;
+mod untrusted;
+#[cfg(feature = "uuid")]
+mod uuid_types;
+pub mod value;
+mod version;
+pub mod vfs;
+pub mod vtab;
+pub mod wal;
 
 pub use bindable::Bindable;
+pub use cache::CachedStatement;
 pub use collectable::Collectable;
 pub use error::RsqliteError;
 pub use iterable::Iterable;
+pub use options::OpenOptions;
+pub use quote::{quote_identifier, quote_literal};
+pub use rows::{MappedRows, Row, Rows};
+#[cfg(feature = "derive")]
+pub use rsqlite_derive::{Bindable, Collectable};
+pub use serialized::SerializedDatabase;
 pub use sqlite3_sys as ffi;
+pub use value::Value;
+pub use version::{
+    compile_option_used, compile_options, source_id, threadsafe, version, version_number,
+    CompileOptions,
+};
 
 use core::ptr;
 use libc::c_int;
-use std::ffi::CString;
-use std::marker::PhantomData;
+use std::cell::{Cell, RefCell};
+use std::ffi::{CStr, CString};
 
 pub type Result<T> = std::result::Result<T, RsqliteError>;
 
+/// returns true if `sql` ends with a complete SQL statement, handy for REPLs
+/// that need to know whether to keep reading more input lines.
+///
+/// Text after an embedded NUL character is ignored, as sqlite would.
+///
+/// ```
+/// # use rsqlite::*;
+/// assert!(is_complete("select 1;"));
+/// assert!(!is_complete("select 1"));
+/// assert!(!is_complete("create trigger t after insert on user begin select 1;"));
+/// ```
+pub fn is_complete(sql: &str) -> bool {
+    let sql = sql.split('\0').next().unwrap_or_default();
+    let sql = CString::new(sql).expect("NUL characters are already stripped");
+    unsafe { ffi::sqlite3_complete(sql.as_ptr()) != 0 }
+}
+
 pub struct Database {
     pub db: *mut ffi::sqlite3,
+    read_only_guard: Cell<bool>,
+    max_size_set: Cell<bool>,
+    busy_handler: Cell<Option<Box<busy::BusyHandler>>>,
+    update_hook: Cell<Option<Box<hooks::UpdateHook>>>,
+    trace_hook: Cell<Option<Box<hooks::TraceHook>>>,
+    progress_handler: Cell<Option<Box<hooks::ProgressHandler>>>,
+    wal_hook: Cell<Option<Box<hooks::WalHook>>>,
+    statement_cache: RefCell<cache::StatementCache>,
+    strict_types: Cell<bool>,
+    unchecked_utf8: Cell<bool>,
+    exact_columns: Cell<bool>,
+    optimize_on_close: Cell<bool>,
 }
 
 pub struct Statement<'a> {
     pub stmt: *mut ffi::sqlite3_stmt,
     column_count: c_int,
-    _marker: PhantomData<&'a ()>,
+    database: &'a Database,
+    /// incremented on every row stepped by `Rows`, to detect stale `Row`s
+    generation: Cell<u64>,
+    /// whether `bindable::Static` may bind without copying
+    static_bindings: Cell<bindable::StaticBindings>,
 }
 
 impl Database {
@@ -251,17 +369,44 @@ impl Database {
     /// # Ok::<(), RsqliteError>(())
     /// ```
     pub fn open_with_flags(path: &str, flags: c_int) -> Result<Database> {
+        Database::open_v2(path, flags, None)
+    }
+
+    /// `sqlite3_open_v2` with an optional VFS name
+    pub(crate) fn open_v2(path: &str, flags: c_int, vfs: Option<&str>) -> Result<Database> {
         let path = CString::new(path)?;
+        let vfs = vfs.map(CString::new).transpose()?;
+        let vfs = vfs.as_ref().map(|vfs| vfs.as_ptr()).unwrap_or(ptr::null());
         let mut db = ptr::null_mut();
-        let retcode = unsafe { ffi::sqlite3_open_v2(path.as_ptr(), &mut db, flags, ptr::null()) };
+        let retcode = unsafe { ffi::sqlite3_open_v2(path.as_ptr(), &mut db, flags, vfs) };
 
         // Drop will close this if it is open_v2 has failed
-        let database = Database { db };
+        let database = Database {
+            db,
+            read_only_guard: Cell::new(false),
+            max_size_set: Cell::new(false),
+            busy_handler: Cell::new(None),
+            update_hook: Cell::new(None),
+            trace_hook: Cell::new(None),
+            progress_handler: Cell::new(None),
+            wal_hook: Cell::new(None),
+            statement_cache: RefCell::new(cache::StatementCache::new()),
+            strict_types: Cell::new(false),
+            unchecked_utf8: Cell::new(false),
+            exact_columns: Cell::new(false),
+            optimize_on_close: Cell::new(false),
+        };
 
-        match retcode {
-            ffi::SQLITE_OK => Ok(database),
-            other => Err(other.into()),
+        if retcode != ffi::SQLITE_OK {
+            return Err(database.error(retcode));
         }
+
+        #[cfg(feature = "regexp")]
+        database.register_regexp()?;
+        #[cfg(feature = "carray")]
+        database.register_carray()?;
+
+        Ok(database)
     }
 
     /// prepare a query to be executed
@@ -274,24 +419,64 @@ impl Database {
     /// # Ok::<(), RsqliteError>(())
     /// ```
     pub fn prepare(&self, sql: &str) -> Result<Statement<'_>> {
+        self.prepare_v3(sql, 0)
+    }
+
+    /// prepare a query that will be reused many times, like `prepare` but
+    /// hints sqlite (`SQLITE_PREPARE_PERSISTENT`) to allocate it for the long term.
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// # let database = Database::open(":memory:")?;
+    /// let mut statement = database.prepare_persistent("select ? * 2")?;
+    /// for i in 0..3 {
+    ///     let doubled: i32 = statement.collect(i)?;
+    ///     assert!(doubled == i * 2);
+    /// }
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn prepare_persistent(&self, sql: &str) -> Result<Statement<'_>> {
+        self.prepare_v3(sql, ffi::SQLITE_PREPARE_PERSISTENT as u32)
+    }
+
+    /// `sqlite3_prepare_v3` with `SQLITE_PREPARE_*` flags
+    fn prepare_v3(&self, sql: &str, flags: u32) -> Result<Statement<'_>> {
         let sql = CString::new(sql)?;
         let mut stmt = ptr::null_mut();
         let len = sql.as_bytes_with_nul().len() as i32;
         let retcode = unsafe {
-            ffi::sqlite3_prepare_v2(self.db, sql.as_ptr(), len, &mut stmt, ptr::null_mut())
+            ffi::sqlite3_prepare_v3(
+                self.db,
+                sql.as_ptr(),
+                len,
+                flags,
+                &mut stmt,
+                ptr::null_mut(),
+            )
         };
 
         match retcode {
-            ffi::SQLITE_OK => Ok(Statement {
-                column_count: unsafe { ffi::sqlite3_column_count(stmt) },
-                stmt,
-                _marker: PhantomData,
-            }),
+            ffi::SQLITE_OK => {
+                let statement = Statement {
+                    column_count: unsafe { ffi::sqlite3_column_count(stmt) },
+                    stmt,
+                    database: self,
+                    generation: Cell::new(0),
+                    static_bindings: Cell::new(bindable::StaticBindings::Disallowed),
+                };
+                self.check_read_only(&statement)?;
+                Ok(statement)
+            }
             other => {
                 unsafe {
                     ffi::sqlite3_finalize(stmt);
                 }
-                Err(other.into())
+                match other {
+                    ffi::SQLITE_AUTH if self.read_only_guard.get() => {
+                        Err(RsqliteError::ReadOnlyViolation)
+                    }
+                    other => Err(self.error(other)),
+                }
             }
         }
     }
@@ -300,11 +485,48 @@ impl Database {
     ///
     /// It is expected that the query does to returns any data,
     /// if you need to return data, you should use `.query()`.
-    pub fn execute(&self, sql: &str, params: impl Bindable) -> Result<()> {
+    ///
+    /// Returns the number of rows changed by an INSERT, UPDATE or DELETE statement.
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// # let database = Database::open(":memory:")?;
+    /// database.execute("create table user (age int)", ())?;
+    /// database.execute("insert into user values (20), (30), (40)", ())?;
+    /// let changed = database.execute("update user set age = age + 1 where age > ?", (25))?;
+    /// assert!(changed == 2);
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn execute(&self, sql: &str, params: impl Bindable) -> Result<i64> {
         let mut statement = self.prepare(sql)?;
         statement.execute(params)
     }
 
+    /// Execute an INSERT and return the rowid of the new row.
+    ///
+    /// For other generated values, collect a `RETURNING` clause instead.
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// # let database = Database::open(":memory:")?;
+    /// database.execute("create table user (id integer primary key, name text)", ())?;
+    /// let id = database.insert("insert into user (name) values (?)", "amin")?;
+    /// assert!(id == 1);
+    ///
+    /// let (id, name): (i64, String) =
+    ///     database.collect("insert into user (name) values (?) returning id, name", "bob")?;
+    /// assert!(id == 2 && name == "bob");
+    ///
+    /// let ids: Vec<i64> =
+    ///     database.collect_vec("insert into user (name) values ('a'), ('b') returning id", ())?;
+    /// assert!(ids == vec![3, 4]);
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn insert(&self, sql: &str, params: impl Bindable) -> Result<i64> {
+        let mut statement = self.prepare(sql)?;
+        statement.insert(params)
+    }
+
     /// Execute a query and collect the results.
     ///
     /// Your query must return the same column count as type `R`
@@ -325,6 +547,100 @@ impl Database {
         statement.collect(params)
     }
 
+    /// Execute a query that must return exactly one row and collect it.
+    ///
+    /// Fails with `NoRows` or `TooManyRows` instead of silently taking the first row.
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// # let database = Database::open(":memory:")?;
+    /// database.execute("create table user (id int, name text)", ())?;
+    /// database.execute("insert into user values (1, 'amin'), (2, 'amin')", ())?;
+    ///
+    /// let id: i32 = database.collect_one("select id from user where id = ?", 1)?;
+    /// assert!(id == 1);
+    /// assert!(matches!(
+    ///     database.collect_one::<i32>("select id from user where id = ?", 3),
+    ///     Err(RsqliteError::NoRows)
+    /// ));
+    /// assert!(matches!(
+    ///     database.collect_one::<i32>("select id from user where name = ?", "amin"),
+    ///     Err(RsqliteError::TooManyRows)
+    /// ));
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn collect_one<R>(&self, sql: &str, params: impl Bindable) -> Result<R>
+    where
+        R: Collectable,
+    {
+        let mut statement = self.prepare(sql)?;
+        statement.collect_one(params)
+    }
+
+    /// Execute a query and report whether it returned at least one row.
+    ///
+    /// Only the first row is stepped, the rest of the result set is never computed.
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// # let database = Database::open(":memory:")?;
+    /// database.execute("create table user (name text)", ())?;
+    /// database.execute("insert into user values ('amin')", ())?;
+    /// assert!(database.exists("select 1 from user where name = ?", "amin")?);
+    /// assert!(!database.exists("select 1 from user where name = ?", "bob")?);
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn exists(&self, sql: &str, params: impl Bindable) -> Result<bool> {
+        let mut statement = self.prepare(sql)?;
+        statement.exists(params)
+    }
+
+    /// Execute a query and collect every row of the result set.
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// # let database = Database::open(":memory:")?;
+    /// let rows: Vec<(i32, String)> =
+    ///     database.collect_vec("select 1, 'one' union select 2, 'two'", ())?;
+    /// assert!(rows == vec![(1, "one".to_owned()), (2, "two".to_owned())]);
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn collect_vec<R>(&self, sql: &str, params: impl Bindable) -> Result<Vec<R>>
+    where
+        R: Collectable,
+    {
+        let mut statement = self.prepare(sql)?;
+        statement.collect_vec(params)
+    }
+
+    /// Execute a two column query and collect it into a map, e.g. `HashMap` or `BTreeMap`.
+    ///
+    /// The first column is the key, later rows overwrite earlier ones with the same key.
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// use std::collections::{BTreeMap, HashMap};
+    /// # let database = Database::open(":memory:")?;
+    /// database.execute("create table color (name text, hex text)", ())?;
+    /// database.execute("insert into color values ('red', '#f00'), ('blue', '#00f')", ())?;
+    ///
+    /// let colors: HashMap<String, String> = database.collect_map("select name, hex from color", ())?;
+    /// assert!(colors["red"] == "#f00");
+    ///
+    /// let colors: BTreeMap<String, String> = database.collect_map("select name, hex from color", ())?;
+    /// assert!(colors.keys().collect::<Vec<_>>() == vec!["blue", "red"]);
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn collect_map<M, K, V>(&self, sql: &str, params: impl Bindable) -> Result<M>
+    where
+        M: Default + Extend<(K, V)>,
+        K: Collectable,
+        V: Collectable,
+    {
+        let mut statement = self.prepare(sql)?;
+        statement.collect_map(params)
+    }
+
     /// for_each iterates over multile rows of data using a colusure
     ///
     /// ```
@@ -345,66 +661,330 @@ impl Database {
         let mut statement = self.prepare(sql)?;
         statement.for_each(params, iterable)
     }
+
+    /// the rowid of the most recent successful INSERT on this connection
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// # let database = Database::open(":memory:")?;
+    /// database.execute("create table user (id integer primary key, name text)", ())?;
+    /// database.execute("insert into user(name) values (?)", ("amin"))?;
+    /// assert!(database.last_insert_rowid() == 1);
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn last_insert_rowid(&self) -> i64 {
+        unsafe { ffi::sqlite3_last_insert_rowid(self.db) }
+    }
+
+    /// sets the value `last_insert_rowid` reports, without inserting anything
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// # let database = Database::open(":memory:")?;
+    /// database.set_last_insert_rowid(42);
+    /// assert!(database.last_insert_rowid() == 42);
+    /// assert!(database.collect::<i64>("select last_insert_rowid()", ())? == 42);
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn set_last_insert_rowid(&self, rowid: i64) {
+        unsafe { ffi::sqlite3_set_last_insert_rowid(self.db, rowid) }
+    }
+
+    /// rows changed by the most recent INSERT, UPDATE or DELETE on this connection,
+    /// not counting changes made by triggers or foreign key actions
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// # let database = Database::open(":memory:")?;
+    /// database.execute("create table user (name text)", ())?;
+    /// database.execute("insert into user values ('amin'), ('bob')", ())?;
+    /// assert!(database.changes() == 2);
+    /// database.execute("delete from user where name = 'bob'", ())?;
+    /// assert!(database.changes() == 1);
+    /// assert!(database.total_changes() == 3);
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn changes(&self) -> i64 {
+        unsafe { ffi::sqlite3_changes64(self.db) }
+    }
+
+    /// rows changed by every INSERT, UPDATE or DELETE since the connection was opened,
+    /// including changes made by triggers and foreign key actions
+    pub fn total_changes(&self) -> i64 {
+        unsafe { ffi::sqlite3_total_changes64(self.db) }
+    }
+
+    /// converts an error code returned by this connection into an `RsqliteError`
+    pub(crate) fn error(&self, ecode: c_int) -> RsqliteError {
+        match ecode {
+            ffi::SQLITE_FULL if self.max_size_set.get() => {
+                // read the message first, checking the limit runs statements of its own
+                let error = self.last_error(ecode);
+                if self.reached_max_size() {
+                    RsqliteError::QuotaExceeded
+                } else {
+                    error
+                }
+            }
+            // the authorizer of the guard can also deny statements while they run
+            ffi::SQLITE_AUTH if self.read_only_guard.get() => RsqliteError::ReadOnlyViolation,
+            ffi::SQLITE_IOERR
+                if unsafe { ffi::sqlite3_extended_errcode(self.db) } == ffi::SQLITE_IOERR_DATA =>
+            {
+                RsqliteError::ChecksumMismatch
+            }
+            other => self.last_error(other),
+        }
+    }
+
+    fn last_error(&self, ecode: c_int) -> RsqliteError {
+        unsafe {
+            let message = CStr::from_ptr(ffi::sqlite3_errmsg(self.db));
+            RsqliteError::new(
+                ecode,
+                ffi::sqlite3_extended_errcode(self.db),
+                message.to_string_lossy().into_owned(),
+            )
+        }
+    }
 }
 
 impl<'a> Statement<'a> {
-    pub fn execute(&mut self, params: impl Bindable) -> Result<()> {
-        params.bind(self, &mut 1)?;
-
-        let retcode = unsafe { ffi::sqlite3_step(self.stmt) };
+    /// executes the statement, returning the number of rows changed.
+    ///
+    /// The count comes from `sqlite3_changes64`, so it is only meaningful
+    /// for INSERT, UPDATE and DELETE statements.
+    pub fn execute(&mut self, params: impl Bindable) -> Result<i64> {
+        self.with_params(params, |statement| {
+            let retcode = unsafe { ffi::sqlite3_step(statement.stmt) };
 
-        let result = match retcode {
-            ffi::SQLITE_DONE => Ok(()),
-            other => Err(other.into()),
-        };
+            let result = match retcode {
+                ffi::SQLITE_DONE => Ok(statement.database.changes()),
+                other => Err(statement.database.error(other)),
+            };
 
-        let _ = unsafe { ffi::sqlite3_reset(self.stmt) };
-        result
+            let _ = unsafe { ffi::sqlite3_reset(statement.stmt) };
+            result
+        })
     }
 
     pub fn collect<R>(&mut self, params: impl Bindable) -> Result<R>
     where
         R: Collectable,
     {
-        if R::columns_needed() > self.column_count {
-            return Err(ffi::SQLITE_RANGE.into());
-        }
-        params.bind(self, &mut 1)?;
+        self.check_columns(R::columns_needed())?;
+        self.with_params(params, |statement| {
+            let result = R::step_and_collect(statement);
+
+            let _ = unsafe { ffi::sqlite3_reset(statement.stmt) };
+            result
+        })
+    }
+
+    /// executes an INSERT, returning the rowid of the new row, see `Database::insert`
+    pub fn insert(&mut self, params: impl Bindable) -> Result<i64> {
+        self.execute(params)?;
+        Ok(self.database.last_insert_rowid())
+    }
+
+    /// whether the statement returns at least one row, see `Database::exists`
+    pub fn exists(&mut self, params: impl Bindable) -> Result<bool> {
+        self.with_params(params, |statement| {
+            let result = match unsafe { ffi::sqlite3_step(statement.stmt) } {
+                ffi::SQLITE_ROW => Ok(true),
+                ffi::SQLITE_DONE => Ok(false),
+                other => Err(statement.database.error(other)),
+            };
+
+            let _ = unsafe { ffi::sqlite3_reset(statement.stmt) };
+            result
+        })
+    }
+
+    /// collects the only row of the result set, see `Database::collect_one`
+    pub fn collect_one<R>(&mut self, params: impl Bindable) -> Result<R>
+    where
+        R: Collectable,
+    {
+        self.check_columns(R::columns_needed())?;
+        self.with_params(params, |statement| {
+            let result = match unsafe { ffi::sqlite3_step(statement.stmt) } {
+                ffi::SQLITE_ROW => R::try_collect(statement, &mut 0).and_then(|row| {
+                    match unsafe { ffi::sqlite3_step(statement.stmt) } {
+                        ffi::SQLITE_DONE => Ok(row),
+                        ffi::SQLITE_ROW => Err(RsqliteError::TooManyRows),
+                        other => Err(statement.database.error(other)),
+                    }
+                }),
+                ffi::SQLITE_DONE => Err(RsqliteError::NoRows),
+                other => Err(statement.database.error(other)),
+            };
 
-        let result = R::step_and_collect(self);
+            let _ = unsafe { ffi::sqlite3_reset(statement.stmt) };
+            result
+        })
+    }
 
-        let _ = unsafe { ffi::sqlite3_reset(self.stmt) };
-        result
+    pub fn collect_vec<R>(&mut self, params: impl Bindable) -> Result<Vec<R>>
+    where
+        R: Collectable,
+    {
+        let mut rows = vec![];
+        self.for_each(params, |row: R| rows.push(row))?;
+        Ok(rows)
+    }
+
+    /// collects `(key, value)` rows into a map, see `Database::collect_map`
+    pub fn collect_map<M, K, V>(&mut self, params: impl Bindable) -> Result<M>
+    where
+        M: Default + Extend<(K, V)>,
+        K: Collectable,
+        V: Collectable,
+    {
+        self.fold(params, M::default(), |mut map, row: (K, V)| {
+            map.extend(std::iter::once(row));
+            map
+        })
     }
 
     pub fn for_each<I, T>(&mut self, params: impl Bindable, mut iterable: I) -> Result<()>
     where
         I: Iterable<(), T>,
     {
-        if I::columns_needed() > self.column_count {
-            return Err(ffi::SQLITE_RANGE.into());
-        }
-        params.bind(self, &mut 1)?;
+        self.check_columns(I::columns_needed())?;
+        self.with_params(params, |statement| {
+            let result = loop {
+                let retcode = unsafe { ffi::sqlite3_step(statement.stmt) };
+                let mut index = 0;
+
+                match retcode {
+                    ffi::SQLITE_ROW => {
+                        if let Err(error) = iterable.iterate(statement, &mut index) {
+                            break Err(error);
+                        }
+                    }
+                    ffi::SQLITE_DONE => break Ok(()),
+                    other => break Err(statement.database.error(other)),
+                };
+            };
+
+            let _ = unsafe { ffi::sqlite3_reset(statement.stmt) };
+            result
+        })
+    }
 
-        let result = loop {
-            let retcode = unsafe { ffi::sqlite3_step(self.stmt) };
-            let mut index = 0;
+    /// folds every row into an accumulator
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// # let database = Database::open(":memory:")?;
+    /// let mut statement = database.prepare("select 1, 'a' union select 2, 'b'")?;
+    /// let (sum, names) = statement.fold((), (0, String::new()), |(sum, names), (x, name): (i32, String)| {
+    ///     (sum + x, names + &name)
+    /// })?;
+    /// assert!(sum == 3 && names == "ab");
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn fold<R, B, F>(&mut self, params: impl Bindable, init: B, mut f: F) -> Result<B>
+    where
+        R: Collectable,
+        F: FnMut(B, R) -> B,
+    {
+        self.try_fold(params, init, |acc, row| Ok(f(acc, row)))
+    }
 
-            match retcode {
-                ffi::SQLITE_ROW => iterable.iterate(self, &mut index),
-                ffi::SQLITE_DONE => break Ok(()),
-                other => break Err(other.into()),
+    /// folds every row into an accumulator, stopping at the first error returned by `f`
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// # let database = Database::open(":memory:")?;
+    /// let mut statement = database.prepare("select 1 union select 2 union select 3")?;
+    /// let sum = statement.try_fold((), 0i32, |sum, x: i32| match x {
+    ///     3 => Err(RsqliteError::NoRows),
+    ///     x => Ok(sum + x),
+    /// });
+    /// assert!(sum.is_err());
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn try_fold<R, B, F>(&mut self, params: impl Bindable, init: B, mut f: F) -> Result<B>
+    where
+        R: Collectable,
+        F: FnMut(B, R) -> Result<B>,
+    {
+        self.check_columns(R::columns_needed())?;
+        self.with_params(params, |statement| {
+            let mut acc = init;
+            let result = loop {
+                match unsafe { ffi::sqlite3_step(statement.stmt) } {
+                    ffi::SQLITE_ROW => {
+                        match R::try_collect(statement, &mut 0).and_then(|row| f(acc, row)) {
+                            Ok(next) => acc = next,
+                            Err(error) => break Err(error),
+                        }
+                    }
+                    ffi::SQLITE_DONE => break Ok(acc),
+                    other => break Err(statement.database.error(other)),
+                }
             };
-        };
 
-        let _ = unsafe { ffi::sqlite3_reset(self.stmt) };
-        result
+            let _ = unsafe { ffi::sqlite3_reset(statement.stmt) };
+            result
+        })
+    }
+
+    /// resets the statement so it runs from the start on the next step,
+    /// the bindings are kept, see `clear_bindings`
+    ///
+    /// `execute`, `collect` and the other methods taking params already reset the statement.
+    pub fn reset(&mut self) {
+        unsafe { ffi::sqlite3_reset(self.stmt) };
+    }
+
+    /// sets all parameters back to NULL
+    ///
+    /// `execute`, `collect` and the other methods taking params already clear the bindings,
+    /// so parameters left out of a call are NULL rather than stale values.
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// # let database = Database::open(":memory:")?;
+    /// let mut statement = database.prepare("select ?1, ?2")?;
+    /// (1, 2).bind(&mut statement, &mut 1)?;
+    /// assert!(statement.expanded_sql().as_deref() == Some("select 1, 2"));
+    /// statement.clear_bindings();
+    /// assert!(statement.expanded_sql().as_deref() == Some("select NULL, NULL"));
+    ///
+    /// let _: (i32, Option<i32>) = statement.collect((1, 2))?;
+    /// let row: (i32, Option<i32>) = statement.collect(3)?;
+    /// assert!(row == (3, None));
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn clear_bindings(&mut self) {
+        unsafe { ffi::sqlite3_clear_bindings(self.stmt) };
+    }
+
+    /// binds `params` and runs `run`, the bindings are cleared afterwards
+    /// so the next call never sees stale values or a pointer to borrowed `bindable::Static` data.
+    fn with_params<T>(
+        &mut self,
+        params: impl Bindable,
+        run: impl FnOnce(&mut Self) -> Result<T>,
+    ) -> Result<T> {
+        use bindable::StaticBindings;
+
+        let guard = bindable::ClearBindings(self);
+        guard.0.static_bindings.set(StaticBindings::Allowed);
+        params.bind(guard.0, &mut 1).and_then(|()| run(guard.0))
     }
 }
 
 impl Drop for Database {
     /// closes the `*mut sqlite3` handle on Drop
     fn drop(&mut self) {
+        self.statement_cache.get_mut().clear();
+        if self.optimize_on_close.get() && !self.db.is_null() {
+            let _ = self.execute_batch("pragma optimize");
+        }
         unsafe {
             ffi::sqlite3_close(self.db);
             self.db = ptr::null_mut();
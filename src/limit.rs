@@ -0,0 +1,71 @@
+//! Run-time limits of a connection, see `Database::limit`
+use super::Database;
+use libc::c_int;
+use sqlite3_sys as ffi;
+
+/// The categories of `sqlite3_limit`, see https://sqlite.org/c3ref/c_limit_attached.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    /// bytes in a string or BLOB, or in a row
+    Length,
+    /// bytes in the text of an SQL statement
+    SqlLength,
+    /// columns in a table, index, view or result set, and terms in ORDER BY or GROUP BY
+    Column,
+    /// depth of the parse tree of an expression
+    ExprDepth,
+    /// terms in a compound SELECT
+    CompoundSelect,
+    /// instructions of the virtual machine program of a statement
+    VdbeOp,
+    /// arguments of a function
+    FunctionArg,
+    /// attached databases
+    Attached,
+    /// bytes in the pattern of LIKE or GLOB
+    LikePatternLength,
+    /// the largest parameter index
+    VariableNumber,
+    /// depth of recursive triggers
+    TriggerDepth,
+    /// auxiliary worker threads of a statement
+    WorkerThreads,
+}
+
+impl Limit {
+    fn as_raw(self) -> c_int {
+        match self {
+            Limit::Length => ffi::SQLITE_LIMIT_LENGTH,
+            Limit::SqlLength => ffi::SQLITE_LIMIT_SQL_LENGTH,
+            Limit::Column => ffi::SQLITE_LIMIT_COLUMN,
+            Limit::ExprDepth => ffi::SQLITE_LIMIT_EXPR_DEPTH,
+            Limit::CompoundSelect => ffi::SQLITE_LIMIT_COMPOUND_SELECT,
+            Limit::VdbeOp => ffi::SQLITE_LIMIT_VDBE_OP,
+            Limit::FunctionArg => ffi::SQLITE_LIMIT_FUNCTION_ARG,
+            Limit::Attached => ffi::SQLITE_LIMIT_ATTACHED,
+            Limit::LikePatternLength => ffi::SQLITE_LIMIT_LIKE_PATTERN_LENGTH,
+            Limit::VariableNumber => ffi::SQLITE_LIMIT_VARIABLE_NUMBER,
+            Limit::TriggerDepth => ffi::SQLITE_LIMIT_TRIGGER_DEPTH,
+            Limit::WorkerThreads => ffi::SQLITE_LIMIT_WORKER_THREADS,
+        }
+    }
+}
+
+impl Database {
+    /// returns the current value of `limit`, setting it to `value` if given
+    ///
+    /// Values above the compile-time maximum are silently truncated to it.
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// use rsqlite::limit::Limit;
+    /// # let database = Database::open(":memory:")?;
+    /// database.limit(Limit::SqlLength, Some(20));
+    /// assert!(database.limit(Limit::SqlLength, None) == 20);
+    /// assert!(database.prepare("select 'a string longer than the limit'").is_err());
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn limit(&self, limit: Limit, value: Option<i32>) -> i32 {
+        unsafe { ffi::sqlite3_limit(self.db, limit.as_raw(), value.unwrap_or(-1)) }
+    }
+}
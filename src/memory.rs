@@ -0,0 +1,53 @@
+//! Bounding and releasing the heap memory used by sqlite
+use super::{Database, Result};
+use sqlite3_sys as ffi;
+
+/// sets the advisory heap limit in bytes, returning the previous one,
+/// 0 means no limit and a negative `limit` only reads it.
+///
+/// Past the soft limit sqlite recycles page cache memory before allocating more,
+/// allocations still succeed.
+///
+/// ```
+/// use rsqlite::memory::{hard_heap_limit, soft_heap_limit};
+///
+/// let previous = soft_heap_limit(64 << 20);
+/// assert!(soft_heap_limit(-1) == 64 << 20);
+/// # soft_heap_limit(previous);
+/// assert!(hard_heap_limit(-1) >= 0);
+/// ```
+pub fn soft_heap_limit(limit: i64) -> i64 {
+    unsafe { ffi::sqlite3_soft_heap_limit64(limit) }
+}
+
+/// sets the heap limit in bytes, returning the previous one,
+/// 0 means no limit and a negative `limit` only reads it.
+///
+/// Past the hard limit allocations fail with `Nomem`.
+pub fn hard_heap_limit(limit: i64) -> i64 {
+    unsafe { ffi::sqlite3_hard_heap_limit64(limit) }
+}
+
+/// tries to free `bytes` of heap held by every connection, returning the bytes freed
+///
+/// Only has an effect if sqlite was compiled with `SQLITE_ENABLE_MEMORY_MANAGEMENT`.
+pub fn release_memory(bytes: i32) -> i32 {
+    unsafe { ffi::sqlite3_release_memory(bytes) }
+}
+
+impl Database {
+    /// frees as much of the heap held by this connection as possible, e.g. unused cache pages
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// # let database = Database::open(":memory:")?;
+    /// database.release_memory()?;
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn release_memory(&self) -> Result<()> {
+        match unsafe { ffi::sqlite3_db_release_memory(self.db) } {
+            ffi::SQLITE_OK => Ok(()),
+            other => Err(self.error(other)),
+        }
+    }
+}
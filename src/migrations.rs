@@ -0,0 +1,200 @@
+//! Schema migrations tracked with `pragma user_version`
+//!
+//! Migration `n` (counting from 1) upgrades the schema from version `n - 1` to `n`.
+//! Each migration runs in its own transaction, together with the update of `user_version`.
+//!
+//! ```
+//! # use rsqlite::*;
+//! use rsqlite::migrations::{Migration, Migrations};
+//!
+//! let migrations = Migrations::new()
+//!     .migration(Migration::sql("create table user(name text)").down_sql("drop table user"))
+//!     .migration(Migration::function(|database| {
+//!         database.execute("insert into user values ('admin')", ())?;
+//!         Ok(())
+//!     }));
+//!
+//! # let database = Database::open(":memory:")?;
+//! database.migrate(&migrations)?;
+//! assert!(database.user_version()? == 2);
+//!
+//! // the second migration has no down step
+//! assert!(database.migrate_to(&migrations, 0).is_err());
+//! assert!(database.user_version()? == 2);
+//! # Ok::<(), RsqliteError>(())
+//! ```
+use super::{Database, Result, RsqliteError};
+use libc::c_char;
+use sqlite3_sys as ffi;
+use std::ffi::CString;
+use std::ptr;
+
+type MigrationFn = dyn Fn(&Database) -> Result<()>;
+
+enum Step {
+    Sql(String),
+    Function(Box<MigrationFn>),
+}
+
+/// A single schema change, with an optional way to undo it
+pub struct Migration {
+    up: Step,
+    down: Option<Step>,
+}
+
+/// An ordered list of migrations
+#[derive(Default)]
+pub struct Migrations {
+    migrations: Vec<Migration>,
+}
+
+impl Migration {
+    /// a migration running `sql`, which may contain several statements
+    pub fn sql(sql: &str) -> Migration {
+        Migration {
+            up: Step::Sql(sql.to_owned()),
+            down: None,
+        }
+    }
+
+    /// a migration running `function`
+    pub fn function(function: impl Fn(&Database) -> Result<()> + 'static) -> Migration {
+        Migration {
+            up: Step::Function(Box::new(function)),
+            down: None,
+        }
+    }
+
+    /// undoes this migration by running `sql`
+    pub fn down_sql(mut self, sql: &str) -> Migration {
+        self.down = Some(Step::Sql(sql.to_owned()));
+        self
+    }
+
+    /// undoes this migration by running `function`
+    pub fn down_function(
+        mut self,
+        function: impl Fn(&Database) -> Result<()> + 'static,
+    ) -> Migration {
+        self.down = Some(Step::Function(Box::new(function)));
+        self
+    }
+}
+
+impl Migrations {
+    pub fn new() -> Migrations {
+        Migrations::default()
+    }
+
+    /// appends the migration to version `len() + 1`
+    pub fn migration(mut self, migration: Migration) -> Migrations {
+        self.migrations.push(migration);
+        self
+    }
+
+    /// the number of migrations, which is also the latest version
+    pub fn len(&self) -> usize {
+        self.migrations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.migrations.is_empty()
+    }
+}
+
+impl Database {
+    /// applies every pending migration
+    pub fn migrate(&self, migrations: &Migrations) -> Result<()> {
+        self.migrate_to(migrations, migrations.len() as i32)
+    }
+
+    /// upgrades or downgrades the schema to `version`
+    ///
+    /// Migrations that succeed are kept when a later one fails.
+    /// A downgrade fails without running anything if one of its migrations has no down step.
+    pub fn migrate_to(&self, migrations: &Migrations, version: i32) -> Result<()> {
+        loop {
+            self.execute("begin immediate", ())?;
+            let result = self
+                .migrate_step(migrations, version)
+                .and_then(|ran| self.execute("commit", ()).map(|_| ran));
+
+            match result {
+                Ok(true) => continue,
+                Ok(false) => return Ok(()),
+                Err(error) => {
+                    if !self.is_autocommit() {
+                        let _ = self.execute("rollback", ());
+                    }
+                    return Err(error);
+                }
+            }
+        }
+    }
+
+    /// runs the next migration towards `version` inside the open transaction,
+    /// returning false once the schema is already at `version`
+    ///
+    /// The current version is read inside the transaction,
+    /// so concurrent migrations of the same database never run a step twice.
+    fn migrate_step(&self, migrations: &Migrations, version: i32) -> Result<bool> {
+        let current = self.user_version()?;
+        let latest = migrations.len() as i32;
+        if current > latest || version < 0 || version > latest {
+            return Err(migration_error(format!(
+                "can not migrate from version {} to {}, there are {} migrations",
+                current, version, latest
+            )));
+        }
+
+        let (step, next) = if version > current {
+            (&migrations.migrations[current as usize].up, current + 1)
+        } else if version < current {
+            let downgrades = &migrations.migrations[version as usize..current as usize];
+            if let Some(index) = downgrades
+                .iter()
+                .position(|migration| migration.down.is_none())
+            {
+                return Err(migration_error(format!(
+                    "migration {} can not be undone",
+                    version + index as i32 + 1
+                )));
+            }
+            match &migrations.migrations[current as usize - 1].down {
+                Some(down) => (down, current - 1),
+                None => unreachable!(),
+            }
+        } else {
+            return Ok(false);
+        };
+
+        match step {
+            Step::Sql(sql) => self.execute_batch(sql)?,
+            Step::Function(function) => function(self)?,
+        }
+        self.set_user_version(next)?;
+        Ok(true)
+    }
+
+    /// runs every statement of `sql`, ignoring the rows they return
+    pub(crate) fn execute_batch(&self, sql: &str) -> Result<()> {
+        let sql = CString::new(sql)?;
+        let ecode = unsafe {
+            ffi::sqlite3_exec(
+                self.db,
+                sql.as_ptr() as *const c_char,
+                None,
+                ptr::null_mut(),
+                ptr::null_mut(),
+            )
+        };
+        match ecode {
+            ffi::SQLITE_OK => Ok(()),
+            other => Err(self.error(other)),
+        }
+    }
+}
+
+fn migration_error(message: String) -> RsqliteError {
+    RsqliteError::new(ffi::SQLITE_ERROR, ffi::SQLITE_ERROR, message)
+}
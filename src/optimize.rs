@@ -0,0 +1,52 @@
+use super::quote::quote_identifier;
+use super::{Database, Result};
+
+impl Database {
+    /// gathers query planner statistics with `analyze`, for every table if `table` is `None`
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// # let database = Database::open(":memory:")?;
+    /// database.execute("create table user (name text)", ())?;
+    /// database.execute("create index user_name on user (name)", ())?;
+    /// database.execute("insert into user values ('amin'), ('bob')", ())?;
+    /// database.analyze(Some("user"))?;
+    ///
+    /// let stats: i32 = database.collect("select count(*) from sqlite_stat1", ())?;
+    /// assert!(stats > 0);
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn analyze(&self, table: Option<&str>) -> Result<()> {
+        let sql = match table {
+            Some(table) => format!("analyze {}", quote_identifier(table)),
+            None => "analyze".to_owned(),
+        };
+        self.execute_batch(&sql)
+    }
+
+    /// runs `pragma optimize`, which analyzes the tables whose statistics look stale
+    ///
+    /// `analysis_limit` sets `pragma analysis_limit`, the rows examined per index,
+    /// so large databases are analyzed approximately but quickly. 400 is a common choice.
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// # let database = Database::open(":memory:")?;
+    /// database.set_optimize_on_close(true);
+    /// database.optimize(Some(400))?;
+    /// assert!(database.pragma_get::<i32>("analysis_limit")? == 400);
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn optimize(&self, analysis_limit: Option<u32>) -> Result<()> {
+        if let Some(limit) = analysis_limit {
+            self.pragma_set("analysis_limit", limit as i64)?;
+        }
+        self.execute_batch("pragma optimize")
+    }
+
+    /// runs `pragma optimize` right before the connection is closed, as recommended
+    /// for short-lived connections, errors are ignored
+    pub fn set_optimize_on_close(&self, enabled: bool) {
+        self.optimize_on_close.set(enabled);
+    }
+}
@@ -0,0 +1,159 @@
+//! A builder for opening databases, see `Database::options()`
+use super::{Database, Result};
+use libc::c_int;
+use sqlite3_sys as ffi;
+
+/// Options and flags used to open a database
+///
+/// Like `std::fs::OpenOptions`, the database is opened read-write
+/// and is not created unless `create()` is called.
+#[derive(Debug, Clone, Default)]
+pub struct OpenOptions {
+    read_only: bool,
+    create: bool,
+    flags: c_int,
+    uri_params: Vec<(String, String)>,
+    vfs: Option<String>,
+    max_size: Option<i64>,
+}
+
+impl OpenOptions {
+    pub fn new() -> OpenOptions {
+        OpenOptions::default()
+    }
+
+    /// opens the database with `SQLITE_OPEN_READONLY`, `create()` is ignored
+    pub fn read_only(&mut self) -> &mut OpenOptions {
+        self.read_only = true;
+        self
+    }
+
+    /// creates the database if it does not exist
+    pub fn create(&mut self) -> &mut OpenOptions {
+        self.create = true;
+        self
+    }
+
+    /// adds `ffi::SQLITE_OPEN_*` flags, like `SQLITE_OPEN_NOMUTEX`
+    pub fn flags(&mut self, flags: c_int) -> &mut OpenOptions {
+        self.flags |= flags;
+        self
+    }
+
+    /// adds a query parameter to the URI filename, like `cache=shared` or `immutable=1`
+    pub fn uri_param(&mut self, key: &str, value: &str) -> &mut OpenOptions {
+        self.uri_params.push((key.to_owned(), value.to_owned()));
+        self
+    }
+
+    /// opens the database through the VFS registered as `vfs`
+    pub fn vfs(&mut self, vfs: &str) -> &mut OpenOptions {
+        self.vfs = Some(vfs.to_owned());
+        self
+    }
+
+    /// limits the size of the main database with `Database::set_max_size` right after opening,
+    /// the limit belongs to the connection and is not stored in the file
+    pub fn max_size(&mut self, bytes: i64) -> &mut OpenOptions {
+        self.max_size = Some(bytes);
+        self
+    }
+
+    /// the filename and flags given to `sqlite3_open_v2`
+    fn filename_and_flags(&self, path: &str) -> (String, c_int) {
+        let mut flags = match (self.read_only, self.create) {
+            (true, _) => ffi::SQLITE_OPEN_READONLY,
+            (false, true) => ffi::SQLITE_OPEN_READWRITE | ffi::SQLITE_OPEN_CREATE,
+            (false, false) => ffi::SQLITE_OPEN_READWRITE,
+        };
+        flags |= self.flags;
+        if self.uri_params.is_empty() {
+            return (path.to_owned(), flags);
+        }
+
+        let query = self
+            .uri_params
+            .iter()
+            .map(|(key, value)| format!("{}={}", encode(key, "&=#"), encode(value, "&=#")))
+            .collect::<Vec<_>>()
+            .join("&");
+        let filename = format!("file:{}?{}", encode(path, "?#"), query);
+        (filename, flags | ffi::SQLITE_OPEN_URI)
+    }
+
+    /// opens the database at `path`
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// let mut options = Database::options();
+    /// options.create().uri_param("mode", "memory").uri_param("cache", "shared");
+    ///
+    /// let first = options.open("options-example")?;
+    /// first.execute("create table t(x integer)", ())?;
+    /// let second = options.open("options-example")?;
+    /// let count: i32 = second.collect("select count(*) from t", ())?;
+    ///
+    /// assert!(count == 0);
+    ///
+    /// let read_only = Database::options().read_only().open(":memory:")?;
+    /// assert!(read_only.execute("create table t(x integer)", ()).is_err());
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn open(&self, path: &str) -> Result<Database> {
+        let (filename, flags) = self.filename_and_flags(path);
+        let database = Database::open_v2(&filename, flags, self.vfs.as_deref())?;
+        if let Some(bytes) = self.max_size {
+            database.set_max_size(bytes)?;
+        }
+        Ok(database)
+    }
+}
+
+/// percent-encodes `%`, the characters in `reserved` and anything that is not printable ascii
+fn encode(text: &str, reserved: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        if byte == b'%' || !byte.is_ascii_graphic() || reserved.as_bytes().contains(&byte) {
+            encoded.push_str(&format!("%{:02X}", byte));
+        } else {
+            encoded.push(byte as char);
+        }
+    }
+    encoded
+}
+
+/// the URI of the in-memory database shared by every connection opened with `name`
+pub(crate) fn shared_memory_uri(name: &str) -> String {
+    format!("file:{}?mode=memory&cache=shared", encode(name, "?#"))
+}
+
+impl Database {
+    /// a builder for opening databases with flags, URI parameters and a VFS
+    pub fn options() -> OpenOptions {
+        OpenOptions::new()
+    }
+
+    /// opens the in-memory database named `name`, shared by every connection
+    /// of the process that opens the same name, e.g. the connections of a pool
+    ///
+    /// The database is deleted once its last connection is closed.
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// let first = Database::open_shared_memory("shared-example")?;
+    /// first.execute("create table user (name text)", ())?;
+    /// first.execute("insert into user values ('amin')", ())?;
+    ///
+    /// let second = Database::open_shared_memory("shared-example")?;
+    /// let count: i32 = second.collect("select count(*) from user", ())?;
+    /// assert!(count == 1);
+    ///
+    /// let other = Database::open_shared_memory("other")?;
+    /// assert!(other.collect::<i32>("select count(*) from user", ()).is_err());
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn open_shared_memory(name: &str) -> Result<Database> {
+        let flags = ffi::SQLITE_OPEN_READWRITE | ffi::SQLITE_OPEN_CREATE | ffi::SQLITE_OPEN_URI;
+        Database::open_with_flags(&shared_memory_uri(name), flags)
+    }
+}
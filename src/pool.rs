@@ -0,0 +1,142 @@
+//! Connection pooling through the `r2d2` and `deadpool` features
+//!
+//! Pooled connections are `SerializedDatabase`s, since pools hand them out across threads.
+use super::options::shared_memory_uri;
+use super::{Database, Result, RsqliteError, SerializedDatabase};
+use libc::c_int;
+use sqlite3_sys as ffi;
+use std::sync::Arc;
+
+type Init = dyn Fn(&Database) -> Result<()> + Send + Sync;
+
+/// Opens the connections of a pool, see the `r2d2` and `deadpool` features
+///
+/// ```
+/// # use rsqlite::*;
+/// # #[cfg(feature = "r2d2")] {
+/// use rsqlite::pool::ConnectionManager;
+///
+/// let manager = ConnectionManager::file("file:pool?mode=memory&cache=shared")
+///     .with_flags(ffi::SQLITE_OPEN_READWRITE | ffi::SQLITE_OPEN_CREATE | ffi::SQLITE_OPEN_URI)
+///     .with_init(|database| database.execute("pragma foreign_keys = on", ()).map(|_| ()));
+/// let pool = r2d2::Pool::builder().max_size(2).build(manager).unwrap();
+///
+/// let connection = pool.get().unwrap();
+/// let foreign_keys: bool = connection.lock().collect("pragma foreign_keys", ())?;
+/// assert!(foreign_keys);
+/// # }
+/// # Ok::<(), RsqliteError>(())
+/// ```
+///
+/// ```
+/// # use rsqlite::*;
+/// # #[cfg(feature = "deadpool")] {
+/// use rsqlite::pool::ConnectionManager;
+/// use deadpool::managed::Pool;
+///
+/// let pool: Pool<ConnectionManager> = Pool::builder(ConnectionManager::memory())
+///     .max_size(2)
+///     .build()
+///     .unwrap();
+/// # let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+/// # runtime.block_on(async {
+/// let connection = pool.get().await.unwrap();
+/// let x: i32 = connection.lock().collect("select 42", ())?;
+/// assert!(x == 42);
+/// # Ok::<(), RsqliteError>(())
+/// # })?;
+/// # }
+/// # Ok::<(), RsqliteError>(())
+/// ```
+#[derive(Clone)]
+pub struct ConnectionManager {
+    path: String,
+    flags: c_int,
+    init: Option<Arc<Init>>,
+}
+
+impl ConnectionManager {
+    /// a manager for the database file at `path`, opened read-write and created if missing
+    pub fn file(path: &str) -> ConnectionManager {
+        ConnectionManager {
+            path: path.to_owned(),
+            flags: ffi::SQLITE_OPEN_READWRITE | ffi::SQLITE_OPEN_CREATE,
+            init: None,
+        }
+    }
+
+    /// a manager for private in-memory databases, each connection gets its own
+    pub fn memory() -> ConnectionManager {
+        ConnectionManager::file(":memory:")
+    }
+
+    /// a manager whose connections share the in-memory database named `name`,
+    /// see `Database::open_shared_memory`
+    pub fn shared_memory(name: &str) -> ConnectionManager {
+        ConnectionManager::file(&shared_memory_uri(name))
+            .with_flags(ffi::SQLITE_OPEN_READWRITE | ffi::SQLITE_OPEN_CREATE | ffi::SQLITE_OPEN_URI)
+    }
+
+    /// opens the connections with `flags` instead
+    pub fn with_flags(mut self, flags: c_int) -> ConnectionManager {
+        self.flags = flags;
+        self
+    }
+
+    /// runs `init` on every new connection, e.g. to set pragmas or register functions
+    pub fn with_init<F>(mut self, init: F) -> ConnectionManager
+    where
+        F: Fn(&Database) -> Result<()> + Send + Sync + 'static,
+    {
+        self.init = Some(Arc::new(init));
+        self
+    }
+
+    fn connect(&self) -> Result<SerializedDatabase> {
+        let database = SerializedDatabase::open_with_flags(&self.path, self.flags)?;
+        if let Some(init) = &self.init {
+            init(&database.lock())?;
+        }
+        Ok(database)
+    }
+
+    fn is_valid(&self, database: &SerializedDatabase) -> Result<()> {
+        database.lock().collect::<i32>("select 1", ()).map(|_| ())
+    }
+}
+
+#[cfg(feature = "r2d2")]
+impl r2d2::ManageConnection for ConnectionManager {
+    type Connection = SerializedDatabase;
+    type Error = RsqliteError;
+
+    fn connect(&self) -> Result<SerializedDatabase> {
+        ConnectionManager::connect(self)
+    }
+
+    fn is_valid(&self, database: &mut SerializedDatabase) -> Result<()> {
+        ConnectionManager::is_valid(self, database)
+    }
+
+    fn has_broken(&self, _database: &mut SerializedDatabase) -> bool {
+        false
+    }
+}
+
+#[cfg(feature = "deadpool")]
+impl deadpool::managed::Manager for ConnectionManager {
+    type Type = SerializedDatabase;
+    type Error = RsqliteError;
+
+    async fn create(&self) -> Result<SerializedDatabase> {
+        self.connect()
+    }
+
+    async fn recycle(
+        &self,
+        database: &mut SerializedDatabase,
+        _metrics: &deadpool::managed::Metrics,
+    ) -> deadpool::managed::RecycleResult<RsqliteError> {
+        Ok(self.is_valid(database)?)
+    }
+}
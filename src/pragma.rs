@@ -0,0 +1,291 @@
+//! Typed access to `PRAGMA` statements
+use super::quote::quote_literal;
+use super::wal::JournalMode;
+use super::{Collectable, Database, Result, RsqliteError};
+use sqlite3_sys as ffi;
+
+/// Values that can be assigned to a pragma, pragmas can not take bound parameters
+pub trait PragmaValue {
+    /// the value as it appears in `pragma name = value`
+    fn to_sql(&self) -> String;
+}
+
+/// The values of `pragma synchronous`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Synchronous {
+    Off,
+    Normal,
+    Full,
+    Extra,
+}
+
+/// The values of `pragma temp_store`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TempStore {
+    Default,
+    File,
+    Memory,
+}
+
+/// The values of `pragma locking_mode`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockingMode {
+    Normal,
+    Exclusive,
+}
+
+/// The values of `pragma auto_vacuum`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutoVacuum {
+    /// free pages stay in the file until a `vacuum`
+    None,
+    /// free pages are moved to the end and truncated on every commit
+    Full,
+    /// like `Full`, but only when `incremental_vacuum` is called
+    Incremental,
+}
+
+impl PragmaValue for i32 {
+    fn to_sql(&self) -> String {
+        self.to_string()
+    }
+}
+impl PragmaValue for i64 {
+    fn to_sql(&self) -> String {
+        self.to_string()
+    }
+}
+impl PragmaValue for bool {
+    fn to_sql(&self) -> String {
+        (*self as i32).to_string()
+    }
+}
+impl PragmaValue for &str {
+    fn to_sql(&self) -> String {
+        quote_literal(self)
+    }
+}
+impl PragmaValue for String {
+    fn to_sql(&self) -> String {
+        quote_literal(self)
+    }
+}
+impl PragmaValue for JournalMode {
+    fn to_sql(&self) -> String {
+        self.as_str().to_owned()
+    }
+}
+impl PragmaValue for Synchronous {
+    fn to_sql(&self) -> String {
+        (*self as i32).to_string()
+    }
+}
+impl PragmaValue for TempStore {
+    fn to_sql(&self) -> String {
+        (*self as i32).to_string()
+    }
+}
+impl PragmaValue for AutoVacuum {
+    fn to_sql(&self) -> String {
+        (*self as i32).to_string()
+    }
+}
+impl PragmaValue for LockingMode {
+    fn to_sql(&self) -> String {
+        match self {
+            LockingMode::Normal => "normal".to_owned(),
+            LockingMode::Exclusive => "exclusive".to_owned(),
+        }
+    }
+}
+
+impl Database {
+    /// reads a pragma that returns a single row, `name` may be schema qualified (`aux.user_version`)
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// # let database = Database::open(":memory:")?;
+    /// database.pragma_set("user_version", 7)?;
+    /// let version: i32 = database.pragma_get("user_version")?;
+    /// assert!(version == 7);
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn pragma_get<T: Collectable>(&self, name: &str) -> Result<T> {
+        self.collect(&format!("pragma {}", pragma_name(name)?), ())
+    }
+
+    /// sets a pragma, any rows it returns are ignored
+    pub fn pragma_set(&self, name: &str, value: impl PragmaValue) -> Result<()> {
+        let sql = format!("pragma {} = {}", pragma_name(name)?, value.to_sql());
+        let mut statement = self.prepare(&sql)?;
+        for row in statement.query(())? {
+            row?;
+        }
+        Ok(())
+    }
+
+    /// reads every row returned by a pragma
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// # let database = Database::open(":memory:")?;
+    /// database.execute("create table user(id integer, name text)", ())?;
+    /// let columns: Vec<(i32, String)> = database.pragma_rows("table_info", "user")?;
+    /// assert!(columns == vec![(0, "id".to_owned()), (1, "name".to_owned())]);
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn pragma_rows<T: Collectable>(
+        &self,
+        name: &str,
+        argument: impl PragmaValue,
+    ) -> Result<Vec<T>> {
+        let sql = format!("pragma {}({})", pragma_name(name)?, argument.to_sql());
+        self.collect_vec(&sql, ())
+    }
+
+    /// `pragma synchronous`
+    pub fn synchronous(&self) -> Result<Synchronous> {
+        match self.pragma_get::<i32>("synchronous")? {
+            0 => Ok(Synchronous::Off),
+            1 => Ok(Synchronous::Normal),
+            2 => Ok(Synchronous::Full),
+            3 => Ok(Synchronous::Extra),
+            other => Err(unexpected("synchronous", other)),
+        }
+    }
+
+    /// sets `pragma synchronous`
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// use rsqlite::pragma::Synchronous;
+    /// # let database = Database::open(":memory:")?;
+    /// database.set_synchronous(Synchronous::Normal)?;
+    /// assert!(database.synchronous()? == Synchronous::Normal);
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn set_synchronous(&self, synchronous: Synchronous) -> Result<()> {
+        self.pragma_set("synchronous", synchronous)
+    }
+
+    /// `pragma temp_store`
+    pub fn temp_store(&self) -> Result<TempStore> {
+        match self.pragma_get::<i32>("temp_store")? {
+            0 => Ok(TempStore::Default),
+            1 => Ok(TempStore::File),
+            2 => Ok(TempStore::Memory),
+            other => Err(unexpected("temp_store", other)),
+        }
+    }
+
+    /// sets `pragma temp_store`
+    pub fn set_temp_store(&self, temp_store: TempStore) -> Result<()> {
+        self.pragma_set("temp_store", temp_store)
+    }
+
+    /// `pragma locking_mode`
+    pub fn locking_mode(&self) -> Result<LockingMode> {
+        let mode: String = self.pragma_get("locking_mode")?;
+        match mode.to_ascii_lowercase().as_str() {
+            "normal" => Ok(LockingMode::Normal),
+            "exclusive" => Ok(LockingMode::Exclusive),
+            _ => Err(unexpected("locking_mode", mode)),
+        }
+    }
+
+    /// sets `pragma locking_mode`
+    pub fn set_locking_mode(&self, locking_mode: LockingMode) -> Result<()> {
+        self.pragma_set("locking_mode", locking_mode)
+    }
+
+    /// `pragma foreign_keys`
+    pub fn foreign_keys(&self) -> Result<bool> {
+        Ok(self.pragma_get::<i32>("foreign_keys")? != 0)
+    }
+
+    /// sets `pragma foreign_keys`, it has no effect inside a transaction
+    pub fn set_foreign_keys(&self, enabled: bool) -> Result<()> {
+        self.pragma_set("foreign_keys", enabled)
+    }
+
+    /// `pragma auto_vacuum`
+    pub fn auto_vacuum(&self) -> Result<AutoVacuum> {
+        match self.pragma_get::<i32>("auto_vacuum")? {
+            0 => Ok(AutoVacuum::None),
+            1 => Ok(AutoVacuum::Full),
+            2 => Ok(AutoVacuum::Incremental),
+            other => Err(unexpected("auto_vacuum", other)),
+        }
+    }
+
+    /// sets `pragma auto_vacuum`, running the `vacuum` needed to turn it on or off
+    /// once the database has tables, which fails inside a transaction
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// use rsqlite::pragma::AutoVacuum;
+    /// # let database = Database::open(":memory:")?;
+    /// database.execute("create table t (x blob)", ())?;
+    /// database.set_auto_vacuum(AutoVacuum::Incremental)?;
+    /// assert!(database.auto_vacuum()? == AutoVacuum::Incremental);
+    ///
+    /// database.execute("insert into t values (zeroblob(1 << 16))", ())?;
+    /// database.execute("delete from t", ())?;
+    /// let free_pages: i32 = database.pragma_get("freelist_count")?;
+    /// database.incremental_vacuum(2)?;
+    /// assert!(database.pragma_get::<i32>("freelist_count")? == free_pages - 2);
+    /// database.incremental_vacuum(0)?;
+    /// assert!(database.pragma_get::<i32>("freelist_count")? == 0);
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn set_auto_vacuum(&self, auto_vacuum: AutoVacuum) -> Result<()> {
+        let previous = self.auto_vacuum()?;
+        self.pragma_set("auto_vacuum", auto_vacuum)?;
+        if (previous == AutoVacuum::None) != (auto_vacuum == AutoVacuum::None) {
+            self.execute("vacuum", ())?;
+        }
+        Ok(())
+    }
+
+    /// frees up to `pages` pages with `pragma incremental_vacuum`, every free page if 0
+    ///
+    /// Does nothing unless `auto_vacuum` is `Incremental`.
+    pub fn incremental_vacuum(&self, pages: u32) -> Result<()> {
+        self.pragma_set("incremental_vacuum", pages as i64)
+    }
+
+    /// `pragma user_version`
+    pub fn user_version(&self) -> Result<i32> {
+        self.pragma_get("user_version")
+    }
+
+    /// sets `pragma user_version`
+    pub fn set_user_version(&self, version: i32) -> Result<()> {
+        self.pragma_set("user_version", version)
+    }
+}
+
+/// rejects anything but an optionally schema qualified pragma name
+fn pragma_name(name: &str) -> Result<&str> {
+    let valid = !name.is_empty()
+        && name.split('.').count() <= 2
+        && name.split('.').all(|part| {
+            !part.is_empty() && part.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_')
+        });
+    match valid {
+        true => Ok(name),
+        false => Err(RsqliteError::new(
+            ffi::SQLITE_ERROR,
+            ffi::SQLITE_ERROR,
+            format!("invalid pragma name {}", name),
+        )),
+    }
+}
+
+fn unexpected(name: &str, value: impl std::fmt::Display) -> RsqliteError {
+    RsqliteError::new(
+        ffi::SQLITE_ERROR,
+        ffi::SQLITE_ERROR,
+        format!("unexpected {} value {}", name, value),
+    )
+}
@@ -0,0 +1,166 @@
+//! Renders query results as an aligned text table, for CLI tools and debugging dumps
+//!
+//! This module is only available with the `pretty` feature.
+use super::{Bindable, Collectable, Database, Result, Statement};
+use libc::c_int;
+use sqlite3_sys as ffi;
+use std::ffi::CStr;
+use std::fmt::Write;
+
+/// Options for `render_table`
+#[derive(Debug, Clone)]
+pub struct TableOptions {
+    /// text shown for NULL values
+    pub null: String,
+    /// cells longer than this many characters are truncated with `…`
+    pub max_width: usize,
+}
+
+impl Default for TableOptions {
+    fn default() -> Self {
+        TableOptions {
+            null: "NULL".to_owned(),
+            max_width: 40,
+        }
+    }
+}
+
+impl Database {
+    /// Execute a query and render the results as an aligned text table.
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// # use rsqlite::pretty::TableOptions;
+    /// # let database = Database::open(":memory:")?;
+    /// let table = database.render_table(
+    ///     "select 'amin' as name, 29 as age union all select 'negar', null",
+    ///     (),
+    ///     &TableOptions::default(),
+    /// )?;
+    /// assert!(table == "\
+    /// +-------+------+
+    /// | name  | age  |
+    /// +-------+------+
+    /// | amin  | 29   |
+    /// | negar | NULL |
+    /// +-------+------+
+    /// ");
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn render_table(
+        &self,
+        sql: &str,
+        params: impl Bindable,
+        options: &TableOptions,
+    ) -> Result<String> {
+        let mut statement = self.prepare(sql)?;
+        statement.render_table(params, options)
+    }
+}
+
+impl<'a> Statement<'a> {
+    /// Execute the statement and render the results as an aligned text table.
+    pub fn render_table(
+        &mut self,
+        params: impl Bindable,
+        options: &TableOptions,
+    ) -> Result<String> {
+        let headers = (0..self.column_count)
+            .map(|column| {
+                let name = unsafe { ffi::sqlite3_column_name(self.stmt, column) };
+                if name.is_null() {
+                    return Err(ffi::SQLITE_NOMEM.into());
+                }
+                let name = unsafe { CStr::from_ptr(name) }
+                    .to_string_lossy()
+                    .into_owned();
+                Ok(truncate(name, options.max_width))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let rows = self.with_params(params, |statement| {
+            let mut rows = vec![];
+            let result: Result<()> = loop {
+                let retcode = unsafe { ffi::sqlite3_step(statement.stmt) };
+                match retcode {
+                    ffi::SQLITE_ROW => rows.push(
+                        (0..statement.column_count)
+                            .map(|column| {
+                                truncate(statement.cell(column, options), options.max_width)
+                            })
+                            .collect::<Vec<_>>(),
+                    ),
+                    ffi::SQLITE_DONE => break Ok(()),
+                    other => break Err(statement.database.error(other)),
+                }
+            };
+            let _ = unsafe { ffi::sqlite3_reset(statement.stmt) };
+            result.map(|()| rows)
+        })?;
+
+        let mut widths = headers
+            .iter()
+            .map(|h| h.chars().count())
+            .collect::<Vec<_>>();
+        for row in rows.iter() {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        let mut table = String::new();
+        write_separator(&mut table, &widths);
+        write_row(&mut table, &widths, &headers);
+        write_separator(&mut table, &widths);
+        for row in rows.iter() {
+            write_row(&mut table, &widths, row);
+        }
+        write_separator(&mut table, &widths);
+        Ok(table)
+    }
+
+    fn cell(&self, column: c_int, options: &TableOptions) -> String {
+        match unsafe { ffi::sqlite3_column_type(self.stmt, column) } {
+            ffi::SQLITE_NULL => options.null.clone(),
+            ffi::SQLITE_BLOB => {
+                let blob = <Box<[u8]>>::collect(self, &mut { column });
+                let hex = blob
+                    .iter()
+                    .map(|b| format!("{:02x}", b))
+                    .collect::<String>();
+                format!("x'{}'", hex)
+            }
+            _ => String::collect(self, &mut { column }),
+        }
+    }
+}
+
+fn truncate(text: String, max_width: usize) -> String {
+    match text.chars().count() > max_width {
+        true => {
+            let mut truncated = text
+                .chars()
+                .take(max_width.saturating_sub(1))
+                .collect::<String>();
+            truncated.push('…');
+            truncated
+        }
+        false => text,
+    }
+}
+
+fn write_separator(table: &mut String, widths: &[usize]) {
+    for width in widths {
+        table.push('+');
+        table.push_str(&"-".repeat(width + 2));
+    }
+    table.push_str("+\n");
+}
+
+fn write_row(table: &mut String, widths: &[usize], cells: &[String]) {
+    for (width, cell) in widths.iter().zip(cells) {
+        let padding = width - cell.chars().count();
+        let _ = write!(table, "| {}{} ", cell, " ".repeat(padding));
+    }
+    table.push_str("|\n");
+}
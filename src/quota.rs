@@ -0,0 +1,85 @@
+//! Capping the size of a database file
+use super::{Database, Result};
+
+impl Database {
+    /// limit the size of the main database to at most `bytes`.
+    ///
+    /// This is implemented with `pragma max_page_count`, rounded down to whole pages.
+    /// Writes that would grow the database past the limit fail with `Err(QuotaExceeded)`.
+    /// The limit can not be set below the current size of the database.
+    ///
+    /// The limit is not stored in the database file, it only holds for this connection
+    /// and has to be applied again every time the database is opened,
+    /// which `OpenOptions::max_size` does.
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// # let path = std::env::temp_dir().join("rsqlite-set-max-size.db");
+    /// # let path = path.to_str().unwrap();
+    /// # let _ = std::fs::remove_file(path);
+    /// let database = Database::open(path)?;
+    /// database.execute("create table data (payload blob)", ())?;
+    /// database.set_max_size(64 * 1024)?;
+    ///
+    /// let payload = vec![0u8; 128 * 1024];
+    /// let result = database.execute("insert into data values (?)", payload.as_slice());
+    /// assert!(matches!(result, Err(RsqliteError::QuotaExceeded)));
+    ///
+    /// // every connection needs the limit, a plain `Database::open` has none
+    /// let database = Database::options().max_size(64 * 1024).open(path)?;
+    /// let result = database.execute("insert into data values (?)", payload.as_slice());
+    /// assert!(matches!(result, Err(RsqliteError::QuotaExceeded)));
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn set_max_size(&self, bytes: i64) -> Result<()> {
+        let page_size: i64 = self.collect("pragma page_size", ())?;
+        let pages = (bytes / page_size).max(1);
+        self.collect::<()>(&format!("pragma max_page_count = {}", pages), ())?;
+        self.max_size_set.set(true);
+        Ok(())
+    }
+
+    /// whether a `SQLITE_FULL` error comes from the limit set by `set_max_size`.
+    ///
+    /// SQLite rolls the failed write back before reporting it, so `page_count` no longer
+    /// shows how far the database grew. Instead the error counts as the limit being reached
+    /// when the disk still has room for every page up to `max_page_count`.
+    pub(crate) fn reached_max_size(&self) -> bool {
+        if !self.max_size_set.get() {
+            return false;
+        }
+        let pages: Result<(i64, i64, i64)> = self.collect(
+            "select page_count, max_page_count, page_size \
+             from pragma_page_count, pragma_max_page_count, pragma_page_size",
+            (),
+        );
+        match pages {
+            Ok((count, max, page_size)) => match self.free_space() {
+                Some(free) => free >= (max - count).saturating_mul(page_size),
+                None => true,
+            },
+            Err(_) => false,
+        }
+    }
+
+    /// bytes available on the file system holding the main database,
+    /// `None` when it is in memory or the platform can not tell
+    #[cfg(unix)]
+    fn free_space(&self) -> Option<i64> {
+        let filename = unsafe { super::ffi::sqlite3_db_filename(self.db, b"main\0".as_ptr() as _) };
+        if filename.is_null() || unsafe { *filename } == 0 {
+            return None;
+        }
+        let mut stat = std::mem::MaybeUninit::<libc::statvfs>::uninit();
+        if unsafe { libc::statvfs(filename, stat.as_mut_ptr()) } != 0 {
+            return None;
+        }
+        let stat = unsafe { stat.assume_init() };
+        Some((stat.f_bavail as i64).saturating_mul(stat.f_frsize as i64))
+    }
+
+    #[cfg(not(unix))]
+    fn free_space(&self) -> Option<i64> {
+        None
+    }
+}
@@ -0,0 +1,45 @@
+//! Quoting helpers for interpolating identifiers and literals into SQL text
+//!
+//! Prefer binding with `?` whenever possible, these are meant for the places where
+//! sqlite does not accept parameters, like table and column names in DDL.
+
+/// quotes an identifier (table, column, index name ...) with double quotes,
+/// doubling any embedded `"` as sqlite expects.
+///
+/// ```
+/// # use rsqlite::*;
+/// assert!(quote_identifier("user table") == r#""user table""#);
+/// assert!(quote_identifier(r#"say "hi""#) == r#""say ""hi""""#);
+/// ```
+pub fn quote_identifier(identifier: &str) -> String {
+    quote(identifier, '"')
+}
+
+/// quotes a string literal with single quotes, doubling any embedded `'`.
+///
+/// ```
+/// # use rsqlite::*;
+/// # let database = Database::open(":memory:")?;
+/// assert!(quote_literal("it's") == "'it''s'");
+///
+/// let sql = format!("select {}", quote_literal("it's"));
+/// let result: String = database.collect(&sql, ())?;
+/// assert!(result == "it's");
+/// # Ok::<(), RsqliteError>(())
+/// ```
+pub fn quote_literal(literal: &str) -> String {
+    quote(literal, '\'')
+}
+
+fn quote(text: &str, quote: char) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push(quote);
+    for c in text.chars() {
+        if c == quote {
+            quoted.push(quote);
+        }
+        quoted.push(c);
+    }
+    quoted.push(quote);
+    quoted
+}
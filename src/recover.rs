@@ -0,0 +1,176 @@
+//! Best-effort recovery of corrupted databases, similar to the `.recover` CLI command
+use super::{quote_identifier, Database, Result, RsqliteError, Statement};
+use sqlite3_sys as ffi;
+
+/// What `recover_into` managed to salvage
+#[derive(Debug)]
+pub struct Recovery {
+    /// one entry per table found in the schema of the corrupted database
+    pub tables: Vec<TableRecovery>,
+    /// schema entries (tables, indexes, views, triggers) that could not be recreated
+    pub failed_schema: Vec<(String, RsqliteError)>,
+}
+
+/// What happened to a single table during `recover_into`
+#[derive(Debug)]
+pub struct TableRecovery {
+    pub name: String,
+    /// rows copied into the new database
+    pub rows_recovered: i64,
+    /// rows that were read but could not be inserted into the new database
+    pub rows_skipped: i64,
+    /// set when reading the table stopped early, any rows after that point are lost
+    pub error: Option<RsqliteError>,
+}
+
+impl Database {
+    /// salvage every readable row of this (possibly corrupted) database into a new
+    /// database file at `path`.
+    ///
+    /// The schema is recreated first, then each table is copied row by row until it is
+    /// exhausted or a read fails, keeping the rowids. Generated columns are recomputed
+    /// and the shadow tables of virtual tables are left to the recreated virtual tables.
+    /// Indexes, views and triggers are created last.
+    /// Nothing is rolled back on failure, the returned `Recovery` reports what was lost.
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// # let path = std::env::temp_dir().join("rsqlite-recover-into.db");
+    /// # let path = path.to_str().unwrap();
+    /// # let _ = std::fs::remove_file(path);
+    /// # let database = Database::open(":memory:")?;
+    /// database.execute("create table user (name text)", ())?;
+    /// database.execute("insert into user values ('amin'), ('negar')", ())?;
+    ///
+    /// let recovery = database.recover_into(path)?;
+    /// assert!(recovery.tables[0].rows_recovered == 2);
+    /// assert!(recovery.tables[0].error.is_none());
+    /// # let database = Database::open(":memory:")?;
+    /// # database.execute("create table sqlitex (name text)", ())?;
+    /// # let _ = std::fs::remove_file(path);
+    /// # assert!(database.recover_into(path)?.tables.len() == 1);
+    /// # let database = Database::open(":memory:")?;
+    /// # database.execute("create table t (a integer, b integer as (a * 2))", ())?;
+    /// # database.execute("insert into t(a) values (1), (2), (3)", ())?;
+    /// # database.execute("delete from t where a = 2", ())?;
+    /// # let _ = std::fs::remove_file(path);
+    /// # let recovery = database.recover_into(path)?;
+    /// # assert!(recovery.tables[0].rows_recovered == 2 && recovery.tables[0].rows_skipped == 0);
+    /// # let rows: Vec<(i64, i64)> = Database::open(path)?.collect_vec("select rowid, b from t", ())?;
+    /// # assert!(rows == vec![(1, 2), (3, 6)]);
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn recover_into(&self, path: &str) -> Result<Recovery> {
+        let mut schema = vec![];
+        self.for_each(
+            "select type, name, sql from sqlite_master
+             where sql is not null and name not like 'sqlite\\_%' escape '\\'
+             and name not in (select name from pragma_table_list
+                              where schema = 'main' and type = 'shadow')
+             order by type != 'table'",
+            (),
+            |kind: String, name: String, sql: String| schema.push((kind, name, sql)),
+        )?;
+
+        let destination = Database::open(path)?;
+        let mut recovery = Recovery {
+            tables: vec![],
+            failed_schema: vec![],
+        };
+
+        for (kind, name, sql) in schema.iter() {
+            if kind != "table" {
+                continue;
+            }
+            match destination.execute(sql, ()) {
+                Ok(_) => recovery.tables.push(self.recover_table(&destination, name)),
+                Err(error) => recovery.failed_schema.push((name.clone(), error)),
+            }
+        }
+        for (kind, name, sql) in schema.iter() {
+            if kind == "table" {
+                continue;
+            }
+            if let Err(error) = destination.execute(sql, ()) {
+                recovery.failed_schema.push((name.clone(), error));
+            }
+        }
+
+        Ok(recovery)
+    }
+
+    fn recover_table(&self, destination: &Database, name: &str) -> TableRecovery {
+        let mut recovery = TableRecovery {
+            name: name.to_owned(),
+            rows_recovered: 0,
+            rows_skipped: 0,
+            error: None,
+        };
+        if let Err(error) = destination.execute("begin", ()) {
+            recovery.error = Some(error);
+            return recovery;
+        }
+        if let Err(error) = self.copy_rows(destination, &mut recovery) {
+            recovery.error = Some(error);
+        }
+        if let Err(error) = destination.execute("commit", ()) {
+            recovery.error.get_or_insert(error);
+        }
+        recovery
+    }
+
+    fn copy_rows(&self, destination: &Database, recovery: &mut TableRecovery) -> Result<()> {
+        // hidden and generated columns can not be inserted
+        let names: Vec<String> = self.collect_vec(
+            "select name from pragma_table_xinfo(?, 'main') where hidden = 0 order by cid",
+            recovery.name.as_str(),
+        )?;
+        let mut columns: Vec<String> = names.iter().map(|name| quote_identifier(name)).collect();
+        // keep the rowids of tables without an INTEGER PRIMARY KEY, under a name no column uses
+        let (kind, without_rowid): (String, bool) = self.collect(
+            "select type, wr from pragma_table_list where schema = 'main' and name = ?",
+            recovery.name.as_str(),
+        )?;
+        let rowid = ["rowid", "_rowid_", "oid"]
+            .iter()
+            .find(|alias| !names.iter().any(|name| name.eq_ignore_ascii_case(alias)));
+        if let (Some(rowid), "table" | "virtual", false) = (rowid, kind.as_str(), without_rowid) {
+            columns.insert(0, rowid.to_string());
+        }
+
+        let table = quote_identifier(&recovery.name);
+        let columns = columns.join(", ");
+        let select = self.prepare(&format!("select {} from {}", columns, table))?;
+        let placeholders = vec!["?"; select.column_count as usize].join(", ");
+        let mut insert = destination.prepare(&format!(
+            "insert into {}({}) values ({})",
+            table, columns, placeholders
+        ))?;
+
+        loop {
+            let retcode = unsafe { ffi::sqlite3_step(select.stmt) };
+            match retcode {
+                ffi::SQLITE_ROW => match copy_row(&select, &mut insert) {
+                    Ok(()) => recovery.rows_recovered += 1,
+                    Err(_) => recovery.rows_skipped += 1,
+                },
+                ffi::SQLITE_DONE => break Ok(()),
+                other => break Err(self.error(other)),
+            }
+        }
+    }
+}
+
+fn copy_row(select: &Statement, insert: &mut Statement) -> Result<()> {
+    for column in 0..select.column_count {
+        let ecode = unsafe {
+            let value = ffi::sqlite3_column_value(select.stmt, column);
+            ffi::sqlite3_bind_value(insert.stmt, column + 1, value)
+        };
+        if ecode != ffi::SQLITE_OK {
+            return Err(insert.database.error(ecode));
+        }
+    }
+    insert.execute(())?;
+    Ok(())
+}
@@ -0,0 +1,98 @@
+//! A `regexp()` SQL function backed by the `regex` crate, so `X REGEXP Y` works
+//!
+//! This module is only available with the `regexp` feature, which registers the function
+//! on every opened database. Compiled patterns are cached per statement via auxdata.
+//!
+//! ```
+//! # use rsqlite::*;
+//! # let database = Database::open(":memory:")?;
+//! let matched: i32 = database.collect("select 'amin' regexp ?", ("^a.*n$"))?;
+//! assert!(matched == 1);
+//! # Ok::<(), RsqliteError>(())
+//! ```
+use super::{Database, Result};
+use core::ptr;
+use libc::{c_char, c_int, c_void};
+use regex::Regex;
+use sqlite3_sys as ffi;
+
+impl Database {
+    /// registers `regexp(pattern, text)` on this connection
+    pub(crate) fn register_regexp(&self) -> Result<()> {
+        let ecode = unsafe {
+            ffi::sqlite3_create_function_v2(
+                self.db,
+                b"regexp\0".as_ptr() as *const c_char,
+                2,
+                ffi::SQLITE_UTF8 | ffi::SQLITE_DETERMINISTIC,
+                ptr::null_mut(),
+                Some(regexp),
+                None,
+                None,
+                None,
+            )
+        };
+        match ecode {
+            ffi::SQLITE_OK => Ok(()),
+            other => Err(self.error(other)),
+        }
+    }
+}
+
+/// `X REGEXP Y` is evaluated as `regexp(Y, X)`, so the pattern comes first
+unsafe extern "C" fn regexp(
+    context: *mut ffi::sqlite3_context,
+    _argc: c_int,
+    argv: *mut *mut ffi::sqlite3_value,
+) {
+    let (pattern, text) = match (value_str(*argv), value_str(*argv.add(1))) {
+        (Some(Ok(pattern)), Some(Ok(text))) => (pattern, text),
+        (Some(Err(_)), _) | (_, Some(Err(_))) => return result_error(context, "invalid UTF-8"),
+        _ => return ffi::sqlite3_result_null(context),
+    };
+
+    let cached = ffi::sqlite3_get_auxdata(context, 0) as *const Regex;
+    if let Some(regex) = cached.as_ref() {
+        return ffi::sqlite3_result_int(context, regex.is_match(text) as c_int);
+    }
+
+    match Regex::new(pattern) {
+        Ok(regex) => {
+            ffi::sqlite3_result_int(context, regex.is_match(text) as c_int);
+            // sqlite may free it right away, so it must not be used after this call
+            let regex = Box::into_raw(Box::new(regex)) as *mut c_void;
+            ffi::sqlite3_set_auxdata(context, 0, regex, Some(drop_regex));
+        }
+        Err(error) => result_error(context, &error.to_string()),
+    }
+}
+
+unsafe extern "C" fn drop_regex(regex: *mut c_void) {
+    drop(Box::from_raw(regex as *mut Regex));
+}
+
+/// `None` for NULL values
+unsafe fn value_str<'a>(
+    value: *mut ffi::sqlite3_value,
+) -> Option<std::result::Result<&'a str, std::str::Utf8Error>> {
+    if ffi::sqlite3_value_type(value) == ffi::SQLITE_NULL {
+        return None;
+    }
+    let ptr = ffi::sqlite3_value_text(value);
+    let bytes = ffi::sqlite3_value_bytes(value);
+    match bytes == 0 {
+        true => Some(Ok("")),
+        false => Some(std::str::from_utf8(std::slice::from_raw_parts(
+            ptr,
+            bytes as usize,
+        ))),
+    }
+}
+
+unsafe fn result_error(context: *mut ffi::sqlite3_context, message: &str) {
+    ffi::sqlite3_result_error(
+        context,
+        message.as_ptr() as *const c_char,
+        message.len() as c_int,
+    );
+}
@@ -0,0 +1,199 @@
+//! Lazy iteration over the rows of a statement
+use super::bindable::StaticBindings;
+use super::{Bindable, Collectable, Iterable, Result, RsqliteError, Statement};
+use libc::c_int;
+use sqlite3_sys as ffi;
+use std::ffi::CStr;
+use std::marker::PhantomData;
+
+/// An iterator over the rows returned by `Statement::query`
+///
+/// The statement is reset and its bindings cleared when `Rows` is dropped.
+pub struct Rows<'s> {
+    statement: &'s Statement<'s>,
+    done: bool,
+}
+
+/// An iterator over the rows returned by `Statement::query_map`, mapped by a closure
+pub struct MappedRows<'s, F, R, T> {
+    rows: Rows<'s>,
+    map: F,
+    marker: PhantomData<fn(T) -> R>,
+}
+
+/// A single result row, only valid until the next row is stepped
+///
+/// Reading from a `Row` after its `Rows` iterator moved on returns `Err(Misuse)`.
+pub struct Row<'s> {
+    statement: &'s Statement<'s>,
+    generation: u64,
+}
+
+impl<'a> Statement<'a> {
+    /// binds the params and returns a lazy iterator over the result rows
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// # let database = Database::open(":memory:")?;
+    /// let mut statement = database.prepare("select 1 union select 2 union select 3")?;
+    /// let mut sum = 0;
+    /// for row in statement.query(())?.take(2) {
+    ///     let x: i32 = row?.collect()?;
+    ///     sum += x;
+    /// }
+    /// assert!(sum == 3);
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn query(&mut self, params: impl Bindable) -> Result<Rows<'_>> {
+        // `Rows` does not borrow `params`, so `bindable::Static` has to copy
+        self.static_bindings.set(StaticBindings::Disallowed);
+        params.bind(self, &mut 1)?;
+        Ok(Rows {
+            statement: self,
+            done: false,
+        })
+    }
+
+    /// like `query`, but collects the columns of each row as the arguments of `map`
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// # let database = Database::open(":memory:")?;
+    /// let mut statement = database.prepare("select 'amin', 29 union select 'bob', 40")?;
+    /// let names = statement
+    ///     .query_map((), |name: String, age: i32| format!("{} ({})", name, age))?
+    ///     .collect::<Result<Vec<_>>>()?;
+    /// assert!(names == vec!["amin (29)", "bob (40)"]);
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn query_map<F, R, T>(
+        &mut self,
+        params: impl Bindable,
+        map: F,
+    ) -> Result<MappedRows<'_, F, R, T>>
+    where
+        F: Iterable<R, T>,
+    {
+        self.check_columns(F::columns_needed())?;
+        Ok(MappedRows {
+            rows: self.query(params)?,
+            map,
+            marker: PhantomData,
+        })
+    }
+}
+
+impl<'s, F, R, T> Iterator for MappedRows<'s, F, R, T>
+where
+    F: Iterable<R, T>,
+{
+    type Item = Result<R>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let row = match self.rows.next()? {
+            Ok(row) => row,
+            Err(error) => return Some(Err(error)),
+        };
+        Some(self.map.iterate(row.statement, &mut 0))
+    }
+}
+
+impl<'s> Iterator for Rows<'s> {
+    type Item = Result<Row<'s>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let retcode = unsafe { ffi::sqlite3_step(self.statement.stmt) };
+        match retcode {
+            ffi::SQLITE_ROW => {
+                let generation = self.statement.generation.get() + 1;
+                self.statement.generation.set(generation);
+                Some(Ok(Row {
+                    statement: self.statement,
+                    generation,
+                }))
+            }
+            ffi::SQLITE_DONE => {
+                self.done = true;
+                None
+            }
+            other => {
+                self.done = true;
+                Some(Err(self.statement.database.error(other)))
+            }
+        }
+    }
+}
+
+impl<'s> Drop for Rows<'s> {
+    /// resets the statement and clears its bindings so it can be executed again
+    fn drop(&mut self) {
+        let _ = unsafe { ffi::sqlite3_reset(self.statement.stmt) };
+        unsafe { ffi::sqlite3_clear_bindings(self.statement.stmt) };
+        // rows handed out by this iterator are no longer valid
+        let generation = self.statement.generation.get() + 1;
+        self.statement.generation.set(generation);
+    }
+}
+
+impl<'s> Row<'s> {
+    /// collects the whole row, starting from the first column
+    pub fn collect<R>(&self) -> Result<R>
+    where
+        R: Collectable,
+    {
+        self.check()?;
+        self.statement.check_columns(R::columns_needed())?;
+        R::try_collect(self.statement, &mut 0)
+    }
+
+    /// collects the column at `index`, starting from 0
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// # let database = Database::open(":memory:")?;
+    /// let mut statement = database.prepare("select 'amin' as name, 29 as age")?;
+    /// for row in statement.query(())? {
+    ///     let row = row?;
+    ///     let age: i32 = row.get(1)?;
+    ///     let name: String = row.get_by_name("name")?;
+    /// #   assert!((name.as_str(), age) == ("amin", 29));
+    /// #   assert!(matches!(row.get::<i32>(2), Err(RsqliteError::Range { .. })));
+    /// }
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn get<T>(&self, index: c_int) -> Result<T>
+    where
+        T: Collectable,
+    {
+        self.check()?;
+        if index < 0 || index + T::columns_needed() > self.statement.column_count {
+            return Err(ffi::SQLITE_RANGE.into());
+        }
+        T::try_collect(self.statement, &mut { index })
+    }
+
+    /// collects the column named `name`, as returned by `sqlite3_column_name`
+    pub fn get_by_name<T>(&self, name: &str) -> Result<T>
+    where
+        T: Collectable,
+    {
+        let index = (0..self.statement.column_count)
+            .find(|&index| unsafe {
+                let column = ffi::sqlite3_column_name(self.statement.stmt, index);
+                !column.is_null() && CStr::from_ptr(column).to_bytes() == name.as_bytes()
+            })
+            .ok_or_else(|| RsqliteError::InvalidColumnName(name.to_owned()))?;
+        self.get(index)
+    }
+
+    /// fails if the statement has moved past this row
+    fn check(&self) -> Result<()> {
+        match self.generation == self.statement.generation.get() {
+            true => Ok(()),
+            false => Err(ffi::SQLITE_MISUSE.into()),
+        }
+    }
+}
@@ -0,0 +1,277 @@
+//! Spatial indexes with R*Tree tables, see the `rtree` feature and https://sqlite.org/rtree.html
+use super::quote::quote_identifier;
+use super::vtab::catch_panic;
+use super::{Bindable, Database, Result};
+use libc::{c_char, c_int, c_void};
+use sqlite3_sys as ffi;
+use std::ffi::CString;
+
+/// Describes an R*Tree table of bounding boxes, created with `create`
+///
+/// Each axis `x` gets a `min_x` and a `max_x` column, the id column is named `id`.
+///
+/// ```
+/// # use rsqlite::*;
+/// use rsqlite::rtree::RTree;
+///
+/// # let database = Database::open(":memory:")?;
+/// let places = RTree::new("places", &["x", "y"]).auxiliary(&["name"]);
+/// places.create(&database)?;
+/// places.insert(&database, 1, &[(0.0, 1.0), (0.0, 1.0)])?;
+/// places.insert(&database, 2, &[(5.0, 6.0), (5.0, 6.0)])?;
+///
+/// assert!(places.overlapping(&database, &[(0.5, 2.0), (0.5, 2.0)])? == vec![1]);
+/// assert!(places.overlapping(&database, &[(-10.0, 10.0), (-10.0, 10.0)])? == vec![1, 2]);
+/// # Ok::<(), RsqliteError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct RTree {
+    name: String,
+    axes: Vec<String>,
+    auxiliary: Vec<String>,
+    integer: bool,
+}
+
+/// How a bounding box relates to the region of a query function
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Within {
+    /// entirely outside, the box and its children are skipped
+    Not,
+    /// overlaps the region
+    Partly,
+    /// entirely inside the region
+    Fully,
+}
+
+/// The box being tested by a query function, see `create_rtree_query_function`
+pub struct QueryInfo<'a> {
+    info: &'a mut ffi::sqlite3_rtree_query_info,
+}
+
+type QueryFunction = Box<dyn Fn(&mut QueryInfo) -> Within + Send>;
+
+impl RTree {
+    /// a table named `name` with one dimension per axis, 1 to 5 axes
+    pub fn new(name: &str, axes: &[&str]) -> RTree {
+        RTree {
+            name: name.to_owned(),
+            axes: axes.iter().map(|&axis| axis.to_owned()).collect(),
+            auxiliary: Vec::new(),
+            integer: false,
+        }
+    }
+
+    /// adds auxiliary columns, stored alongside each box but not indexed
+    pub fn auxiliary(mut self, columns: &[&str]) -> RTree {
+        self.auxiliary = columns.iter().map(|&column| column.to_owned()).collect();
+        self
+    }
+
+    /// stores coordinates as 32-bit integers with `rtree_i32`, instead of 32-bit floats
+    pub fn integer(mut self, integer: bool) -> RTree {
+        self.integer = integer;
+        self
+    }
+
+    /// creates the table if it does not exist
+    pub fn create(&self, database: &Database) -> Result<()> {
+        let mut columns = vec!["id".to_owned()];
+        columns.extend(self.box_columns(self.axes.len()));
+        for column in &self.auxiliary {
+            columns.push(format!("+{}", quote_identifier(column)));
+        }
+        let module = match self.integer {
+            true => "rtree_i32",
+            false => "rtree",
+        };
+        let sql = format!(
+            "create virtual table if not exists {} using {}({})",
+            quote_identifier(&self.name),
+            module,
+            columns.join(", ")
+        );
+        database.execute(&sql, ()).map(|_| ())
+    }
+
+    /// inserts or replaces the box `id`, given as a `(min, max)` range per axis
+    pub fn insert(&self, database: &Database, id: i64, ranges: &[(f64, f64)]) -> Result<()> {
+        let placeholders = vec!["?"; 1 + 2 * ranges.len()].join(", ");
+        let columns = self.box_columns(ranges.len());
+        let sql = format!(
+            "insert or replace into {}(id, {}) values ({})",
+            quote_identifier(&self.name),
+            columns.join(", "),
+            placeholders
+        );
+        let mut statement = database.prepare_cached(&sql)?;
+        let mut index = 1;
+        id.bind(&mut statement, &mut index)?;
+        for (min, max) in ranges {
+            min.bind(&mut statement, &mut index)?;
+            max.bind(&mut statement, &mut index)?;
+        }
+        statement.execute(()).map(|_| ())
+    }
+
+    /// the ids of the boxes overlapping the box given as a `(min, max)` range per axis
+    pub fn overlapping(&self, database: &Database, ranges: &[(f64, f64)]) -> Result<Vec<i64>> {
+        self.query(database, ranges, false)
+    }
+
+    /// the ids of the boxes entirely inside the box given as a `(min, max)` range per axis
+    pub fn within(&self, database: &Database, ranges: &[(f64, f64)]) -> Result<Vec<i64>> {
+        self.query(database, ranges, true)
+    }
+
+    fn query(&self, database: &Database, ranges: &[(f64, f64)], within: bool) -> Result<Vec<i64>> {
+        let columns = self.box_columns(ranges.len());
+        let conditions: Vec<String> = columns
+            .chunks(2)
+            .map(|axis| match within {
+                true => format!("{} >= ? and {} <= ?", axis[0], axis[1]),
+                false => format!("{} >= ? and {} <= ?", axis[1], axis[0]),
+            })
+            .collect();
+        let sql = format!(
+            "select id from {} where {} order by id",
+            quote_identifier(&self.name),
+            conditions.join(" and ")
+        );
+        let mut statement = database.prepare_cached(&sql)?;
+        let mut index = 1;
+        for (min, max) in ranges {
+            min.bind(&mut statement, &mut index)?;
+            max.bind(&mut statement, &mut index)?;
+        }
+        let ids = statement
+            .query(())?
+            .map(|row| row?.collect())
+            .collect::<Result<Vec<i64>>>();
+        ids
+    }
+
+    fn box_columns(&self, axes: usize) -> Vec<String> {
+        let mut columns = Vec::new();
+        for axis in self.axes.iter().take(axes) {
+            columns.push(quote_identifier(&format!("min_{}", axis)));
+            columns.push(quote_identifier(&format!("max_{}", axis)));
+        }
+        columns
+    }
+}
+
+impl Within {
+    fn as_raw(self) -> c_int {
+        // NOT_WITHIN, PARTLY_WITHIN and FULLY_WITHIN of sqlite3rtree.h
+        match self {
+            Within::Not => 0,
+            Within::Partly => 1,
+            Within::Fully => 2,
+        }
+    }
+}
+
+impl<'a> QueryInfo<'a> {
+    /// the arguments of the function in SQL
+    pub fn params(&self) -> &[f64] {
+        match self.info.aParam.is_null() {
+            true => &[],
+            false => unsafe {
+                std::slice::from_raw_parts(self.info.aParam, self.info.nParam as usize)
+            },
+        }
+    }
+
+    /// the box being tested, as `min, max` pairs per axis
+    pub fn coordinates(&self) -> &[f64] {
+        unsafe { std::slice::from_raw_parts(self.info.aCoord, self.info.nCoord as usize) }
+    }
+
+    /// the depth of the box in the tree, 0 for the boxes of the table rows
+    pub fn level(&self) -> i32 {
+        self.info.iLevel
+    }
+
+    /// the id of the row being tested, only meaningful at level 0
+    pub fn rowid(&self) -> i64 {
+        self.info.iRowid
+    }
+
+    /// orders the results, boxes with a lower score are visited first, default the parent's score
+    pub fn set_score(&mut self, score: f64) {
+        self.info.rScore = score;
+    }
+}
+
+impl Database {
+    /// registers a function for `id MATCH name(params...)` queries on R*Tree tables,
+    /// deciding for every box whether it is within the region described by the params
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// use rsqlite::rtree::{RTree, Within};
+    ///
+    /// # let database = Database::open(":memory:")?;
+    /// let places = RTree::new("places", &["x", "y"]);
+    /// places.create(&database)?;
+    /// places.insert(&database, 1, &[(0.0, 1.0), (0.0, 1.0)])?;
+    /// places.insert(&database, 2, &[(5.0, 6.0), (5.0, 6.0)])?;
+    ///
+    /// // circle(x, y, radius), tested against the center of each box
+    /// database.create_rtree_query_function("circle", |info| {
+    ///     let (params, box_) = (info.params(), info.coordinates());
+    ///     let dx = (box_[0] + box_[1]) / 2.0 - params[0];
+    ///     let dy = (box_[2] + box_[3]) / 2.0 - params[1];
+    ///     match info.level() == 0 && (dx * dx + dy * dy).sqrt() > params[2] {
+    ///         true => Within::Not,
+    ///         false => Within::Partly,
+    ///     }
+    /// })?;
+    ///
+    /// let ids: Vec<i64> = database.collect_vec("select id from places where id match circle(6, 6, 1)", ())?;
+    /// assert!(ids == vec![2]);
+    ///
+    /// // a panic, here indexing the missing radius, fails the query
+    /// let result = database.collect_vec::<i64>("select id from places where id match circle(6, 6)", ());
+    /// assert!(result.is_err());
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn create_rtree_query_function<F>(&self, name: &str, function: F) -> Result<()>
+    where
+        F: Fn(&mut QueryInfo) -> Within + Send + 'static,
+    {
+        let name = CString::new(name)?;
+        let function: Box<QueryFunction> = Box::new(Box::new(function));
+        let data = Box::into_raw(function) as *mut c_void;
+        // sqlite calls drop_query_function even if registering fails
+        let ecode = unsafe {
+            ffi::sqlite3_rtree_query_callback(
+                self.db,
+                name.as_ptr() as *const c_char,
+                Some(call_query_function),
+                data,
+                Some(drop_query_function),
+            )
+        };
+        match ecode {
+            ffi::SQLITE_OK => Ok(()),
+            other => Err(self.error(other)),
+        }
+    }
+}
+
+unsafe extern "C" fn call_query_function(info: *mut ffi::sqlite3_rtree_query_info) -> c_int {
+    let function = &*((*info).pContext as *const QueryFunction);
+    let mut info = QueryInfo { info: &mut *info };
+    match catch_panic(|| Ok(function(&mut info))) {
+        Ok(within) => {
+            info.info.eWithin = within.as_raw();
+            ffi::SQLITE_OK
+        }
+        Err(_) => ffi::SQLITE_ERROR,
+    }
+}
+
+unsafe extern "C" fn drop_query_function(data: *mut c_void) {
+    drop(Box::from_raw(data as *mut QueryFunction));
+}
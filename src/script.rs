@@ -0,0 +1,196 @@
+//! Running SQL scripts, like the output of `Database::dump`
+use super::{Database, Result, RsqliteError};
+use sqlite3_sys as ffi;
+use std::ffi::CString;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+
+/// How far `Database::restore_from_script` got
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScriptProgress {
+    /// statements executed so far
+    pub statements: usize,
+    /// bytes of the script read so far
+    pub bytes: u64,
+}
+
+impl Database {
+    /// runs the SQL script from `reader` in a single transaction, rolled back on error
+    ///
+    /// The script is read line by line and each statement runs once its last line is read,
+    /// but a line is held in memory whole, so a minified script is read all at once
+    /// and scripts with many statements per line are best split across lines.
+    /// Its own `BEGIN`, `COMMIT`, `END` and `ROLLBACK` statements are skipped in favor of
+    /// the transaction, while `SAVEPOINT`, `RELEASE` and `ROLLBACK TO` nest inside it.
+    /// `progress` is called after every statement.
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// # let database = Database::open(":memory:")?;
+    /// database.execute("create table user (name text)", ())?;
+    /// database.execute("insert into user values ('amin'), ('semi;colon')", ())?;
+    /// database.execute("create trigger shout after insert on user begin update user set name = upper(name); end", ())?;
+    /// let mut script = Vec::new();
+    /// database.dump(&mut script)?;
+    ///
+    /// let copy = Database::open(":memory:")?;
+    /// let mut statements = 0;
+    /// copy.restore_from_script(script.as_slice(), |progress| statements = progress.statements)?;
+    /// assert!(statements == 7);
+    ///
+    /// let names: Vec<String> = copy.collect_vec("select name from user", ())?;
+    /// assert!(names == vec!["amin", "semi;colon"]);
+    ///
+    /// let script = "-- made by hand\nBEGIN; savepoint one; delete from user;\nrollback to one; COMMIT;";
+    /// copy.restore_from_script(script.as_bytes(), |_| {})?;
+    /// assert!(copy.collect::<i32>("select count(*) from user", ())? == 2);
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn restore_from_script(
+        &self,
+        reader: impl Read,
+        mut progress: impl FnMut(ScriptProgress),
+    ) -> Result<ScriptProgress> {
+        self.execute("begin immediate", ())?;
+        let result = self.run_script(reader, |statement, done| {
+            if !is_transaction_control(statement) {
+                self.execute_batch(statement)?;
+            }
+            progress(done);
+            Ok(())
+        });
+        let result = result.and_then(|done| self.execute("commit", ()).map(|_| done));
+        if result.is_err() && !self.is_autocommit() {
+            let _ = self.execute("rollback", ());
+        }
+        result
+    }
+
+    /// runs every statement of the SQL script from `reader`, returning how many ran
+    ///
+    /// Statements before a failing one stay applied unless the script has its own transaction.
+    /// The error is a `Script` error with the index and the byte offset of the failed statement.
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// # let database = Database::open(":memory:")?;
+    /// let schema = "
+    ///     create table user (name text);
+    ///     -- a comment; with a semicolon
+    ///     insert into user values ('amin');
+    /// ";
+    /// assert!(database.execute_script(schema.as_bytes())? == 2);
+    ///
+    /// let error = database.execute_script("select 1;\nselect * from nowhere;".as_bytes()).unwrap_err();
+    /// assert!(matches!(error, RsqliteError::Script { statement: 1, offset: 10, .. }));
+    ///
+    /// let error = database.execute_script("select 1; select 2; select * from nowhere;".as_bytes()).unwrap_err();
+    /// assert!(matches!(error, RsqliteError::Script { statement: 2, offset: 20, .. }));
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn execute_script(&self, reader: impl Read) -> Result<usize> {
+        let done = self.run_script(reader, |statement, _| self.execute_batch(statement))?;
+        Ok(done.statements)
+    }
+
+    /// runs every statement of the SQL script at `path`, see `execute_script`
+    pub fn execute_script_file(&self, path: impl AsRef<Path>) -> Result<usize> {
+        self.execute_script(File::open(path)?)
+    }
+
+    /// splits the script from `reader` into complete statements and calls `run` on each,
+    /// skipping those made only of comments, errors are wrapped in `RsqliteError::Script`
+    fn run_script(
+        &self,
+        reader: impl Read,
+        mut run: impl FnMut(&str, ScriptProgress) -> Result<()>,
+    ) -> Result<ScriptProgress> {
+        let mut reader = BufReader::new(reader);
+        let mut done = ScriptProgress {
+            statements: 0,
+            bytes: 0,
+        };
+        let mut lines = String::new();
+        let mut offset = 0;
+        loop {
+            let read = reader.read_line(&mut lines)?;
+            done.bytes += read as u64;
+            let end_of_script = read == 0;
+            let complete = lines.trim_end().ends_with(';') && is_complete(&lines)?;
+            if complete || end_of_script {
+                // the lines may hold several statements, like `select 1; select 2;`
+                let mut rest = lines.as_str();
+                while !rest.trim().is_empty() {
+                    let length = first_statement(rest)?.unwrap_or(rest.len());
+                    let statement = &rest[..length];
+                    // report where the statement starts, past whitespace and comments
+                    let start = offset + (length - skip_comments(statement).len()) as u64;
+                    rest = &rest[length..];
+                    offset += length as u64;
+                    if skip_comments(statement).is_empty() {
+                        continue;
+                    }
+                    let index = done.statements;
+                    done.statements += 1;
+                    run(statement.trim(), done).map_err(|source| RsqliteError::Script {
+                        statement: index,
+                        offset: start,
+                        source: Box::new(source),
+                    })?;
+                }
+                offset += rest.len() as u64;
+                lines.clear();
+            }
+            if end_of_script {
+                return Ok(done);
+            }
+        }
+    }
+}
+
+/// `sqlite3_complete` knows about string literals, comments and trigger bodies
+fn is_complete(sql: &str) -> Result<bool> {
+    let sql = CString::new(sql)?;
+    Ok(unsafe { ffi::sqlite3_complete(sql.as_ptr()) != 0 })
+}
+
+/// the length of the first complete statement of `sql`, up to its `;`
+fn first_statement(sql: &str) -> Result<Option<usize>> {
+    for (end, _) in sql.match_indices(';') {
+        if is_complete(&sql[..=end])? {
+            return Ok(Some(end + 1));
+        }
+    }
+    Ok(None)
+}
+
+/// `sql` past any leading whitespace, `-- line` and `/* block */` comments
+fn skip_comments(sql: &str) -> &str {
+    let mut sql = sql.trim_start();
+    loop {
+        sql = if let Some(comment) = sql.strip_prefix("--") {
+            comment.find('\n').map_or("", |end| &comment[end + 1..])
+        } else if let Some(comment) = sql.strip_prefix("/*") {
+            comment.find("*/").map_or("", |end| &comment[end + 2..])
+        } else {
+            return sql;
+        }
+        .trim_start();
+    }
+}
+
+/// whether `statement` is a top level BEGIN, COMMIT, END or ROLLBACK,
+/// but not a ROLLBACK TO a savepoint
+fn is_transaction_control(statement: &str) -> bool {
+    let mut words = skip_comments(statement)
+        .split(|c: char| !c.is_ascii_alphabetic())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_ascii_lowercase());
+    match words.next().as_deref() {
+        Some("begin") | Some("commit") | Some("end") => true,
+        Some("rollback") => words.find(|word| word != "transaction").as_deref() != Some("to"),
+        // SAVEPOINT and RELEASE nest inside the transaction of `restore_from_script`
+        _ => false,
+    }
+}
@@ -0,0 +1,103 @@
+//! Snapshotting whole databases to and from bytes
+use super::{Database, Result};
+use libc::{c_char, c_uint, c_void};
+use sqlite3_sys as ffi;
+
+impl Database {
+    /// serializes the main database into the bytes of an sqlite database file.
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// # let database = Database::open(":memory:")?;
+    /// database.execute("create table user (name text)", ())?;
+    /// database.execute("insert into user values ('amin')", ())?;
+    /// let bytes = database.serialize()?;
+    ///
+    /// let copy = Database::open(":memory:")?;
+    /// copy.deserialize(&bytes)?;
+    /// let name: String = copy.collect("select name from user", ())?;
+    /// assert!(name == "amin");
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn serialize(&self) -> Result<Vec<u8>> {
+        let mut size: ffi::sqlite3_int64 = 0;
+        let ptr = unsafe {
+            ffi::sqlite3_serialize(self.db, b"main\0".as_ptr() as *const c_char, &mut size, 0)
+        };
+        if ptr.is_null() {
+            return match size {
+                0 => Ok(vec![]),
+                _ => Err(ffi::SQLITE_NOMEM.into()),
+            };
+        }
+        let bytes = unsafe { std::slice::from_raw_parts(ptr, size as usize).to_vec() };
+        unsafe { ffi::sqlite3_free(ptr as *mut c_void) };
+        Ok(bytes)
+    }
+
+    /// replaces the main database with a copy of `bytes`, as returned by `serialize`.
+    ///
+    /// The connection keeps the database in memory and it can grow as it is written to.
+    pub fn deserialize(&self, bytes: &[u8]) -> Result<()> {
+        let size = bytes.len() as ffi::sqlite3_int64;
+        let ptr =
+            unsafe { ffi::sqlite3_malloc64(bytes.len().max(1) as ffi::sqlite3_uint64) } as *mut u8;
+        if ptr.is_null() {
+            return Err(ffi::SQLITE_NOMEM.into());
+        }
+        unsafe { std::ptr::copy_nonoverlapping(bytes.as_ptr(), ptr, bytes.len()) };
+
+        // sqlite frees the buffer, even when this fails
+        let flags = ffi::SQLITE_DESERIALIZE_FREEONCLOSE | ffi::SQLITE_DESERIALIZE_RESIZEABLE;
+        let ecode = unsafe {
+            ffi::sqlite3_deserialize(
+                self.db,
+                b"main\0".as_ptr() as *const c_char,
+                ptr,
+                size,
+                size,
+                flags as c_uint,
+            )
+        };
+        match ecode {
+            ffi::SQLITE_OK => Ok(()),
+            other => Err(self.error(other)),
+        }
+    }
+
+    /// opens a read-only database over `bytes`, like an `include_bytes!` dataset,
+    /// without copying them or touching the filesystem
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// # let database = Database::open(":memory:")?;
+    /// database.execute("create table user (name text)", ())?;
+    /// database.execute("insert into user values ('amin')", ())?;
+    /// let bytes: &'static [u8] = Box::leak(database.serialize()?.into_boxed_slice());
+    ///
+    /// let dataset = Database::open_from_bytes(bytes)?;
+    /// let name: String = dataset.collect("select name from user", ())?;
+    /// assert!(name == "amin");
+    /// assert!(dataset.execute("delete from user", ()).is_err());
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn open_from_bytes(bytes: &'static [u8]) -> Result<Database> {
+        let database = Database::open(":memory:")?;
+        let size = bytes.len() as ffi::sqlite3_int64;
+        // sqlite never writes to a read-only buffer, nor frees one without FREEONCLOSE
+        let ecode = unsafe {
+            ffi::sqlite3_deserialize(
+                database.db,
+                b"main\0".as_ptr() as *const c_char,
+                bytes.as_ptr() as *mut u8,
+                size,
+                size,
+                ffi::SQLITE_DESERIALIZE_READONLY as c_uint,
+            )
+        };
+        match ecode {
+            ffi::SQLITE_OK => Ok(database),
+            other => Err(database.error(other)),
+        }
+    }
+}
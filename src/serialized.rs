@@ -0,0 +1,104 @@
+//! A connection that can be shared between threads, see `SerializedDatabase`
+use super::{Database, Result};
+use libc::c_int;
+use sqlite3_sys as ffi;
+use std::ops::Deref;
+use std::sync::{Mutex, MutexGuard};
+
+/// A `Send + Sync` database, so it can be shared in an `Arc` between threads
+///
+/// The connection is opened with `SQLITE_OPEN_FULLMUTEX` and every use goes through
+/// `lock()`, so only one thread at a time touches it.
+///
+/// Callbacks registered through the guard (functions, hooks, collations, ...)
+/// may later run on any thread that locks the database,
+/// so every API storing one requires it to be `Send`:
+///
+/// ```compile_fail
+/// # use rsqlite::*;
+/// use std::{cell::RefCell, rc::Rc};
+///
+/// let database = SerializedDatabase::open(":memory:")?;
+/// let log = Rc::new(RefCell::new(vec![]));
+/// database.lock().update_hook(move |_, _, table, _| log.borrow_mut().push(table.to_owned()));
+/// # Ok::<(), RsqliteError>(())
+/// ```
+///
+/// ```
+/// # use rsqlite::*;
+/// use std::sync::Arc;
+///
+/// let database = Arc::new(SerializedDatabase::open(":memory:")?);
+/// database.lock().execute("create table hits (thread int)", ())?;
+///
+/// let handles: Vec<_> = (0..4)
+///     .map(|thread| {
+///         let database = Arc::clone(&database);
+///         std::thread::spawn(move || database.lock().execute("insert into hits values (?)", thread))
+///     })
+///     .collect();
+/// for handle in handles {
+///     handle.join().unwrap()?;
+/// }
+///
+/// let hits: i32 = database.lock().collect("select count(*) from hits", ())?;
+/// assert!(hits == 4);
+/// # Ok::<(), RsqliteError>(())
+/// ```
+pub struct SerializedDatabase {
+    database: Mutex<Connection>,
+}
+
+/// A `Database` that can move to another thread
+struct Connection(Database);
+
+// The connection pointer and the `Cell`s of `Database` are not `Send`, but the mutex
+// gives a single thread at a time access to them, and sqlite serializes the connection
+// itself, see `SQLITE_OPEN_FULLMUTEX`. Every callback stored on the connection is `Send`.
+unsafe impl Send for Connection {}
+
+/// Exclusive access to a `SerializedDatabase`, released when dropped
+pub struct SerializedGuard<'a> {
+    guard: MutexGuard<'a, Connection>,
+}
+
+impl SerializedDatabase {
+    /// opens the database read-write, creating it if it does not exist
+    pub fn open(path: &str) -> Result<SerializedDatabase> {
+        let flags = ffi::SQLITE_OPEN_READWRITE | ffi::SQLITE_OPEN_CREATE;
+        SerializedDatabase::open_with_flags(path, flags)
+    }
+
+    /// opens the database with `flags`, `SQLITE_OPEN_NOMUTEX` is replaced by `SQLITE_OPEN_FULLMUTEX`
+    ///
+    /// Fails with `Misuse` if sqlite was compiled with `SQLITE_THREADSAFE=0`.
+    pub fn open_with_flags(path: &str, flags: c_int) -> Result<SerializedDatabase> {
+        if unsafe { ffi::sqlite3_threadsafe() } == 0 {
+            return Err(ffi::SQLITE_MISUSE.into());
+        }
+        let flags = (flags & !ffi::SQLITE_OPEN_NOMUTEX) | ffi::SQLITE_OPEN_FULLMUTEX;
+        let database = Database::open_with_flags(path, flags)?;
+        Ok(SerializedDatabase {
+            database: Mutex::new(Connection(database)),
+        })
+    }
+
+    /// waits for exclusive access to the database
+    ///
+    /// A panic while the database was locked does not poison it.
+    pub fn lock(&self) -> SerializedGuard<'_> {
+        let guard = match self.database.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        SerializedGuard { guard }
+    }
+}
+
+impl Deref for SerializedGuard<'_> {
+    type Target = Database;
+
+    fn deref(&self) -> &Database {
+        &self.guard.0
+    }
+}
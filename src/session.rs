@@ -0,0 +1,562 @@
+//! Recording changes as changesets and patchsets, see the `session` feature
+//! and https://sqlite.org/sessionintro.html
+use super::function::FromValue;
+use super::hooks::Action;
+use super::{Database, Result};
+use libc::{c_char, c_int, c_uchar, c_void};
+use sqlite3_sys as ffi;
+use std::ffi::{CStr, CString};
+use std::marker::PhantomData;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+
+/// Records the changes made to the attached tables of a database
+///
+/// ```
+/// # use rsqlite::*;
+/// # let database = Database::open(":memory:")?;
+/// database.execute("create table user (id integer primary key, name text)", ())?;
+///
+/// let session = database.session("main")?;
+/// session.attach(Some("user"))?;
+/// assert!(session.is_empty());
+///
+/// database.execute("insert into user values (1, 'amin')", ())?;
+/// database.execute("update user set name = 'bob' where id = 1", ())?;
+/// assert!(!session.is_empty());
+///
+/// let changeset = session.changeset()?;
+/// let patchset = session.patchset()?;
+/// assert!(!changeset.is_empty() && patchset.len() <= changeset.len());
+/// # Ok::<(), RsqliteError>(())
+/// ```
+pub struct Session<'a> {
+    session: *mut ffi::sqlite3_session,
+    database: &'a Database,
+}
+
+/// Merges several changesets, or several patchsets, into one
+///
+/// Changes to the same row are combined, e.g. an insert followed by an update
+/// becomes a single insert of the updated values.
+///
+/// ```
+/// # use rsqlite::*;
+/// use rsqlite::session::{Changegroup, ConflictAction};
+///
+/// # let database = Database::open(":memory:")?;
+/// database.execute("create table user (id integer primary key, name text)", ())?;
+/// let mut changesets = vec![];
+/// for sql in ["insert into user values (1, 'amin')", "update user set name = 'bob'"] {
+///     let session = database.session("main")?;
+///     session.attach(None)?;
+///     database.execute(sql, ())?;
+///     changesets.push(session.changeset()?);
+/// }
+///
+/// let mut group = Changegroup::new()?;
+/// for changeset in &changesets {
+///     group.add(changeset)?;
+/// }
+/// let merged = group.output()?;
+///
+/// let copy = Database::open(":memory:")?;
+/// copy.execute("create table user (id integer primary key, name text)", ())?;
+/// copy.apply_changeset(&merged, |_| true, |_, _| ConflictAction::Abort)?;
+/// let names: Vec<String> = copy.collect_vec("select name from user", ())?;
+/// assert!(names == vec!["bob"]);
+/// # Ok::<(), RsqliteError>(())
+/// ```
+pub struct Changegroup {
+    group: *mut ffi::sqlite3_changegroup,
+}
+
+impl Changegroup {
+    /// an empty changegroup
+    pub fn new() -> Result<Changegroup> {
+        let mut group = ptr::null_mut();
+        match unsafe { ffi::sqlite3changegroup_new(&mut group) } {
+            ffi::SQLITE_OK => Ok(Changegroup { group }),
+            other => Err(other.into()),
+        }
+    }
+
+    /// adds the changes of `changeset`, mixing changesets and patchsets is an error
+    pub fn add(&mut self, changeset: &[u8]) -> Result<()> {
+        let ecode = unsafe {
+            ffi::sqlite3changegroup_add(
+                self.group,
+                changeset.len() as c_int,
+                changeset.as_ptr() as *mut c_void,
+            )
+        };
+        match ecode {
+            ffi::SQLITE_OK => Ok(()),
+            other => Err(other.into()),
+        }
+    }
+
+    /// the merged changeset, or patchset if patchsets were added
+    pub fn output(&self) -> Result<Vec<u8>> {
+        let mut size = 0;
+        let mut buffer = ptr::null_mut();
+        let ecode = unsafe { ffi::sqlite3changegroup_output(self.group, &mut size, &mut buffer) };
+        let output = unsafe { sqlite_buffer(buffer, size) };
+        match ecode {
+            ffi::SQLITE_OK => Ok(output),
+            other => Err(other.into()),
+        }
+    }
+}
+
+impl Drop for Changegroup {
+    fn drop(&mut self) {
+        unsafe { ffi::sqlite3changegroup_delete(self.group) };
+    }
+}
+
+/// Why a change could not be applied cleanly, passed to the conflict handler of `apply_changeset`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictKind {
+    /// the row to update or delete exists, but its values differ from the old values of the change
+    Data,
+    /// the row to update or delete does not exist
+    NotFound,
+    /// the row to insert already exists
+    Conflict,
+    /// applying the change violates a UNIQUE, CHECK or NOT NULL constraint
+    Constraint,
+    /// the whole changeset violates foreign keys, reported once before committing
+    /// with the number of violations and no change
+    ForeignKey(usize),
+}
+
+/// How to resolve a conflict reported to the conflict handler of `apply_changeset`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictAction {
+    /// skips the change
+    Omit,
+    /// overwrites the conflicting row, only valid for `Data` and `Conflict`
+    Replace,
+    /// rolls back the whole changeset, `apply_changeset` returns `Err(Abort)`
+    Abort,
+}
+
+impl ConflictAction {
+    fn as_raw(self) -> c_int {
+        match self {
+            ConflictAction::Omit => ffi::SQLITE_CHANGESET_OMIT,
+            ConflictAction::Replace => ffi::SQLITE_CHANGESET_REPLACE,
+            ConflictAction::Abort => ffi::SQLITE_CHANGESET_ABORT,
+        }
+    }
+}
+
+/// Iterates over the changes of a changeset or patchset, see `Change`
+///
+/// ```
+/// # use rsqlite::*;
+/// use rsqlite::hooks::Action;
+/// use rsqlite::session::ChangesetIter;
+///
+/// # let database = Database::open(":memory:")?;
+/// database.execute("create table user (id integer primary key, name text)", ())?;
+/// database.execute("insert into user values (1, 'amin')", ())?;
+/// let session = database.session("main")?;
+/// session.attach(None)?;
+/// database.execute("update user set name = 'bob' where id = 1", ())?;
+///
+/// let changeset = session.changeset()?;
+/// let mut changes = ChangesetIter::new(&changeset)?;
+/// while let Some(change) = changes.next()? {
+///     assert!(change.table() == "user" && change.action() == Action::Update);
+///     assert!(change.primary_key() == vec![true, false]);
+///     assert!(change.old_value::<String>(1)? == Some("amin".to_owned()));
+///     assert!(change.new_value::<String>(1)? == Some("bob".to_owned()));
+/// }
+/// # Ok::<(), RsqliteError>(())
+/// ```
+pub struct ChangesetIter<'c> {
+    iter: *mut ffi::sqlite3_changeset_iter,
+    changeset: PhantomData<&'c [u8]>,
+}
+
+/// A single change of a changeset, only valid until the next one is read
+pub struct Change<'i> {
+    iter: *mut ffi::sqlite3_changeset_iter,
+    table: String,
+    column_count: c_int,
+    action: Action,
+    indirect: bool,
+    marker: PhantomData<&'i ()>,
+}
+
+type ChangesetValueFn = unsafe extern "C" fn(
+    *mut ffi::sqlite3_changeset_iter,
+    c_int,
+    *mut *mut ffi::sqlite3_value,
+) -> c_int;
+
+impl<'c> ChangesetIter<'c> {
+    /// starts iterating over `changeset`
+    pub fn new(changeset: &'c [u8]) -> Result<ChangesetIter<'c>> {
+        let mut iter = ptr::null_mut();
+        let ecode = unsafe {
+            ffi::sqlite3changeset_start(
+                &mut iter,
+                changeset.len() as c_int,
+                changeset.as_ptr() as *mut c_void,
+            )
+        };
+        match ecode {
+            ffi::SQLITE_OK => Ok(ChangesetIter {
+                iter,
+                changeset: PhantomData,
+            }),
+            other => Err(other.into()),
+        }
+    }
+
+    /// reads the next change, or `None` at the end of the changeset
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Result<Option<Change<'_>>> {
+        match unsafe { ffi::sqlite3changeset_next(self.iter) } {
+            ffi::SQLITE_ROW => unsafe { Change::read(self.iter) }.map(Some),
+            ffi::SQLITE_DONE => Ok(None),
+            other => Err(other.into()),
+        }
+    }
+}
+
+impl<'c> Drop for ChangesetIter<'c> {
+    fn drop(&mut self) {
+        unsafe { ffi::sqlite3changeset_finalize(self.iter) };
+    }
+}
+
+impl<'i> Change<'i> {
+    /// # Safety
+    /// `iter` must point to a change, and outlive `'i`
+    unsafe fn read(iter: *mut ffi::sqlite3_changeset_iter) -> Result<Change<'i>> {
+        let mut table: *const c_char = ptr::null();
+        let mut column_count = 0;
+        let mut action = 0;
+        let mut indirect = 0;
+        let ecode = ffi::sqlite3changeset_op(
+            iter,
+            &mut table,
+            &mut column_count,
+            &mut action,
+            &mut indirect,
+        );
+        if ecode != ffi::SQLITE_OK {
+            return Err(ecode.into());
+        }
+        if table.is_null() {
+            return Err(ffi::SQLITE_MISUSE.into());
+        }
+        Ok(Change {
+            iter,
+            table: CStr::from_ptr(table).to_string_lossy().into_owned(),
+            column_count,
+            action: match action {
+                ffi::SQLITE_INSERT => Action::Insert,
+                ffi::SQLITE_UPDATE => Action::Update,
+                _ => Action::Delete,
+            },
+            indirect: indirect != 0,
+            marker: PhantomData,
+        })
+    }
+
+    /// the table the change applies to
+    pub fn table(&self) -> &str {
+        &self.table
+    }
+
+    /// whether a row was inserted, updated or deleted
+    pub fn action(&self) -> Action {
+        self.action
+    }
+
+    /// whether the change was recorded while the session was marked indirect
+    pub fn indirect(&self) -> bool {
+        self.indirect
+    }
+
+    /// the number of columns of the table
+    pub fn column_count(&self) -> usize {
+        self.column_count as usize
+    }
+
+    /// which columns are part of the primary key of the table
+    pub fn primary_key(&self) -> Vec<bool> {
+        let mut flags: *mut c_uchar = ptr::null_mut();
+        let mut count = 0;
+        unsafe { ffi::sqlite3changeset_pk(self.iter, &mut flags, &mut count) };
+        match flags.is_null() {
+            true => Vec::new(),
+            false => unsafe { std::slice::from_raw_parts(flags, count as usize) }
+                .iter()
+                .map(|&flag| flag != 0)
+                .collect(),
+        }
+    }
+
+    /// the value of `column` before an update or delete,
+    /// `None` if it is not part of the change, like unchanged columns of an update
+    pub fn old_value<T: FromValue>(&self, column: usize) -> Result<Option<T>> {
+        self.value(ffi::sqlite3changeset_old, column)
+    }
+
+    /// the value of `column` after an insert or update,
+    /// `None` if it is not part of the change, like unchanged columns of an update
+    pub fn new_value<T: FromValue>(&self, column: usize) -> Result<Option<T>> {
+        self.value(ffi::sqlite3changeset_new, column)
+    }
+
+    /// the value of `column` in the row already in the database,
+    /// only available to the conflict handler for `Data` and `Conflict` conflicts
+    pub fn conflicting_value<T: FromValue>(&self, column: usize) -> Result<Option<T>> {
+        self.value(ffi::sqlite3changeset_conflict, column)
+    }
+
+    fn value<T: FromValue>(&self, f: ChangesetValueFn, column: usize) -> Result<Option<T>> {
+        if column >= self.column_count() {
+            return Err(ffi::SQLITE_RANGE.into());
+        }
+        let mut value = ptr::null_mut();
+        match unsafe { f(self.iter, column as c_int, &mut value) } {
+            ffi::SQLITE_OK if value.is_null() => Ok(None),
+            ffi::SQLITE_OK => Ok(Some(unsafe { T::from_value(value) })),
+            other => Err(other.into()),
+        }
+    }
+}
+
+struct ApplyHandlers<F, C> {
+    filter: F,
+    conflict: C,
+}
+
+impl Database {
+    /// applies a changeset or patchset in a single savepoint, rolled back on error
+    ///
+    /// Changes to tables for which `filter` returns false are skipped,
+    /// `conflict` decides what to do with the changes that do not apply cleanly.
+    /// A panic in `filter` skips the table and a panic in `conflict` aborts the whole apply.
+    /// It is passed the conflicting change, except for `ForeignKey` conflicts.
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// use rsqlite::session::{ConflictAction, ConflictKind};
+    ///
+    /// let schema = "create table user (id integer primary key, name text)";
+    /// let source = Database::open(":memory:")?;
+    /// source.execute(schema, ())?;
+    /// let session = source.session("main")?;
+    /// session.attach(None)?;
+    /// source.execute("insert into user values (1, 'amin'), (2, 'bob')", ())?;
+    /// let changeset = session.changeset()?;
+    ///
+    /// let target = Database::open(":memory:")?;
+    /// target.execute(schema, ())?;
+    /// target.execute("insert into user values (2, 'robert')", ())?;
+    /// let mut conflicts = vec![];
+    /// target.apply_changeset(&changeset, |table| table == "user", |kind, change| {
+    ///     let change = change.unwrap();
+    ///     conflicts.push((kind, change.conflicting_value::<String>(1).unwrap()));
+    ///     ConflictAction::Replace
+    /// })?;
+    /// assert!(conflicts == vec![(ConflictKind::Conflict, Some("robert".to_owned()))]);
+    ///
+    /// let names: Vec<String> = target.collect_vec("select name from user order by id", ())?;
+    /// assert!(names == vec!["amin", "bob"]);
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    ///
+    /// Foreign key violations are reported once, with their count:
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// use rsqlite::session::{ConflictAction, ConflictKind};
+    ///
+    /// let user = "create table user (id integer primary key)";
+    /// let post = "create table post (id integer primary key, user integer references user(id))";
+    /// let source = Database::open(":memory:")?;
+    /// source.execute(user, ())?;
+    /// source.execute(post, ())?;
+    /// source.execute("insert into user values (1)", ())?;
+    /// let session = source.session("main")?;
+    /// session.attach(Some("post"))?;
+    /// source.execute("insert into post values (1, 1)", ())?;
+    /// let changeset = session.changeset()?;
+    ///
+    /// let target = Database::open(":memory:")?;
+    /// target.execute(user, ())?;
+    /// target.execute(post, ())?;
+    /// target.execute("pragma foreign_keys = on", ())?;
+    /// let mut conflicts = vec![];
+    /// let result = target.apply_changeset(&changeset, |_| true, |kind, change| {
+    ///     conflicts.push((kind, change.is_some()));
+    ///     ConflictAction::Abort
+    /// });
+    /// assert!(result.is_err());
+    /// assert!(conflicts == vec![(ConflictKind::ForeignKey(1), false)]);
+    /// assert!(target.collect::<i32>("select count(*) from post", ())? == 0);
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn apply_changeset<F, C>(&self, changeset: &[u8], filter: F, conflict: C) -> Result<()>
+    where
+        F: FnMut(&str) -> bool,
+        C: FnMut(ConflictKind, Option<&Change>) -> ConflictAction,
+    {
+        let mut handlers = ApplyHandlers { filter, conflict };
+        let ecode = unsafe {
+            ffi::sqlite3changeset_apply_v2(
+                self.db,
+                changeset.len() as c_int,
+                changeset.as_ptr() as *mut c_void,
+                Some(call_filter::<F, C>),
+                Some(call_conflict::<F, C>),
+                &mut handlers as *mut ApplyHandlers<F, C> as *mut c_void,
+                ptr::null_mut(),
+                ptr::null_mut(),
+                0,
+            )
+        };
+        match ecode {
+            ffi::SQLITE_OK => Ok(()),
+            other => Err(self.error(other)),
+        }
+    }
+
+    /// starts recording changes to `schema`, like `main` or an attached alias,
+    /// no table is recorded until it is attached
+    pub fn session(&self, schema: &str) -> Result<Session<'_>> {
+        let schema = CString::new(schema)?;
+        let mut session = ptr::null_mut();
+        let ecode = unsafe { ffi::sqlite3session_create(self.db, schema.as_ptr(), &mut session) };
+        match ecode {
+            ffi::SQLITE_OK => Ok(Session {
+                session,
+                database: self,
+            }),
+            other => Err(self.error(other)),
+        }
+    }
+}
+
+impl<'a> Session<'a> {
+    /// records the changes to `table`, or to every table if `None`
+    ///
+    /// Only tables with a PRIMARY KEY are recorded.
+    pub fn attach(&self, table: Option<&str>) -> Result<()> {
+        let table = table.map(CString::new).transpose()?;
+        let table = table.as_ref().map_or(ptr::null(), |table| table.as_ptr());
+        let ecode = unsafe { ffi::sqlite3session_attach(self.session, table) };
+        match ecode {
+            ffi::SQLITE_OK => Ok(()),
+            other => Err(self.database.error(other)),
+        }
+    }
+
+    /// pauses or resumes recording, returning whether it is now enabled
+    pub fn set_enabled(&self, enabled: bool) -> bool {
+        unsafe { ffi::sqlite3session_enable(self.session, enabled as c_int) != 0 }
+    }
+
+    /// marks the changes recorded from now on as indirect, or not
+    pub fn set_indirect(&self, indirect: bool) -> bool {
+        unsafe { ffi::sqlite3session_indirect(self.session, indirect as c_int) != 0 }
+    }
+
+    /// whether no change has been recorded
+    pub fn is_empty(&self) -> bool {
+        unsafe { ffi::sqlite3session_isempty(self.session) != 0 }
+    }
+
+    /// the recorded changes, with the old values of updated and deleted rows
+    pub fn changeset(&self) -> Result<Vec<u8>> {
+        self.output(ffi::sqlite3session_changeset)
+    }
+
+    /// the recorded changes, smaller than a changeset since it only keeps
+    /// the primary key of deleted rows and the new values of updated columns
+    pub fn patchset(&self) -> Result<Vec<u8>> {
+        self.output(ffi::sqlite3session_patchset)
+    }
+
+    fn output(
+        &self,
+        f: unsafe extern "C" fn(*mut ffi::sqlite3_session, *mut c_int, *mut *mut c_void) -> c_int,
+    ) -> Result<Vec<u8>> {
+        let mut size = 0;
+        let mut buffer = ptr::null_mut();
+        let ecode = unsafe { f(self.session, &mut size, &mut buffer) };
+        let output = unsafe { sqlite_buffer(buffer, size) };
+        match ecode {
+            ffi::SQLITE_OK => Ok(output),
+            other => Err(self.database.error(other)),
+        }
+    }
+}
+
+impl<'a> Drop for Session<'a> {
+    fn drop(&mut self) {
+        unsafe { ffi::sqlite3session_delete(self.session) };
+    }
+}
+
+unsafe extern "C" fn call_filter<F, C>(data: *mut c_void, table: *const c_char) -> c_int
+where
+    F: FnMut(&str) -> bool,
+{
+    let handlers = &mut *(data as *mut ApplyHandlers<F, C>);
+    let table = CStr::from_ptr(table).to_string_lossy();
+    catch_unwind(AssertUnwindSafe(|| (handlers.filter)(&table))).unwrap_or(false) as c_int
+}
+
+unsafe extern "C" fn call_conflict<F, C>(
+    data: *mut c_void,
+    kind: c_int,
+    iter: *mut ffi::sqlite3_changeset_iter,
+) -> c_int
+where
+    C: FnMut(ConflictKind, Option<&Change>) -> ConflictAction,
+{
+    let handlers = &mut *(data as *mut ApplyHandlers<F, C>);
+    let kind = match kind {
+        ffi::SQLITE_CHANGESET_DATA => ConflictKind::Data,
+        ffi::SQLITE_CHANGESET_NOTFOUND => ConflictKind::NotFound,
+        ffi::SQLITE_CHANGESET_CONFLICT => ConflictKind::Conflict,
+        ffi::SQLITE_CHANGESET_CONSTRAINT => ConflictKind::Constraint,
+        _ => {
+            // the iterator holds no change here, its table name is NULL
+            let mut count = 0;
+            if ffi::sqlite3changeset_fk_conflicts(iter, &mut count) != ffi::SQLITE_OK {
+                return ffi::SQLITE_CHANGESET_ABORT;
+            }
+            let kind = ConflictKind::ForeignKey(count as usize);
+            return catch_unwind(AssertUnwindSafe(|| (handlers.conflict)(kind, None)))
+                .map_or(ffi::SQLITE_CHANGESET_ABORT, ConflictAction::as_raw);
+        }
+    };
+    match Change::read(iter) {
+        Ok(change) => catch_unwind(AssertUnwindSafe(|| {
+            (handlers.conflict)(kind, Some(&change))
+        }))
+        .map_or(ffi::SQLITE_CHANGESET_ABORT, ConflictAction::as_raw),
+        Err(_) => ffi::SQLITE_CHANGESET_ABORT,
+    }
+}
+
+/// copies and frees a buffer allocated by sqlite
+pub(crate) unsafe fn sqlite_buffer(buffer: *mut c_void, size: c_int) -> Vec<u8> {
+    if buffer.is_null() {
+        return Vec::new();
+    }
+    let output = std::slice::from_raw_parts(buffer as *const u8, size as usize).to_vec();
+    ffi::sqlite3_free(buffer);
+    output
+}
@@ -0,0 +1,89 @@
+//! Space usage analysis, built on the DBSTAT virtual table
+use super::{Database, Result};
+
+/// How much space a single table or index takes in the database file
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpaceUsage {
+    /// name of the table or index
+    pub name: String,
+    /// true for indexes, false for tables (including `sqlite_schema`)
+    pub is_index: bool,
+    /// number of pages used
+    pub pages: i64,
+    /// bytes of payload stored in those pages
+    pub payload_bytes: i64,
+    /// bytes that are allocated but unused
+    pub unused_bytes: i64,
+    /// total bytes of all the pages
+    pub total_bytes: i64,
+    /// fraction of pages that do not directly follow the previous page, from 0.0 to 1.0
+    pub fragmentation: f64,
+}
+
+impl Database {
+    /// report the space used by every table and index of the main database.
+    ///
+    /// SQLite must be compiled with `SQLITE_ENABLE_DBSTAT_VTAB`,
+    /// otherwise this returns `Err(Error)` (no such table: dbstat).
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// # let database = Database::open(":memory:")?;
+    /// database.execute("create table user (name text)", ())?;
+    /// database.execute("insert into user values ('amin')", ())?;
+    ///
+    /// let report = database.space_report()?;
+    /// let user = report.iter().find(|usage| usage.name == "user").unwrap();
+    /// assert!(user.pages == 1 && !user.is_index);
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn space_report(&self) -> Result<Vec<SpaceUsage>> {
+        let mut report: Vec<SpaceUsage> = vec![];
+        let mut previous_page = 0;
+        let mut out_of_order = 0;
+
+        self.for_each(
+            "select s.name, coalesce(m.type = 'index', 0), s.pageno, s.payload, s.unused, s.pgsize
+             from dbstat as s left join sqlite_master as m on m.name = s.name
+             order by s.name, s.path",
+            (),
+            |name: String, is_index: i32, page: i32, payload: i32, unused: i32, size: i32| {
+                if report
+                    .last()
+                    .map(|usage| usage.name != name)
+                    .unwrap_or(true)
+                {
+                    finish(report.last_mut(), out_of_order);
+                    report.push(SpaceUsage {
+                        name,
+                        is_index: is_index != 0,
+                        pages: 0,
+                        payload_bytes: 0,
+                        unused_bytes: 0,
+                        total_bytes: 0,
+                        fragmentation: 0.0,
+                    });
+                    out_of_order = 0;
+                } else if page != previous_page + 1 {
+                    out_of_order += 1;
+                }
+                previous_page = page;
+
+                let usage = report.last_mut().unwrap();
+                usage.pages += 1;
+                usage.payload_bytes += payload as i64;
+                usage.unused_bytes += unused as i64;
+                usage.total_bytes += size as i64;
+            },
+        )?;
+        finish(report.last_mut(), out_of_order);
+
+        Ok(report)
+    }
+}
+
+fn finish(usage: Option<&mut SpaceUsage>, out_of_order: i64) {
+    if let Some(usage) = usage {
+        usage.fragmentation = out_of_order as f64 / usage.pages as f64;
+    }
+}
@@ -0,0 +1,96 @@
+use super::{Result, Statement};
+use libc::c_int;
+use sqlite3_sys as ffi;
+use std::ffi::{CStr, CString};
+
+#[cfg(feature = "normalize")]
+extern "C" {
+    // only exported by sqlite builds with `SQLITE_ENABLE_NORMALIZE`
+    fn sqlite3_normalized_sql(stmt: *mut ffi::sqlite3_stmt) -> *const libc::c_char;
+}
+
+impl<'a> Statement<'a> {
+    /// the SQL text the statement was prepared from
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// # let database = Database::open(":memory:")?;
+    /// let mut statement = database.prepare("select ?, ?")?;
+    /// assert!(statement.sql() == Some("select ?, ?"));
+    ///
+    /// (1, "it's").bind(&mut statement, &mut 1)?;
+    /// assert!(statement.expanded_sql().as_deref() == Some("select 1, 'it''s'"));
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn sql(&self) -> Option<&str> {
+        let ptr = unsafe { ffi::sqlite3_sql(self.stmt) };
+        match ptr.is_null() {
+            true => None,
+            false => unsafe { CStr::from_ptr(ptr) }.to_str().ok(),
+        }
+    }
+
+    /// the SQL text with the parameters replaced by the currently bound values
+    ///
+    /// `None` if the expansion is larger than `SQLITE_LIMIT_LENGTH` or on out of memory.
+    pub fn expanded_sql(&self) -> Option<String> {
+        unsafe {
+            let ptr = ffi::sqlite3_expanded_sql(self.stmt);
+            if ptr.is_null() {
+                return None;
+            }
+            let sql = CStr::from_ptr(ptr).to_string_lossy().into_owned();
+            ffi::sqlite3_free(ptr as *mut _);
+            Some(sql)
+        }
+    }
+
+    /// the SQL text with literals replaced by `?` and whitespace normalized,
+    /// requires sqlite compiled with `SQLITE_ENABLE_NORMALIZE`
+    #[cfg(feature = "normalize")]
+    pub fn normalized_sql(&self) -> Option<&str> {
+        let ptr = unsafe { sqlite3_normalized_sql(self.stmt) };
+        match ptr.is_null() {
+            true => None,
+            false => unsafe { CStr::from_ptr(ptr) }.to_str().ok(),
+        }
+    }
+
+    /// number of parameters, the largest index for numbered parameters like `?3`
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// # let database = Database::open(":memory:")?;
+    /// let statement = database.prepare("select :name, ?, @age")?;
+    /// assert!(statement.parameter_count() == 3);
+    /// assert!(statement.parameter_name(1) == Some(":name"));
+    /// assert!(statement.parameter_name(2) == None);
+    /// assert!(statement.parameter_index("@age")? == Some(3));
+    /// assert!(statement.parameter_index("age")? == None);
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn parameter_count(&self) -> c_int {
+        unsafe { ffi::sqlite3_bind_parameter_count(self.stmt) }
+    }
+
+    /// name of the parameter at `index`, starting from 1, including its `:`, `@` or `$` prefix
+    ///
+    /// `None` for nameless `?` parameters or if `index` is out of range.
+    pub fn parameter_name(&self, index: c_int) -> Option<&str> {
+        let ptr = unsafe { ffi::sqlite3_bind_parameter_name(self.stmt, index) };
+        match ptr.is_null() {
+            true => None,
+            false => unsafe { CStr::from_ptr(ptr) }.to_str().ok(),
+        }
+    }
+
+    /// index of the parameter named `name`, which includes its prefix like `:name`
+    pub fn parameter_index(&self, name: &str) -> Result<Option<c_int>> {
+        let name = CString::new(name)?;
+        let index = unsafe { ffi::sqlite3_bind_parameter_index(self.stmt, name.as_ptr()) };
+        Ok(match index {
+            0 => None,
+            index => Some(index),
+        })
+    }
+}
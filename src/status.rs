@@ -0,0 +1,246 @@
+//! Performance counters of statements, connections and the whole library
+use super::Statement;
+use super::{Database, Result};
+use libc::c_int;
+use sqlite3_sys as ffi;
+
+/// The counters of `Statement::status`,
+/// see https://sqlite.org/c3ref/c_stmtstatus_counter.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StmtStatus {
+    /// steps forward in a full table scan, a hint that an index could help
+    FullscanStep,
+    /// sort operations, a hint that an index could help
+    Sort,
+    /// rows inserted into automatic indexes, a hint that a permanent index could help
+    Autoindex,
+    /// virtual machine operations
+    VmStep,
+    /// times the statement was prepared again after a schema change
+    Reprepare,
+    /// times the statement ran to completion or was reset after at least one step
+    Run,
+    /// joins skipped thanks to a bloom filter
+    FilterHit,
+    /// bloom filter checks that did not skip a join
+    FilterMiss,
+    /// bytes of heap used by the statement, never reset
+    MemUsed,
+}
+
+/// The counters of `Database::status`,
+/// see https://sqlite.org/c3ref/c_dbstatus_options.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DbStatus {
+    /// lookaside memory slots in use
+    LookasideUsed,
+    /// lookaside allocations that were satisfied
+    LookasideHit,
+    /// allocations that missed the lookaside because they were too large
+    LookasideMissSize,
+    /// allocations that missed the lookaside because it was full
+    LookasideMissFull,
+    /// bytes of heap used by the page cache
+    CacheUsed,
+    /// like `CacheUsed`, with caches shared between connections split evenly
+    CacheUsedShared,
+    /// page cache hits
+    CacheHit,
+    /// page cache misses
+    CacheMiss,
+    /// dirty pages written to disk
+    CacheWrite,
+    /// dirty pages written to disk in the middle of a transaction
+    CacheSpill,
+    /// bytes of heap used by the schemas
+    SchemaUsed,
+    /// bytes of heap used by the prepared statements
+    StmtUsed,
+    /// 1 if there are unresolved deferred foreign key constraints, 0 otherwise
+    DeferredFks,
+}
+
+/// The counters of `sqlite_status`, shared by every connection,
+/// see https://sqlite.org/c3ref/c_status_malloc_count.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SqliteStatus {
+    /// bytes of heap allocated by sqlite
+    MemoryUsed,
+    /// number of separate heap allocations
+    MallocCount,
+    /// the largest allocation requested, only `highwater` is meaningful
+    MallocSize,
+    /// pages used in the `SQLITE_CONFIG_PAGECACHE` memory
+    PagecacheUsed,
+    /// bytes of page cache that did not fit in the `SQLITE_CONFIG_PAGECACHE` memory
+    PagecacheOverflow,
+    /// the largest page cache allocation, only `highwater` is meaningful
+    PagecacheSize,
+    /// the deepest parser stack, only tracked with `YYTRACKMAXSTACKDEPTH`
+    ParserStack,
+}
+
+/// The value of a status counter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Status {
+    pub current: i64,
+    /// the highest value since the counter was last reset
+    pub highwater: i64,
+}
+
+impl DbStatus {
+    fn as_raw(self) -> c_int {
+        match self {
+            DbStatus::LookasideUsed => ffi::SQLITE_DBSTATUS_LOOKASIDE_USED,
+            DbStatus::LookasideHit => ffi::SQLITE_DBSTATUS_LOOKASIDE_HIT,
+            DbStatus::LookasideMissSize => ffi::SQLITE_DBSTATUS_LOOKASIDE_MISS_SIZE,
+            DbStatus::LookasideMissFull => ffi::SQLITE_DBSTATUS_LOOKASIDE_MISS_FULL,
+            DbStatus::CacheUsed => ffi::SQLITE_DBSTATUS_CACHE_USED,
+            DbStatus::CacheUsedShared => ffi::SQLITE_DBSTATUS_CACHE_USED_SHARED,
+            DbStatus::CacheHit => ffi::SQLITE_DBSTATUS_CACHE_HIT,
+            DbStatus::CacheMiss => ffi::SQLITE_DBSTATUS_CACHE_MISS,
+            DbStatus::CacheWrite => ffi::SQLITE_DBSTATUS_CACHE_WRITE,
+            DbStatus::CacheSpill => ffi::SQLITE_DBSTATUS_CACHE_SPILL,
+            DbStatus::SchemaUsed => ffi::SQLITE_DBSTATUS_SCHEMA_USED,
+            DbStatus::StmtUsed => ffi::SQLITE_DBSTATUS_STMT_USED,
+            DbStatus::DeferredFks => ffi::SQLITE_DBSTATUS_DEFERRED_FKS,
+        }
+    }
+}
+
+impl SqliteStatus {
+    fn as_raw(self) -> c_int {
+        match self {
+            SqliteStatus::MemoryUsed => ffi::SQLITE_STATUS_MEMORY_USED,
+            SqliteStatus::MallocCount => ffi::SQLITE_STATUS_MALLOC_COUNT,
+            SqliteStatus::MallocSize => ffi::SQLITE_STATUS_MALLOC_SIZE,
+            SqliteStatus::PagecacheUsed => ffi::SQLITE_STATUS_PAGECACHE_USED,
+            SqliteStatus::PagecacheOverflow => ffi::SQLITE_STATUS_PAGECACHE_OVERFLOW,
+            SqliteStatus::PagecacheSize => ffi::SQLITE_STATUS_PAGECACHE_SIZE,
+            SqliteStatus::ParserStack => ffi::SQLITE_STATUS_PARSER_STACK,
+        }
+    }
+}
+
+/// the value of a library wide counter, resetting its `highwater` if `reset` is true
+///
+/// ```
+/// use rsqlite::status::{sqlite_status, SqliteStatus};
+///
+/// let memory = sqlite_status(SqliteStatus::MemoryUsed, false)?;
+/// assert!(memory.highwater >= memory.current);
+/// # Ok::<(), rsqlite::RsqliteError>(())
+/// ```
+pub fn sqlite_status(counter: SqliteStatus, reset: bool) -> Result<Status> {
+    let (mut current, mut highwater) = (0, 0);
+    let ecode = unsafe {
+        ffi::sqlite3_status64(
+            counter.as_raw(),
+            &mut current,
+            &mut highwater,
+            reset as c_int,
+        )
+    };
+    match ecode {
+        ffi::SQLITE_OK => Ok(Status { current, highwater }),
+        other => Err(other.into()),
+    }
+}
+
+impl Database {
+    /// the value of a connection counter, resetting its `highwater` if `reset` is true
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// use rsqlite::status::DbStatus;
+    /// # let database = Database::open(":memory:")?;
+    /// database.execute("create table user (name text)", ())?;
+    /// assert!(database.status(DbStatus::SchemaUsed, false)?.current > 0);
+    /// assert!(database.status(DbStatus::DeferredFks, false)?.current == 0);
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn status(&self, counter: DbStatus, reset: bool) -> Result<Status> {
+        let (mut current, mut highwater) = (0, 0);
+        let ecode = unsafe {
+            ffi::sqlite3_db_status(
+                self.db,
+                counter.as_raw(),
+                &mut current,
+                &mut highwater,
+                reset as c_int,
+            )
+        };
+        match ecode {
+            ffi::SQLITE_OK => Ok(Status {
+                current: current.into(),
+                highwater: highwater.into(),
+            }),
+            other => Err(self.error(other)),
+        }
+    }
+}
+
+impl StmtStatus {
+    fn as_raw(self) -> c_int {
+        match self {
+            StmtStatus::FullscanStep => ffi::SQLITE_STMTSTATUS_FULLSCAN_STEP,
+            StmtStatus::Sort => ffi::SQLITE_STMTSTATUS_SORT,
+            StmtStatus::Autoindex => ffi::SQLITE_STMTSTATUS_AUTOINDEX,
+            StmtStatus::VmStep => ffi::SQLITE_STMTSTATUS_VM_STEP,
+            StmtStatus::Reprepare => ffi::SQLITE_STMTSTATUS_REPREPARE,
+            StmtStatus::Run => ffi::SQLITE_STMTSTATUS_RUN,
+            StmtStatus::FilterHit => ffi::SQLITE_STMTSTATUS_FILTER_HIT,
+            StmtStatus::FilterMiss => ffi::SQLITE_STMTSTATUS_FILTER_MISS,
+            StmtStatus::MemUsed => ffi::SQLITE_STMTSTATUS_MEMUSED,
+        }
+    }
+}
+
+impl<'a> Statement<'a> {
+    /// the current value of a performance counter
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// use rsqlite::status::StmtStatus;
+    /// # let database = Database::open(":memory:")?;
+    /// database.execute("create table user (name text)", ())?;
+    /// database.execute("insert into user values ('amin'), ('bob')", ())?;
+    ///
+    /// let mut statement = database.prepare("select name from user where name = ?")?;
+    /// let _: Option<String> = statement.collect("bob")?;
+    /// assert!(statement.status(StmtStatus::FullscanStep) > 0);
+    /// assert!(statement.status(StmtStatus::Run) == 1);
+    ///
+    /// assert!(statement.reset_status(StmtStatus::Run) == 1);
+    /// assert!(statement.status(StmtStatus::Run) == 0);
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn status(&self, counter: StmtStatus) -> i32 {
+        unsafe { ffi::sqlite3_stmt_status(self.stmt, counter.as_raw(), 0) }
+    }
+
+    /// the current value of a performance counter, which is then reset to zero
+    pub fn reset_status(&self, counter: StmtStatus) -> i32 {
+        unsafe { ffi::sqlite3_stmt_status(self.stmt, counter.as_raw(), 1) }
+    }
+
+    /// whether the statement leaves the database file unchanged
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// # let database = Database::open(":memory:")?;
+    /// database.execute("create table user (name text)", ())?;
+    /// assert!(database.prepare("select * from user")?.readonly());
+    /// assert!(!database.prepare("delete from user")?.readonly());
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn readonly(&self) -> bool {
+        unsafe { ffi::sqlite3_stmt_readonly(self.stmt) != 0 }
+    }
+
+    /// whether the statement was stepped but has not run to completion or been reset,
+    /// which keeps a read transaction open
+    pub fn busy(&self) -> bool {
+        unsafe { ffi::sqlite3_stmt_busy(self.stmt) != 0 }
+    }
+}
@@ -0,0 +1,115 @@
+use super::{Database, Result, RsqliteError, Statement};
+use libc::c_int;
+use sqlite3_sys as ffi;
+
+impl Database {
+    /// enables or disables strict typing for values collected from this connection.
+    ///
+    /// In strict mode the implicit conversions are rejected with `Mismatch`:
+    /// integers only come from INTEGER columns,
+    /// `f64` from INTEGER or REAL, `String` from TEXT and `Box<[u8]>` from BLOB.
+    /// NULL is only accepted by `Option<T>`.
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// # let database = Database::open(":memory:")?;
+    /// let lenient: i32 = database.collect("select '42'", ())?;
+    /// assert!(lenient == 42);
+    ///
+    /// database.set_strict_types(true);
+    /// assert!(matches!(database.collect::<i32>("select '42'", ()), Err(RsqliteError::Mismatch { .. })));
+    /// assert!(database.collect::<i32>("select null", ()).is_err());
+    /// assert!(database.collect::<Option<i32>>("select null", ())? == None);
+    /// assert!(database.collect::<i32>("select 1 << 40", ()).is_err());
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn set_strict_types(&self, enabled: bool) {
+        self.strict_types.set(enabled);
+    }
+}
+
+impl Database {
+    /// when enabled, collecting a whole row fails with `Range` unless the statement
+    /// returns exactly as many columns as the collected type needs.
+    ///
+    /// By default extra columns are ignored.
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// # let database = Database::open(":memory:")?;
+    /// let (a, b): (i32, i32) = database.collect("select 1, 2, 3", ())?;
+    /// assert!((a, b) == (1, 2));
+    ///
+    /// database.set_exact_columns(true);
+    /// assert!(database.collect::<(i32, i32)>("select 1, 2, 3", ()).is_err());
+    /// assert!(database.collect::<(i32, i32, i32)>("select 1, 2, 3", ()).is_ok());
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn set_exact_columns(&self, enabled: bool) {
+        self.exact_columns.set(enabled);
+    }
+
+    /// skips the UTF-8 validation of text collected as `String` from this connection.
+    ///
+    /// # Safety
+    ///
+    /// Every TEXT value collected as `String` while this is enabled must be valid UTF-8,
+    /// which SQLite does not guarantee (e.g. `cast(x'ff' as text)`).
+    pub unsafe fn set_unchecked_utf8(&self, enabled: bool) {
+        self.unchecked_utf8.set(enabled);
+    }
+}
+
+/// in strict mode, fails unless `column` holds one of the `expected` datatypes
+pub(crate) fn check_type(
+    statement: &Statement,
+    column: c_int,
+    expected: &[c_int],
+    target: &str,
+) -> Result<()> {
+    if !statement.database.strict_types.get() {
+        return Ok(());
+    }
+    let sqlite_type = unsafe { ffi::sqlite3_column_type(statement.stmt, column) };
+    match expected.contains(&sqlite_type) {
+        true => Ok(()),
+        false => Err(RsqliteError::new(
+            ffi::SQLITE_MISMATCH,
+            ffi::SQLITE_MISMATCH,
+            format!(
+                "column {} is {}, which can not be collected as {}",
+                column,
+                type_name(sqlite_type),
+                target
+            ),
+        )),
+    }
+}
+
+fn type_name(sqlite_type: c_int) -> &'static str {
+    match sqlite_type {
+        ffi::SQLITE_INTEGER => "INTEGER",
+        ffi::SQLITE_FLOAT => "REAL",
+        ffi::SQLITE_TEXT => "TEXT",
+        ffi::SQLITE_BLOB => "BLOB",
+        _ => "NULL",
+    }
+}
+
+impl Statement<'_> {
+    /// fails unless the statement has enough columns, or exactly `needed` with `set_exact_columns`
+    pub(crate) fn check_columns(&self, needed: c_int) -> Result<()> {
+        let exact = self.database.exact_columns.get();
+        if needed > self.column_count || (exact && needed != self.column_count) {
+            return Err(RsqliteError::new(
+                ffi::SQLITE_RANGE,
+                ffi::SQLITE_RANGE,
+                format!(
+                    "expected {} columns, the statement returns {}",
+                    needed, self.column_count
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
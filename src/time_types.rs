@@ -0,0 +1,162 @@
+//! `Bindable` and `Collectable` for the `time` crate date and time types
+//!
+//! This module is only available with the `time` feature.
+//! Values bind as ISO-8601 text in the format of SQLite's date functions
+//! (`YYYY-MM-DD HH:MM:SS.SSS`, converted to UTC for `OffsetDateTime`),
+//! wrap them in `UnixEpoch` to store integer seconds instead.
+//! Collecting accepts both ISO-8601 text and Unix epoch integers.
+//!
+//! ```
+//! # use rsqlite::*;
+//! use rsqlite::bindable::UnixEpoch;
+//! use time::macros::{date, datetime};
+//! # let database = Database::open(":memory:")?;
+//! let day = date!(2021 - 03 - 14);
+//! let next_day: time::Date = database.collect("select date(?, '+1 day')", day)?;
+//! assert!(next_day == date!(2021 - 03 - 15));
+//!
+//! let moment = datetime!(2021-03-14 15:09:26 +01:00);
+//! let text: String = database.collect("select ?", moment)?;
+//! assert!(text == "2021-03-14 14:09:26");
+//! let epoch: i64 = database.collect("select ?", UnixEpoch(moment))?;
+//! assert!(epoch == moment.unix_timestamp());
+//! let collected: time::OffsetDateTime = database.collect("select ?", epoch)?;
+//! assert!(collected == moment);
+//! # Ok::<(), RsqliteError>(())
+//! ```
+use super::bindable::UnixEpoch;
+use super::datetime::{invalid, stored, Stored};
+use super::{Bindable, Collectable, Result, RsqliteError, Statement};
+use libc::c_int;
+use sqlite3_sys as ffi;
+use time::format_description::well_known::Rfc3339;
+use time::format_description::BorrowedFormatItem;
+use time::macros::format_description;
+use time::{Date, OffsetDateTime, PrimitiveDateTime, Time, UtcOffset};
+
+const DATETIME_FORMAT: &[BorrowedFormatItem<'static>] =
+    format_description!("[year]-[month]-[day] [hour]:[minute]:[second]");
+const DATETIME_FRACTION_FORMAT: &[BorrowedFormatItem<'static>] =
+    format_description!("[year]-[month]-[day] [hour]:[minute]:[second].[subsecond digits:3]");
+const DATE_FORMAT: &[BorrowedFormatItem<'static>] = format_description!("[year]-[month]-[day]");
+const TIME_FORMAT: &[BorrowedFormatItem<'static>] = format_description!("[hour]:[minute]:[second]");
+const TIME_FRACTION_FORMAT: &[BorrowedFormatItem<'static>] =
+    format_description!("[hour]:[minute]:[second].[subsecond digits:3]");
+const DATETIME_PARSE: &[BorrowedFormatItem<'static>] = format_description!(
+    "[year]-[month]-[day][first [ ][T]][hour]:[minute][optional [:[second][optional [.[subsecond]]]]]"
+);
+const TIME_PARSE: &[BorrowedFormatItem<'static>] =
+    format_description!("[hour]:[minute][optional [:[second][optional [.[subsecond]]]]]");
+
+fn format_datetime(datetime: PrimitiveDateTime) -> Result<String> {
+    let format = match datetime.nanosecond() {
+        0 => DATETIME_FORMAT,
+        _ => DATETIME_FRACTION_FORMAT,
+    };
+    datetime.format(format).map_err(unformattable)
+}
+
+fn unformattable(error: time::error::Format) -> RsqliteError {
+    RsqliteError::new(
+        ffi::SQLITE_MISMATCH,
+        ffi::SQLITE_MISMATCH,
+        format!("the date or time can not be formatted - {}", error),
+    )
+}
+
+fn parse_datetime(text: &str) -> Result<PrimitiveDateTime> {
+    let text = text.trim();
+    PrimitiveDateTime::parse(text, DATETIME_PARSE)
+        .or_else(|_| Date::parse(text, DATE_FORMAT).map(|date| date.midnight()))
+        .map_err(|_| invalid(text))
+}
+
+fn from_epoch(seconds: i64) -> Result<OffsetDateTime> {
+    OffsetDateTime::from_unix_timestamp(seconds).map_err(|_| invalid(&seconds.to_string()))
+}
+
+fn to_utc(datetime: OffsetDateTime) -> PrimitiveDateTime {
+    let utc = datetime.to_offset(UtcOffset::UTC);
+    PrimitiveDateTime::new(utc.date(), utc.time())
+}
+
+impl Bindable for PrimitiveDateTime {
+    fn bind(&self, statement: &mut Statement, index: &mut c_int) -> Result<()> {
+        format_datetime(*self)?.bind(statement, index)
+    }
+}
+impl Bindable for OffsetDateTime {
+    fn bind(&self, statement: &mut Statement, index: &mut c_int) -> Result<()> {
+        format_datetime(to_utc(*self))?.bind(statement, index)
+    }
+}
+impl Bindable for Date {
+    fn bind(&self, statement: &mut Statement, index: &mut c_int) -> Result<()> {
+        self.format(DATE_FORMAT)
+            .map_err(unformattable)?
+            .bind(statement, index)
+    }
+}
+impl Bindable for Time {
+    fn bind(&self, statement: &mut Statement, index: &mut c_int) -> Result<()> {
+        let format = match self.nanosecond() {
+            0 => TIME_FORMAT,
+            _ => TIME_FRACTION_FORMAT,
+        };
+        self.format(format)
+            .map_err(unformattable)?
+            .bind(statement, index)
+    }
+}
+impl Bindable for UnixEpoch<PrimitiveDateTime> {
+    fn bind(&self, statement: &mut Statement, index: &mut c_int) -> Result<()> {
+        self.0.assume_utc().unix_timestamp().bind(statement, index)
+    }
+}
+impl Bindable for UnixEpoch<OffsetDateTime> {
+    fn bind(&self, statement: &mut Statement, index: &mut c_int) -> Result<()> {
+        self.0.unix_timestamp().bind(statement, index)
+    }
+}
+
+/// `collect` falls back to the Unix epoch (or midnight) on invalid input
+macro_rules! collectable_time {
+    ($($t:ty, $fallback:expr => |$stored:ident| $parse:expr),+) => ($(
+        impl Collectable for $t {
+            fn collect(statement: &Statement, column: &mut c_int) -> Self {
+                Self::try_collect(statement, column).unwrap_or($fallback)
+            }
+            fn try_collect(statement: &Statement, column: &mut c_int) -> Result<Self> {
+                let $stored = stored(statement, column)?;
+                $parse
+            }
+            fn columns_needed() -> c_int {
+                1
+            }
+        }
+    )+);
+}
+
+collectable_time!(
+    PrimitiveDateTime, to_utc(OffsetDateTime::UNIX_EPOCH) => |stored| match stored {
+        Stored::Text(text) => parse_datetime(&text),
+        Stored::Epoch(seconds) => from_epoch(seconds).map(to_utc),
+    },
+    OffsetDateTime, OffsetDateTime::UNIX_EPOCH => |stored| match stored {
+        Stored::Text(text) => match OffsetDateTime::parse(text.trim(), &Rfc3339) {
+            Ok(datetime) => Ok(datetime),
+            Err(_) => parse_datetime(&text).map(|datetime| datetime.assume_utc()),
+        },
+        Stored::Epoch(seconds) => from_epoch(seconds),
+    },
+    Date, OffsetDateTime::UNIX_EPOCH.date() => |stored| match stored {
+        Stored::Text(text) => parse_datetime(&text).map(|datetime| datetime.date()),
+        Stored::Epoch(seconds) => from_epoch(seconds).map(|datetime| datetime.date()),
+    },
+    Time, Time::MIDNIGHT => |stored| match stored {
+        Stored::Text(text) => Time::parse(text.trim(), TIME_PARSE)
+            .or_else(|_| parse_datetime(&text).map(|datetime| datetime.time()))
+            .map_err(|_| invalid(&text)),
+        Stored::Epoch(seconds) => from_epoch(seconds).map(|datetime| datetime.time()),
+    }
+);
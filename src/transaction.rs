@@ -0,0 +1,63 @@
+//! The transaction state of a connection
+use super::{Database, Result};
+use sqlite3_sys as ffi;
+use std::ffi::CString;
+
+/// The transaction state of a schema, see `Database::txn_state`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TxnState {
+    /// no transaction is open
+    None,
+    /// a read transaction is open
+    Read,
+    /// a write transaction is open
+    Write,
+}
+
+impl Database {
+    /// whether the connection is in autocommit mode, that is no `begin` is pending
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// # let database = Database::open(":memory:")?;
+    /// assert!(database.is_autocommit());
+    /// database.execute("begin", ())?;
+    /// assert!(!database.is_autocommit());
+    /// database.execute("commit", ())?;
+    /// assert!(database.is_autocommit());
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn is_autocommit(&self) -> bool {
+        unsafe { ffi::sqlite3_get_autocommit(self.db) != 0 }
+    }
+
+    /// the transaction state of `schema`, like `main` or an attached alias,
+    /// or the most advanced state of all schemas if `schema` is `None`
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// use rsqlite::transaction::TxnState;
+    /// # let database = Database::open(":memory:")?;
+    /// database.execute("create table user (name text)", ())?;
+    /// database.execute("begin", ())?;
+    /// assert!(database.txn_state(None)? == TxnState::None);
+    /// database.execute("insert into user values ('amin')", ())?;
+    /// assert!(database.txn_state(Some("main"))? == TxnState::Write);
+    /// assert!(database.txn_state(Some("temp"))? == TxnState::None);
+    /// # database.execute("commit", ())?;
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn txn_state(&self, schema: Option<&str>) -> Result<TxnState> {
+        let schema = schema.map(CString::new).transpose()?;
+        let schema = schema
+            .as_ref()
+            .map_or(std::ptr::null(), |schema| schema.as_ptr());
+        match unsafe { ffi::sqlite3_txn_state(self.db, schema) } {
+            ffi::SQLITE_TXN_NONE => Ok(TxnState::None),
+            ffi::SQLITE_TXN_READ => Ok(TxnState::Read),
+            ffi::SQLITE_TXN_WRITE => Ok(TxnState::Write),
+            // -1 for an unknown schema
+            _ => Err(ffi::SQLITE_NOTFOUND.into()),
+        }
+    }
+}
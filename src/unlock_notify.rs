@@ -0,0 +1,90 @@
+use super::{Database, Result, Statement};
+use libc::{c_int, c_void};
+use sqlite3_sys as ffi;
+use std::sync::{Condvar, Mutex};
+
+/// signaled by sqlite once the connection holding the lock is done
+struct Unlock {
+    fired: Mutex<bool>,
+    condvar: Condvar,
+}
+
+impl Database {
+    /// blocks until the shared-cache connection that made the last statement
+    /// of this one fail with `SQLITE_LOCKED` commits or rolls back
+    ///
+    /// Returns `Err(Locked)` right away if waiting would deadlock,
+    /// and immediately if nothing is blocking this connection.
+    pub fn wait_for_unlock(&self) -> Result<()> {
+        let unlock = Unlock {
+            fired: Mutex::new(false),
+            condvar: Condvar::new(),
+        };
+        let data = &unlock as *const Unlock as *mut c_void;
+        let ecode = unsafe { ffi::sqlite3_unlock_notify(self.db, Some(call_unlock), data) };
+        if ecode != ffi::SQLITE_OK {
+            return Err(self.error(ecode));
+        }
+        let mut fired = unlock.fired.lock().unwrap_or_else(|e| e.into_inner());
+        while !*fired {
+            fired = unlock
+                .condvar
+                .wait(fired)
+                .unwrap_or_else(|e| e.into_inner());
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Statement<'a> {
+    /// steps the statement, returning whether it produced a row to read with `Collectable`,
+    /// waits with `wait_for_unlock` instead of failing when a shared-cache table is locked
+    ///
+    /// The statement is reset before retrying, so a locked statement
+    /// starts over from its first row.
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// let uri = "file:rsqlite-unlock-notify?mode=memory&cache=shared";
+    /// let flags = ffi::SQLITE_OPEN_READWRITE | ffi::SQLITE_OPEN_CREATE | ffi::SQLITE_OPEN_URI;
+    /// let writer = Database::open_with_flags(uri, flags)?;
+    /// writer.execute("create table user (name text)", ())?;
+    /// writer.execute("begin", ())?;
+    /// writer.execute("insert into user values ('amin')", ())?;
+    ///
+    /// let reader = std::thread::spawn(move || -> Result<i32> {
+    ///     let database = Database::open_with_flags(uri, flags)?;
+    ///     let mut statement = database.prepare("select count(*) from user")?;
+    ///     assert!(statement.step_blocking()?);
+    ///     i32::try_collect(&statement, &mut 0)
+    /// });
+    /// std::thread::sleep(std::time::Duration::from_millis(50));
+    /// writer.execute("commit", ())?;
+    /// assert!(reader.join().unwrap()? == 1);
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn step_blocking(&mut self) -> Result<bool> {
+        loop {
+            match unsafe { ffi::sqlite3_step(self.stmt) } {
+                ffi::SQLITE_ROW => return Ok(true),
+                ffi::SQLITE_DONE => return Ok(false),
+                ffi::SQLITE_LOCKED
+                    if unsafe { ffi::sqlite3_extended_errcode(self.database.db) }
+                        == ffi::SQLITE_LOCKED_SHAREDCACHE =>
+                {
+                    self.database.wait_for_unlock()?;
+                    unsafe { ffi::sqlite3_reset(self.stmt) };
+                }
+                other => return Err(self.database.error(other)),
+            }
+        }
+    }
+}
+
+unsafe extern "C" fn call_unlock(args: *mut *mut c_void, count: c_int) {
+    for &data in std::slice::from_raw_parts(args, count as usize) {
+        let unlock = &*(data as *const Unlock);
+        *unlock.fired.lock().unwrap_or_else(|e| e.into_inner()) = true;
+        unlock.condvar.notify_one();
+    }
+}
@@ -0,0 +1,54 @@
+//! Opening database files that come from untrusted sources
+use super::config::DbConfig;
+use super::limit::Limit;
+use super::{Database, Result};
+use sqlite3_sys as ffi;
+
+/// conservative limits, as recommended by https://sqlite.org/security.html
+const UNTRUSTED_LIMITS: [(Limit, i32); 11] = [
+    (Limit::Length, 1_000_000),
+    (Limit::SqlLength, 100_000),
+    (Limit::Column, 100),
+    (Limit::ExprDepth, 10),
+    (Limit::CompoundSelect, 3),
+    (Limit::VdbeOp, 25_000),
+    (Limit::FunctionArg, 8),
+    (Limit::Attached, 0),
+    (Limit::LikePatternLength, 50),
+    (Limit::VariableNumber, 10),
+    (Limit::TriggerDepth, 10),
+];
+
+impl Database {
+    /// open a database file received from an untrusted source.
+    ///
+    /// The file is opened read-only with defensive mode on, `trusted_schema` off,
+    /// extension loading disabled and conservative `sqlite3_limit` values.
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// # let path = std::env::temp_dir().join("rsqlite-open-untrusted.db");
+    /// # let path = path.to_str().unwrap();
+    /// # let _ = std::fs::remove_file(path);
+    /// # Database::open(path)?.execute("create table user (name text)", ())?;
+    /// let database = Database::open_untrusted(path)?;
+    /// let count: i32 = database.collect("select count(*) from user", ())?;
+    /// # assert!(count == 0);
+    ///
+    /// // writes are rejected
+    /// assert!(database.execute("insert into user values ('amin')", ()).is_err());
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn open_untrusted(path: &str) -> Result<Database> {
+        let database = Database::open_with_flags(path, ffi::SQLITE_OPEN_READONLY)?;
+
+        database.set_db_config(DbConfig::Defensive, true)?;
+        database.set_db_config(DbConfig::TrustedSchema, false)?;
+        database.set_db_config(DbConfig::EnableLoadExtension, false)?;
+        for &(limit, value) in UNTRUSTED_LIMITS.iter() {
+            database.limit(limit, Some(value));
+        }
+
+        Ok(database)
+    }
+}
@@ -0,0 +1,71 @@
+//! `Bindable` and `Collectable` for `uuid::Uuid`
+//!
+//! This module is only available with the `uuid` feature.
+//! A `Uuid` binds as a 16-byte BLOB, bind `uuid.hyphenated()` to store TEXT instead.
+//! Collecting accepts both forms.
+//!
+//! ```
+//! # use rsqlite::*;
+//! use uuid::Uuid;
+//! # let database = Database::open(":memory:")?;
+//! database.execute("create table user(id blob primary key, external_id text)", ())?;
+//! let id = Uuid::parse_str("67e55044-10b1-426f-9247-bb680e5fe0c8").unwrap();
+//! database.execute("insert into user values (?, ?)", (id, id.hyphenated()))?;
+//!
+//! let (length, external_id): (i32, String) =
+//!     database.collect("select length(id), external_id from user", ())?;
+//! assert!((length, external_id.as_str()) == (16, "67e55044-10b1-426f-9247-bb680e5fe0c8"));
+//! let ids: (Uuid, Uuid) = database.collect("select id, external_id from user", ())?;
+//! assert!(ids == (id, id));
+//! # Ok::<(), RsqliteError>(())
+//! ```
+use super::{Bindable, Collectable, Result, RsqliteError, Statement};
+use libc::c_int;
+use sqlite3_sys as ffi;
+use uuid::fmt::Hyphenated;
+use uuid::Uuid;
+
+impl Bindable for Uuid {
+    fn bind(&self, statement: &mut Statement, index: &mut c_int) -> Result<()> {
+        (&self.as_bytes()[..]).bind(statement, index)
+    }
+}
+impl Bindable for Hyphenated {
+    fn bind(&self, statement: &mut Statement, index: &mut c_int) -> Result<()> {
+        self.to_string().bind(statement, index)
+    }
+}
+
+/// `collect` falls back to the nil UUID on invalid input
+impl Collectable for Uuid {
+    fn collect(statement: &Statement, column: &mut c_int) -> Self {
+        Self::try_collect(statement, column).unwrap_or_default()
+    }
+    fn try_collect(statement: &Statement, column: &mut c_int) -> Result<Self> {
+        let sqlite_type = unsafe { ffi::sqlite3_column_type(statement.stmt, *column) };
+        let uuid = match sqlite_type {
+            ffi::SQLITE_BLOB => {
+                let bytes = <Box<[u8]>>::try_collect(statement, column)?;
+                Uuid::from_slice(&bytes).ok()
+            }
+            ffi::SQLITE_TEXT => {
+                let text = String::try_collect(statement, column)?;
+                Uuid::parse_str(text.trim()).ok()
+            }
+            _ => {
+                *column += 1;
+                None
+            }
+        };
+        uuid.ok_or_else(|| {
+            RsqliteError::new(
+                ffi::SQLITE_MISMATCH,
+                ffi::SQLITE_MISMATCH,
+                "value is not a valid UUID".to_owned(),
+            )
+        })
+    }
+    fn columns_needed() -> c_int {
+        1
+    }
+}
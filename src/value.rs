@@ -0,0 +1,196 @@
+//! A dynamically typed SQL value, for queries whose types are only known at runtime
+use super::{Bindable, Collectable, Result, Statement};
+use libc::c_int;
+use sqlite3_sys as ffi;
+
+/// Any value sqlite can store, one variant per storage class
+///
+/// ```
+/// # use rsqlite::*;
+/// # let database = Database::open(":memory:")?;
+/// database.execute("create table anything (x)", ())?;
+/// for value in [Value::Null, Value::Integer(1), Value::Real(1.5), Value::from("one"), Value::from(vec![1u8])] {
+///     database.execute("insert into anything values (?)", value)?;
+/// }
+///
+/// let values: Vec<Value> = database.collect_vec("select x from anything", ())?;
+/// assert!(values[0] == Value::Null && values[2] == Value::Real(1.5));
+/// assert!(values[3] == Value::Text("one".to_owned()) && values[4] == Value::Blob(vec![1]));
+///
+/// let (kind, text): (String, Value) = database.collect("select typeof(?), ?", (Value::Integer(7), "text"))?;
+/// assert!(kind == "integer" && text.as_str() == Some("text"));
+/// # Ok::<(), RsqliteError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Integer(i64),
+    Real(f64),
+    Text(String),
+    Blob(Vec<u8>),
+}
+
+impl Value {
+    /// the `ffi::SQLITE_*` datatype of the value, like `ffi::SQLITE_INTEGER`
+    pub fn data_type(&self) -> c_int {
+        match self {
+            Value::Null => ffi::SQLITE_NULL,
+            Value::Integer(_) => ffi::SQLITE_INTEGER,
+            Value::Real(_) => ffi::SQLITE_FLOAT,
+            Value::Text(_) => ffi::SQLITE_TEXT,
+            Value::Blob(_) => ffi::SQLITE_BLOB,
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        *self == Value::Null
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Value::Integer(integer) => Some(*integer),
+            _ => None,
+        }
+    }
+
+    /// the value of a `Real`, or of an `Integer` converted to f64
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Integer(integer) => Some(*integer as f64),
+            Value::Real(real) => Some(*real),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::Text(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    pub fn as_blob(&self) -> Option<&[u8]> {
+        match self {
+            Value::Blob(blob) => Some(blob),
+            _ => None,
+        }
+    }
+}
+
+impl Bindable for Value {
+    fn bind(&self, statement: &mut Statement, index: &mut c_int) -> Result<()> {
+        match self {
+            Value::Null => None::<i64>.bind(statement, index),
+            Value::Integer(integer) => integer.bind(statement, index),
+            Value::Real(real) => real.bind(statement, index),
+            Value::Text(text) => text.bind(statement, index),
+            Value::Blob(blob) => blob.bind(statement, index),
+        }
+    }
+}
+
+/// binds each value to consecutive parameters
+///
+/// ```
+/// # use rsqlite::*;
+/// # let database = Database::open(":memory:")?;
+/// let mut params = vec![Value::from("amin")];
+/// let mut conditions = vec!["name = ?"];
+/// let min_age: Option<i64> = Some(18);
+/// if let Some(age) = min_age {
+///     conditions.push("age >= ?");
+///     params.push(Value::from(age));
+/// }
+/// let sql = format!("select count(*) from (select 'amin' as name, 29 as age) where {}", conditions.join(" and "));
+/// let count: i32 = database.collect(&sql, &params)?;
+/// assert!(count == 1);
+/// # Ok::<(), RsqliteError>(())
+/// ```
+impl Bindable for &[Value] {
+    fn bind(&self, statement: &mut Statement, index: &mut c_int) -> Result<()> {
+        for value in self.iter() {
+            value.bind(statement, index)?;
+        }
+        Ok(())
+    }
+}
+impl Bindable for Vec<Value> {
+    fn bind(&self, statement: &mut Statement, index: &mut c_int) -> Result<()> {
+        self.as_slice().bind(statement, index)
+    }
+}
+
+/// `collect` replaces invalid UTF-8 in text with `U+FFFD`
+impl Collectable for Value {
+    fn collect(statement: &Statement, column: &mut c_int) -> Self {
+        match unsafe { ffi::sqlite3_column_type(statement.stmt, *column) } {
+            ffi::SQLITE_TEXT => Value::Text(String::collect(statement, column)),
+            _ => Self::try_collect(statement, column).unwrap_or(Value::Null),
+        }
+    }
+    fn try_collect(statement: &Statement, column: &mut c_int) -> Result<Self> {
+        let value = match unsafe { ffi::sqlite3_column_type(statement.stmt, *column) } {
+            ffi::SQLITE_INTEGER => Value::Integer(i64::try_collect(statement, column)?),
+            ffi::SQLITE_FLOAT => Value::Real(f64::try_collect(statement, column)?),
+            ffi::SQLITE_TEXT => Value::Text(String::try_collect(statement, column)?),
+            ffi::SQLITE_BLOB => Value::Blob(<Box<[u8]>>::try_collect(statement, column)?.into()),
+            _ => {
+                *column += 1;
+                Value::Null
+            }
+        };
+        Ok(value)
+    }
+    fn columns_needed() -> c_int {
+        1
+    }
+}
+
+impl From<i32> for Value {
+    fn from(integer: i32) -> Value {
+        Value::Integer(integer as i64)
+    }
+}
+impl From<i64> for Value {
+    fn from(integer: i64) -> Value {
+        Value::Integer(integer)
+    }
+}
+impl From<bool> for Value {
+    fn from(boolean: bool) -> Value {
+        Value::Integer(boolean as i64)
+    }
+}
+impl From<f64> for Value {
+    fn from(real: f64) -> Value {
+        Value::Real(real)
+    }
+}
+impl From<&str> for Value {
+    fn from(text: &str) -> Value {
+        Value::Text(text.to_owned())
+    }
+}
+impl From<String> for Value {
+    fn from(text: String) -> Value {
+        Value::Text(text)
+    }
+}
+impl From<&[u8]> for Value {
+    fn from(blob: &[u8]) -> Value {
+        Value::Blob(blob.to_vec())
+    }
+}
+impl From<Vec<u8>> for Value {
+    fn from(blob: Vec<u8>) -> Value {
+        Value::Blob(blob)
+    }
+}
+impl<T> From<Option<T>> for Value
+where
+    T: Into<Value>,
+{
+    fn from(value: Option<T>) -> Value {
+        value.map_or(Value::Null, Into::into)
+    }
+}
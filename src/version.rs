@@ -0,0 +1,80 @@
+//! The version and compile options of the linked SQLite library
+use libc::{c_char, c_int};
+use sqlite3_sys as ffi;
+use std::ffi::{CStr, CString};
+
+/// An iterator over the compile options of SQLite, see `compile_options`
+pub struct CompileOptions {
+    index: c_int,
+}
+
+/// the version of the linked SQLite library, like `"3.40.1"`
+///
+/// ```
+/// # use rsqlite::*;
+/// let version = rsqlite::version();
+/// assert!(version.starts_with("3."));
+/// assert!(rsqlite::version_number() >= 3_000_000);
+/// # let database = Database::open(":memory:")?;
+/// assert!(database.collect::<String>("select sqlite_version()", ())? == version);
+/// # Ok::<(), RsqliteError>(())
+/// ```
+pub fn version() -> &'static str {
+    unsafe { static_str(ffi::sqlite3_libversion()) }
+}
+
+/// the version as `major * 1_000_000 + minor * 1_000 + patch`, like `3040001`
+pub fn version_number() -> i32 {
+    unsafe { ffi::sqlite3_libversion_number() }
+}
+
+/// the date, time and hash of the SQLite check-in the library was built from
+pub fn source_id() -> &'static str {
+    unsafe { static_str(ffi::sqlite3_sourceid()) }
+}
+
+/// the `SQLITE_THREADSAFE` compile option: 0 single-thread, 1 serialized, 2 multi-thread
+pub fn threadsafe() -> i32 {
+    unsafe { ffi::sqlite3_threadsafe() }
+}
+
+/// the options SQLite was compiled with, without the `SQLITE_` prefix
+///
+/// ```
+/// # use rsqlite::*;
+/// let options: Vec<&str> = rsqlite::compile_options().collect();
+/// assert!(options.iter().any(|option| option.starts_with("THREADSAFE=")));
+/// assert!(rsqlite::compile_option_used("THREADSAFE"));
+/// assert!(!rsqlite::compile_option_used("NOT_AN_OPTION"));
+/// ```
+pub fn compile_options() -> CompileOptions {
+    CompileOptions { index: 0 }
+}
+
+/// whether SQLite was compiled with `option`, with or without the `SQLITE_` prefix,
+/// like `ENABLE_FTS5` or `THREADSAFE`
+pub fn compile_option_used(option: &str) -> bool {
+    match CString::new(option) {
+        Ok(option) => unsafe { ffi::sqlite3_compileoption_used(option.as_ptr()) != 0 },
+        Err(_) => false,
+    }
+}
+
+impl Iterator for CompileOptions {
+    type Item = &'static str;
+
+    fn next(&mut self) -> Option<&'static str> {
+        let option = unsafe { ffi::sqlite3_compileoption_get(self.index) };
+        if option.is_null() {
+            return None;
+        }
+        self.index += 1;
+        Some(unsafe { static_str(option) })
+    }
+}
+
+/// # Safety
+/// `text` must be a static, NUL terminated string, as the ones returned by these functions
+unsafe fn static_str(text: *const c_char) -> &'static str {
+    CStr::from_ptr(text).to_str().unwrap_or_default()
+}
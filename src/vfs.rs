@@ -0,0 +1,512 @@
+//! Custom VFS implementations in Rust
+//!
+//! Implement `Vfs` for the file system and `VfsFile` for its files, register it with
+//! `register()` and open databases with `Database::open_with_vfs`.
+//! Shared memory is not supported, use `pragma locking_mode = exclusive` for WAL databases.
+//! Randomness, sleeping and the current time are delegated to the default VFS.
+//! A panic in a `Vfs` or `VfsFile` method is caught and reported to SQLite as `SQLITE_IOERR`.
+//!
+//! ```
+//! # use rsqlite::*;
+//! use rsqlite::vfs::{self, Vfs, VfsFile};
+//! use std::collections::HashMap;
+//! use std::sync::{Arc, Mutex};
+//!
+//! type Data = Arc<Mutex<Vec<u8>>>;
+//!
+//! #[derive(Default)]
+//! struct MemoryVfs(Mutex<HashMap<String, Data>>);
+//! struct MemoryFile(Data);
+//!
+//! impl Vfs for MemoryVfs {
+//!     type File = MemoryFile;
+//!     fn open(&self, path: Option<&str>, _flags: i32) -> Result<MemoryFile> {
+//!         let mut files = self.0.lock().unwrap();
+//!         let data = match path {
+//!             Some(path) => files.entry(path.to_owned()).or_default().clone(),
+//!             None => Data::default(),
+//!         };
+//!         Ok(MemoryFile(data))
+//!     }
+//!     fn delete(&self, path: &str, _sync_dir: bool) -> Result<()> {
+//!         self.0.lock().unwrap().remove(path);
+//!         Ok(())
+//!     }
+//!     fn exists(&self, path: &str) -> Result<bool> {
+//!         Ok(self.0.lock().unwrap().contains_key(path))
+//!     }
+//! }
+//!
+//! impl VfsFile for MemoryFile {
+//!     fn read(&mut self, buf: &mut [u8], offset: i64) -> Result<usize> {
+//!         let data = self.0.lock().unwrap();
+//!         let start = (offset as usize).min(data.len());
+//!         let end = (start + buf.len()).min(data.len());
+//!         buf[..end - start].copy_from_slice(&data[start..end]);
+//!         Ok(end - start)
+//!     }
+//!     fn write(&mut self, buf: &[u8], offset: i64) -> Result<()> {
+//!         let mut data = self.0.lock().unwrap();
+//!         let end = offset as usize + buf.len();
+//!         if data.len() < end {
+//!             data.resize(end, 0);
+//!         }
+//!         data[offset as usize..end].copy_from_slice(buf);
+//!         Ok(())
+//!     }
+//!     fn truncate(&mut self, size: i64) -> Result<()> {
+//!         self.0.lock().unwrap().truncate(size as usize);
+//!         Ok(())
+//!     }
+//!     fn sync(&mut self, _flags: i32) -> Result<()> {
+//!         Ok(())
+//!     }
+//!     fn file_size(&self) -> Result<i64> {
+//!         Ok(self.0.lock().unwrap().len() as i64)
+//!     }
+//! }
+//!
+//! vfs::register("memory", MemoryVfs::default(), false)?;
+//! let database = Database::open_with_vfs("test.db", "memory")?;
+//! database.execute("create table t(x integer)", ())?;
+//! database.execute("insert into t values (1), (2)", ())?;
+//! let sum: i32 = database.collect("select sum(x) from t", ())?;
+//! assert!(sum == 3);
+//! # Ok::<(), RsqliteError>(())
+//! ```
+use super::{Database, Result, RsqliteError};
+use core::ptr;
+use libc::{c_char, c_double, c_int, c_void};
+use sqlite3_sys as ffi;
+use std::ffi::{CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+/// A file system
+pub trait Vfs: Send + Sync + Sized + 'static {
+    type File: VfsFile;
+
+    /// opens the file at `path`, or a temporary file when `path` is `None`,
+    /// `flags` is a combination of `ffi::SQLITE_OPEN_*`
+    fn open(&self, path: Option<&str>, flags: c_int) -> Result<Self::File>;
+
+    /// deletes the file at `path`
+    fn delete(&self, path: &str, sync_dir: bool) -> Result<()>;
+
+    /// true if the file at `path` exists
+    fn exists(&self, path: &str) -> Result<bool>;
+
+    /// true if the file at `path` can be read and written
+    fn is_writable(&self, path: &str) -> Result<bool> {
+        self.exists(path)
+    }
+
+    /// the canonical form of `path`
+    fn full_pathname(&self, path: &str) -> Result<String> {
+        Ok(path.to_owned())
+    }
+}
+
+/// A file opened by a `Vfs`
+pub trait VfsFile: Send + Sized {
+    /// reads at `offset` into `buf`, returning the number of bytes read,
+    /// which is less than `buf.len()` only at the end of the file
+    fn read(&mut self, buf: &mut [u8], offset: i64) -> Result<usize>;
+
+    /// writes `buf` at `offset`, growing the file if needed
+    fn write(&mut self, buf: &[u8], offset: i64) -> Result<()>;
+
+    /// truncates the file to `size` bytes
+    fn truncate(&mut self, size: i64) -> Result<()>;
+
+    /// flushes the file to storage, `flags` is a combination of `ffi::SQLITE_SYNC_*`
+    fn sync(&mut self, flags: c_int) -> Result<()>;
+
+    /// the size of the file in bytes
+    fn file_size(&self) -> Result<i64>;
+
+    /// raises the lock to `level`, one of `ffi::SQLITE_LOCK_*`
+    fn lock(&mut self, _level: c_int) -> Result<()> {
+        Ok(())
+    }
+
+    /// lowers the lock to `level`, one of `ffi::SQLITE_LOCK_*`
+    fn unlock(&mut self, _level: c_int) -> Result<()> {
+        Ok(())
+    }
+
+    /// true if any connection holds a RESERVED or higher lock on the file
+    fn check_reserved_lock(&self) -> Result<bool> {
+        Ok(false)
+    }
+
+    /// the sector size of the underlying storage, 0 lets sqlite pick the default
+    fn sector_size(&self) -> c_int {
+        0
+    }
+
+    /// a combination of `ffi::SQLITE_IOCAP_*`
+    fn device_characteristics(&self) -> c_int {
+        0
+    }
+}
+
+struct VfsData<V: Vfs> {
+    vfs: V,
+    name: CString,
+    io_methods: ffi::sqlite3_io_methods,
+    default: *mut ffi::sqlite3_vfs,
+}
+
+#[repr(C)]
+struct FileData<F> {
+    base: ffi::sqlite3_file,
+    file: F,
+}
+
+/// registers `vfs` under `name`, making it the default VFS if `make_default` is set.
+///
+/// A registered VFS lives until the process exits, registering the same name again
+/// replaces it for new connections.
+/// SQLite only aligns the memory of an open file to 8 bytes,
+/// so a `VfsFile` that needs a larger alignment is rejected with `SQLITE_MISUSE`.
+pub fn register<V: Vfs>(name: &str, vfs: V, make_default: bool) -> Result<()> {
+    if std::mem::align_of::<FileData<V::File>>() > 8 {
+        return Err(RsqliteError::new(
+            ffi::SQLITE_MISUSE,
+            ffi::SQLITE_MISUSE,
+            "VfsFile types aligned to more than 8 bytes are not supported".to_owned(),
+        ));
+    }
+    let default = unsafe { ffi::sqlite3_vfs_find(ptr::null()) };
+    if default.is_null() {
+        return Err(ffi::SQLITE_ERROR.into());
+    }
+
+    let mut io_methods: ffi::sqlite3_io_methods = unsafe { std::mem::zeroed() };
+    io_methods.iVersion = 1;
+    io_methods.xClose = Some(x_close::<V::File>);
+    io_methods.xRead = Some(x_read::<V::File>);
+    io_methods.xWrite = Some(x_write::<V::File>);
+    io_methods.xTruncate = Some(x_truncate::<V::File>);
+    io_methods.xSync = Some(x_sync::<V::File>);
+    io_methods.xFileSize = Some(x_file_size::<V::File>);
+    io_methods.xLock = Some(x_lock::<V::File>);
+    io_methods.xUnlock = Some(x_unlock::<V::File>);
+    io_methods.xCheckReservedLock = Some(x_check_reserved_lock::<V::File>);
+    io_methods.xFileControl = Some(x_file_control);
+    io_methods.xSectorSize = Some(x_sector_size::<V::File>);
+    io_methods.xDeviceCharacteristics = Some(x_device_characteristics::<V::File>);
+
+    let data = Box::leak(Box::new(VfsData {
+        vfs,
+        name: CString::new(name)?,
+        io_methods,
+        default,
+    }));
+
+    let mut sqlite_vfs: ffi::sqlite3_vfs = unsafe { std::mem::zeroed() };
+    sqlite_vfs.iVersion = 1;
+    sqlite_vfs.szOsFile = std::mem::size_of::<FileData<V::File>>() as c_int;
+    sqlite_vfs.mxPathname = unsafe { (*default).mxPathname };
+    sqlite_vfs.zName = data.name.as_ptr();
+    sqlite_vfs.pAppData = data as *mut VfsData<V> as *mut c_void;
+    sqlite_vfs.xOpen = Some(x_open::<V>);
+    sqlite_vfs.xDelete = Some(x_delete::<V>);
+    sqlite_vfs.xAccess = Some(x_access::<V>);
+    sqlite_vfs.xFullPathname = Some(x_full_pathname::<V>);
+    sqlite_vfs.xRandomness = Some(x_randomness::<V>);
+    sqlite_vfs.xSleep = Some(x_sleep::<V>);
+    sqlite_vfs.xCurrentTime = Some(x_current_time::<V>);
+    sqlite_vfs.xGetLastError = Some(x_get_last_error);
+    let sqlite_vfs = Box::leak(Box::new(sqlite_vfs));
+
+    let ecode = unsafe { ffi::sqlite3_vfs_register(sqlite_vfs, make_default as c_int) };
+    match ecode {
+        ffi::SQLITE_OK => Ok(()),
+        other => Err(other.into()),
+    }
+}
+
+impl Database {
+    /// open a database through the VFS registered as `vfs`,
+    /// see `vfs::register()`
+    pub fn open_with_vfs(path: &str, vfs: &str) -> Result<Database> {
+        let flags = ffi::SQLITE_OPEN_READWRITE | ffi::SQLITE_OPEN_CREATE;
+        Database::open_v2(path, flags, Some(vfs))
+    }
+}
+
+fn error_code(error: RsqliteError, default: c_int) -> c_int {
+    error.extended_code().unwrap_or(default)
+}
+
+/// runs a callback of the VFS, a panic becomes `SQLITE_IOERR` instead of unwinding into sqlite
+fn guard(callback: impl FnOnce() -> c_int) -> c_int {
+    catch_unwind(AssertUnwindSafe(callback)).unwrap_or(ffi::SQLITE_IOERR)
+}
+
+unsafe fn vfs_data<'a, V: Vfs>(vfs: *mut ffi::sqlite3_vfs) -> &'a VfsData<V> {
+    &*((*vfs).pAppData as *const VfsData<V>)
+}
+
+unsafe fn file_data<'a, F>(file: *mut ffi::sqlite3_file) -> &'a mut FileData<F> {
+    &mut *(file as *mut FileData<F>)
+}
+
+unsafe fn path<'a>(path: *const c_char) -> Option<&'a str> {
+    match path.is_null() {
+        true => None,
+        false => CStr::from_ptr(path).to_str().ok(),
+    }
+}
+
+unsafe extern "C" fn x_open<V: Vfs>(
+    vfs: *mut ffi::sqlite3_vfs,
+    name: *const c_char,
+    file: *mut ffi::sqlite3_file,
+    flags: c_int,
+    out_flags: *mut c_int,
+) -> c_int {
+    let data = vfs_data::<V>(vfs);
+    // sqlite does not call xClose when xOpen fails, as long as pMethods is null
+    (*file).pMethods = ptr::null();
+    if !name.is_null() && path(name).is_none() {
+        return ffi::SQLITE_CANTOPEN;
+    }
+    guard(|| match data.vfs.open(path(name), flags) {
+        Ok(opened) => {
+            ptr::write(
+                file as *mut FileData<V::File>,
+                FileData {
+                    base: ffi::sqlite3_file {
+                        pMethods: &data.io_methods,
+                    },
+                    file: opened,
+                },
+            );
+            if !out_flags.is_null() {
+                *out_flags = flags;
+            }
+            ffi::SQLITE_OK
+        }
+        Err(error) => error_code(error, ffi::SQLITE_CANTOPEN),
+    })
+}
+
+unsafe extern "C" fn x_delete<V: Vfs>(
+    vfs: *mut ffi::sqlite3_vfs,
+    name: *const c_char,
+    sync_dir: c_int,
+) -> c_int {
+    let data = vfs_data::<V>(vfs);
+    let Some(name) = path(name) else {
+        return ffi::SQLITE_IOERR_DELETE;
+    };
+    guard(|| match data.vfs.delete(name, sync_dir != 0) {
+        Ok(()) => ffi::SQLITE_OK,
+        Err(error) => error_code(error, ffi::SQLITE_IOERR_DELETE),
+    })
+}
+
+unsafe extern "C" fn x_access<V: Vfs>(
+    vfs: *mut ffi::sqlite3_vfs,
+    name: *const c_char,
+    flags: c_int,
+    out: *mut c_int,
+) -> c_int {
+    let data = vfs_data::<V>(vfs);
+    let Some(name) = path(name) else {
+        *out = 0;
+        return ffi::SQLITE_OK;
+    };
+    guard(|| {
+        let result = match flags {
+            ffi::SQLITE_ACCESS_READWRITE => data.vfs.is_writable(name),
+            _ => data.vfs.exists(name),
+        };
+        match result {
+            Ok(found) => {
+                *out = found as c_int;
+                ffi::SQLITE_OK
+            }
+            Err(error) => error_code(error, ffi::SQLITE_IOERR_ACCESS),
+        }
+    })
+}
+
+unsafe extern "C" fn x_full_pathname<V: Vfs>(
+    vfs: *mut ffi::sqlite3_vfs,
+    name: *const c_char,
+    size: c_int,
+    out: *mut c_char,
+) -> c_int {
+    let data = vfs_data::<V>(vfs);
+    let Some(name) = path(name) else {
+        return ffi::SQLITE_CANTOPEN;
+    };
+    guard(|| match data.vfs.full_pathname(name) {
+        Ok(full) if full.len() < size as usize && !full.contains('\0') => {
+            ptr::copy_nonoverlapping(full.as_ptr() as *const c_char, out, full.len());
+            *out.add(full.len()) = 0;
+            ffi::SQLITE_OK
+        }
+        Ok(_) => ffi::SQLITE_CANTOPEN,
+        Err(error) => error_code(error, ffi::SQLITE_CANTOPEN),
+    })
+}
+
+unsafe extern "C" fn x_randomness<V: Vfs>(
+    vfs: *mut ffi::sqlite3_vfs,
+    size: c_int,
+    out: *mut c_char,
+) -> c_int {
+    let default = vfs_data::<V>(vfs).default;
+    match (*default).xRandomness {
+        Some(randomness) => randomness(default, size, out),
+        None => 0,
+    }
+}
+
+unsafe extern "C" fn x_sleep<V: Vfs>(vfs: *mut ffi::sqlite3_vfs, microseconds: c_int) -> c_int {
+    let default = vfs_data::<V>(vfs).default;
+    match (*default).xSleep {
+        Some(sleep) => sleep(default, microseconds),
+        None => 0,
+    }
+}
+
+unsafe extern "C" fn x_current_time<V: Vfs>(
+    vfs: *mut ffi::sqlite3_vfs,
+    out: *mut c_double,
+) -> c_int {
+    let default = vfs_data::<V>(vfs).default;
+    match (*default).xCurrentTime {
+        Some(current_time) => current_time(default, out),
+        None => ffi::SQLITE_ERROR,
+    }
+}
+
+unsafe extern "C" fn x_get_last_error(
+    _vfs: *mut ffi::sqlite3_vfs,
+    _size: c_int,
+    _out: *mut c_char,
+) -> c_int {
+    0
+}
+
+unsafe extern "C" fn x_close<F: VfsFile>(file: *mut ffi::sqlite3_file) -> c_int {
+    guard(|| {
+        ptr::drop_in_place(file as *mut FileData<F>);
+        ffi::SQLITE_OK
+    })
+}
+
+unsafe extern "C" fn x_read<F: VfsFile>(
+    file: *mut ffi::sqlite3_file,
+    buf: *mut c_void,
+    amount: c_int,
+    offset: ffi::sqlite3_int64,
+) -> c_int {
+    let data = file_data::<F>(file);
+    let buf = std::slice::from_raw_parts_mut(buf as *mut u8, amount as usize);
+    guard(|| match data.file.read(buf, offset) {
+        Ok(read) if read >= buf.len() => ffi::SQLITE_OK,
+        Ok(read) => {
+            // sqlite expects the rest of the buffer to be zeroed on a short read
+            buf[read..].fill(0);
+            ffi::SQLITE_IOERR_SHORT_READ
+        }
+        Err(error) => error_code(error, ffi::SQLITE_IOERR_READ),
+    })
+}
+
+unsafe extern "C" fn x_write<F: VfsFile>(
+    file: *mut ffi::sqlite3_file,
+    buf: *const c_void,
+    amount: c_int,
+    offset: ffi::sqlite3_int64,
+) -> c_int {
+    let data = file_data::<F>(file);
+    let buf = std::slice::from_raw_parts(buf as *const u8, amount as usize);
+    guard(|| match data.file.write(buf, offset) {
+        Ok(()) => ffi::SQLITE_OK,
+        Err(error) => error_code(error, ffi::SQLITE_IOERR_WRITE),
+    })
+}
+
+unsafe extern "C" fn x_truncate<F: VfsFile>(
+    file: *mut ffi::sqlite3_file,
+    size: ffi::sqlite3_int64,
+) -> c_int {
+    guard(|| match file_data::<F>(file).file.truncate(size) {
+        Ok(()) => ffi::SQLITE_OK,
+        Err(error) => error_code(error, ffi::SQLITE_IOERR_TRUNCATE),
+    })
+}
+
+unsafe extern "C" fn x_sync<F: VfsFile>(file: *mut ffi::sqlite3_file, flags: c_int) -> c_int {
+    guard(|| match file_data::<F>(file).file.sync(flags) {
+        Ok(()) => ffi::SQLITE_OK,
+        Err(error) => error_code(error, ffi::SQLITE_IOERR_FSYNC),
+    })
+}
+
+unsafe extern "C" fn x_file_size<F: VfsFile>(
+    file: *mut ffi::sqlite3_file,
+    size: *mut ffi::sqlite3_int64,
+) -> c_int {
+    guard(|| match file_data::<F>(file).file.file_size() {
+        Ok(file_size) => {
+            *size = file_size;
+            ffi::SQLITE_OK
+        }
+        Err(error) => error_code(error, ffi::SQLITE_IOERR_FSTAT),
+    })
+}
+
+unsafe extern "C" fn x_lock<F: VfsFile>(file: *mut ffi::sqlite3_file, level: c_int) -> c_int {
+    guard(|| match file_data::<F>(file).file.lock(level) {
+        Ok(()) => ffi::SQLITE_OK,
+        Err(error) => error_code(error, ffi::SQLITE_IOERR_LOCK),
+    })
+}
+
+unsafe extern "C" fn x_unlock<F: VfsFile>(file: *mut ffi::sqlite3_file, level: c_int) -> c_int {
+    guard(|| match file_data::<F>(file).file.unlock(level) {
+        Ok(()) => ffi::SQLITE_OK,
+        Err(error) => error_code(error, ffi::SQLITE_IOERR_UNLOCK),
+    })
+}
+
+unsafe extern "C" fn x_check_reserved_lock<F: VfsFile>(
+    file: *mut ffi::sqlite3_file,
+    out: *mut c_int,
+) -> c_int {
+    guard(|| match file_data::<F>(file).file.check_reserved_lock() {
+        Ok(reserved) => {
+            *out = reserved as c_int;
+            ffi::SQLITE_OK
+        }
+        Err(error) => error_code(error, ffi::SQLITE_IOERR_CHECKRESERVEDLOCK),
+    })
+}
+
+unsafe extern "C" fn x_file_control(
+    _file: *mut ffi::sqlite3_file,
+    _op: c_int,
+    _arg: *mut c_void,
+) -> c_int {
+    ffi::SQLITE_NOTFOUND
+}
+
+unsafe extern "C" fn x_sector_size<F: VfsFile>(file: *mut ffi::sqlite3_file) -> c_int {
+    // a panic falls back to the default sector size
+    catch_unwind(AssertUnwindSafe(|| file_data::<F>(file).file.sector_size())).unwrap_or(0)
+}
+
+unsafe extern "C" fn x_device_characteristics<F: VfsFile>(file: *mut ffi::sqlite3_file) -> c_int {
+    catch_unwind(AssertUnwindSafe(|| {
+        file_data::<F>(file).file.device_characteristics()
+    }))
+    .unwrap_or(0)
+}
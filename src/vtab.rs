@@ -0,0 +1,752 @@
+//! Virtual tables implemented in Rust
+//!
+//! Implement `VTab` for the table and `VTabCursor` for its cursor, then register the
+//! module with `Database::create_module` and use it with `create virtual table ... using`.
+//! Tables created this way are read-only.
+use super::function::{FromValue, ToResult};
+use super::{Database, Result, RsqliteError};
+use libc::{c_char, c_int, c_void};
+use sqlite3_sys as ffi;
+use std::ffi::{CStr, CString};
+use std::marker::PhantomData;
+
+/// A virtual table
+pub trait VTab: Sized + Send {
+    /// data given to `create_module`, shared by every table using the module
+    type Aux: Send + 'static;
+    type Cursor: VTabCursor;
+
+    /// creates or connects to a table, returning a `create table` statement
+    /// that declares its columns (the table name in it is ignored).
+    ///
+    /// `args` are the module name, the database name, the table name and then
+    /// the arguments given in `create virtual table ... using module(arguments)`.
+    fn connect(aux: &Self::Aux, args: &[String]) -> Result<(String, Self)>;
+
+    /// picks a query plan, see https://sqlite.org/vtab.html#xbestindex
+    fn best_index(&self, info: &mut IndexInfo) -> Result<()>;
+
+    /// opens a new cursor over the table
+    fn open(&self) -> Result<Self::Cursor>;
+}
+
+/// A cursor over the rows of a virtual table
+pub trait VTabCursor: Sized {
+    /// starts a scan using the plan chosen by `best_index`,
+    /// `args` holds the values of the constraints given an `argv_index`
+    fn filter(&mut self, idx_num: i32, idx_str: Option<&str>, args: &Values) -> Result<()>;
+
+    /// advances to the next row
+    fn next(&mut self) -> Result<()>;
+
+    /// true when the cursor moved past the last row
+    fn eof(&self) -> bool;
+
+    /// sets the value of `column` of the current row on `context`
+    fn column(&self, context: &mut Context, column: i32) -> Result<()>;
+
+    /// the rowid of the current row
+    fn rowid(&self) -> Result<i64>;
+}
+
+/// A constraint on a column, as seen by `best_index`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndexConstraint {
+    /// the constrained column, -1 for the rowid
+    pub column: i32,
+    /// one of `ffi::SQLITE_INDEX_CONSTRAINT_*`
+    pub op: i32,
+    /// constraints that are not usable must not be used by the plan
+    pub usable: bool,
+}
+
+/// The query planner inputs and outputs of `best_index`
+pub struct IndexInfo<'a> {
+    info: &'a mut ffi::sqlite3_index_info,
+}
+
+/// The arguments of `VTabCursor::filter`, and of FTS5 auxiliary functions
+pub struct Values<'a> {
+    pub(crate) values: &'a [*mut ffi::sqlite3_value],
+}
+
+/// Where `VTabCursor::column` writes its result
+pub struct Context<'a> {
+    context: *mut ffi::sqlite3_context,
+    _marker: PhantomData<&'a mut ffi::sqlite3_context>,
+}
+
+impl<'a> IndexInfo<'a> {
+    /// the constraints of the WHERE clause
+    pub fn constraints(&self) -> Vec<IndexConstraint> {
+        (0..self.info.nConstraint as usize)
+            .map(|i| {
+                let constraint = unsafe { &*self.info.aConstraint.add(i) };
+                IndexConstraint {
+                    column: constraint.iColumn,
+                    op: constraint.op as i32,
+                    usable: constraint.usable != 0,
+                }
+            })
+            .collect()
+    }
+
+    /// the ORDER BY terms, as `(column, descending)`
+    pub fn order_by(&self) -> Vec<(i32, bool)> {
+        (0..self.info.nOrderBy as usize)
+            .map(|i| {
+                let order_by = unsafe { &*self.info.aOrderBy.add(i) };
+                (order_by.iColumn, order_by.desc != 0)
+            })
+            .collect()
+    }
+
+    /// passes the right-hand side of `constraint` to `filter` as `args[argv_index - 1]`,
+    /// `omit` tells sqlite it does not need to double check the constraint.
+    ///
+    /// Fails with `Err(Range)` if `constraint` is not an index of `constraints()`.
+    pub fn set_argv_index(&mut self, constraint: usize, argv_index: i32, omit: bool) -> Result<()> {
+        if constraint >= self.info.nConstraint as usize {
+            return Err(ffi::SQLITE_RANGE.into());
+        }
+        let usage = unsafe { &mut *self.info.aConstraintUsage.add(constraint) };
+        usage.argvIndex = argv_index;
+        usage.omit = omit as _;
+        Ok(())
+    }
+
+    /// a number passed to `filter` to identify the chosen plan
+    pub fn set_idx_num(&mut self, idx_num: i32) {
+        self.info.idxNum = idx_num;
+    }
+
+    /// a string passed to `filter` to identify the chosen plan
+    pub fn set_idx_str(&mut self, idx_str: &str) -> Result<()> {
+        let idx_str = CString::new(idx_str)?;
+        let copy =
+            unsafe { ffi::sqlite3_mprintf(b"%s\0".as_ptr() as *const c_char, idx_str.as_ptr()) };
+        if copy.is_null() {
+            return Err(ffi::SQLITE_NOMEM.into());
+        }
+        if self.info.needToFreeIdxStr != 0 {
+            unsafe { ffi::sqlite3_free(self.info.idxStr as *mut c_void) };
+        }
+        self.info.idxStr = copy;
+        self.info.needToFreeIdxStr = 1;
+        Ok(())
+    }
+
+    /// tells sqlite the rows come out already in the requested order
+    pub fn set_order_by_consumed(&mut self, consumed: bool) {
+        self.info.orderByConsumed = consumed as c_int;
+    }
+
+    /// the estimated cost of the plan, lower is better
+    pub fn set_estimated_cost(&mut self, cost: f64) {
+        self.info.estimatedCost = cost;
+    }
+
+    /// the estimated number of rows returned by the plan
+    pub fn set_estimated_rows(&mut self, rows: i64) {
+        self.info.estimatedRows = rows;
+    }
+}
+
+impl<'a> Values<'a> {
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// reads the value at `index`, `Err(Range)` if it is out of range
+    pub fn get<T: FromValue>(&self, index: usize) -> Result<T> {
+        self.raw(index).map(|value| unsafe { T::from_value(value) })
+    }
+
+    /// the underlying `sqlite3_value` at `index`, `Err(Range)` if it is out of range
+    pub fn raw(&self, index: usize) -> Result<*mut ffi::sqlite3_value> {
+        match self.values.get(index) {
+            Some(&value) => Ok(value),
+            None => Err(ffi::SQLITE_RANGE.into()),
+        }
+    }
+}
+
+impl<'a> Context<'a> {
+    /// sets the result
+    pub fn set<T: ToResult>(&mut self, value: T) {
+        unsafe { value.to_result(self.context) }
+    }
+}
+
+impl Database {
+    /// registers a virtual table module
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// use rsqlite::vtab::*;
+    ///
+    /// struct Numbers(Vec<i64>);
+    /// struct NumbersCursor(Vec<i64>, usize);
+    ///
+    /// impl VTab for Numbers {
+    ///     type Aux = Vec<i64>;
+    ///     type Cursor = NumbersCursor;
+    ///     fn connect(aux: &Vec<i64>, _args: &[String]) -> Result<(String, Self)> {
+    ///         Ok(("create table x(value integer)".to_owned(), Numbers(aux.clone())))
+    ///     }
+    ///     fn best_index(&self, info: &mut IndexInfo) -> Result<()> {
+    ///         info.set_estimated_cost(self.0.len() as f64);
+    ///         Ok(())
+    ///     }
+    ///     fn open(&self) -> Result<NumbersCursor> {
+    ///         Ok(NumbersCursor(self.0.clone(), 0))
+    ///     }
+    /// }
+    ///
+    /// impl VTabCursor for NumbersCursor {
+    ///     fn filter(&mut self, _: i32, _: Option<&str>, _: &Values) -> Result<()> {
+    ///         self.1 = 0;
+    ///         Ok(())
+    ///     }
+    ///     fn next(&mut self) -> Result<()> {
+    ///         self.1 += 1;
+    ///         Ok(())
+    ///     }
+    ///     fn eof(&self) -> bool {
+    ///         // a panic in a cursor method fails the statement
+    ///         assert!(self.0.len() < 100, "too many numbers");
+    ///         self.1 >= self.0.len()
+    ///     }
+    ///     fn column(&self, context: &mut Context, _column: i32) -> Result<()> {
+    ///         context.set(self.0[self.1]);
+    ///         Ok(())
+    ///     }
+    ///     fn rowid(&self) -> Result<i64> {
+    ///         Ok(self.1 as i64)
+    ///     }
+    /// }
+    ///
+    /// # let database = Database::open(":memory:")?;
+    /// database.create_module::<Numbers>("numbers", vec![1, 2, 3])?;
+    /// database.execute("create virtual table temp.nums using numbers", ())?;
+    /// let sum: i32 = database.collect("select sum(value) from nums where value > 1", ())?;
+    /// assert!(sum == 5);
+    ///
+    /// database.create_module::<Numbers>("many", (0..100).collect())?;
+    /// database.execute("create virtual table temp.many using many", ())?;
+    /// let error = database.collect::<i32>("select sum(value) from many", ()).unwrap_err();
+    /// assert!(error.to_string().contains("too many numbers"));
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn create_module<T: VTab>(&self, name: &str, aux: T::Aux) -> Result<()> {
+        self.create_module_v2::<T>(name, module::<T>(), aux)
+    }
+
+    fn create_module_v2<T: VTab>(
+        &self,
+        name: &str,
+        module: ffi::sqlite3_module,
+        aux: T::Aux,
+    ) -> Result<()> {
+        let name = CString::new(name)?;
+        let data = Box::into_raw(Box::new(ModuleData::<T> { module, aux }));
+        // sqlite calls drop_module_data when the module is replaced or the connection closes,
+        // including when registration fails
+        let ecode = unsafe {
+            ffi::sqlite3_create_module_v2(
+                self.db,
+                name.as_ptr(),
+                &(*data).module,
+                data as *mut c_void,
+                Some(drop_module_data::<T>),
+            )
+        };
+        match ecode {
+            ffi::SQLITE_OK => Ok(()),
+            other => Err(self.error(other)),
+        }
+    }
+}
+
+fn module<T: VTab>() -> ffi::sqlite3_module {
+    let mut module: ffi::sqlite3_module = unsafe { std::mem::zeroed() };
+    module.iVersion = 1;
+    module.xCreate = Some(x_connect::<T>);
+    module.xConnect = Some(x_connect::<T>);
+    module.xBestIndex = Some(x_best_index::<T>);
+    module.xDisconnect = Some(x_disconnect::<T>);
+    module.xDestroy = Some(x_disconnect::<T>);
+    module.xOpen = Some(x_open::<T>);
+    module.xClose = Some(x_close::<T>);
+    module.xFilter = Some(x_filter::<T>);
+    module.xNext = Some(x_next::<T>);
+    module.xEof = Some(x_eof::<T>);
+    module.xColumn = Some(x_column::<T>);
+    module.xRowid = Some(x_rowid::<T>);
+    module
+}
+
+struct ModuleData<T: VTab> {
+    module: ffi::sqlite3_module,
+    aux: T::Aux,
+}
+
+#[repr(C)]
+struct VTabData<T> {
+    base: ffi::sqlite3_vtab,
+    vtab: T,
+}
+
+#[repr(C)]
+struct CursorData<C> {
+    base: ffi::sqlite3_vtab_cursor,
+    cursor: C,
+    // set when `eof` panicked, which it can not report itself
+    failed: bool,
+}
+
+unsafe extern "C" fn drop_module_data<T: VTab>(data: *mut c_void) {
+    drop(Box::from_raw(data as *mut ModuleData<T>));
+}
+
+/// runs a user callback, turning a panic into an error instead of unwinding into sqlite
+pub(crate) fn catch_panic<T>(f: impl FnOnce() -> Result<T>) -> Result<T> {
+    match std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)) {
+        Ok(result) => result,
+        Err(panic) => {
+            let reason = match panic.downcast_ref::<&str>() {
+                Some(reason) => reason.to_string(),
+                None => panic.downcast_ref::<String>().cloned().unwrap_or_default(),
+            };
+            Err(RsqliteError::new(
+                ffi::SQLITE_ERROR,
+                ffi::SQLITE_ERROR,
+                format!("callback panicked: {}", reason),
+            ))
+        }
+    }
+}
+
+/// reports `error` through the error message of `vtab`
+unsafe fn vtab_error(vtab: *mut ffi::sqlite3_vtab, error: RsqliteError) -> c_int {
+    set_error_message(&mut (*vtab).zErrMsg, &error);
+    error.extended_code().unwrap_or(ffi::SQLITE_ERROR)
+}
+
+unsafe fn set_error_message(target: &mut *mut c_char, error: &RsqliteError) {
+    if !target.is_null() {
+        ffi::sqlite3_free(*target as *mut c_void);
+    }
+    let message = CString::new(error.to_string().replace('\0', "")).unwrap_or_default();
+    *target = ffi::sqlite3_mprintf(b"%s\0".as_ptr() as *const c_char, message.as_ptr());
+}
+
+unsafe extern "C" fn x_connect<T: VTab>(
+    db: *mut ffi::sqlite3,
+    aux: *mut c_void,
+    argc: c_int,
+    argv: *const *const c_char,
+    vtab: *mut *mut ffi::sqlite3_vtab,
+    err: *mut *mut c_char,
+) -> c_int {
+    let data = &*(aux as *const ModuleData<T>);
+    let args = (0..argc as usize)
+        .map(|i| CStr::from_ptr(*argv.add(i)).to_string_lossy().into_owned())
+        .collect::<Vec<_>>();
+
+    let result = catch_panic(|| T::connect(&data.aux, &args)).and_then(|(schema, table)| {
+        let schema = CString::new(schema)?;
+        match ffi::sqlite3_declare_vtab(db, schema.as_ptr()) {
+            ffi::SQLITE_OK => Ok(table),
+            other => Err(other.into()),
+        }
+    });
+    match result {
+        Ok(table) => {
+            let data = Box::new(VTabData {
+                base: std::mem::zeroed(),
+                vtab: table,
+            });
+            *vtab = Box::into_raw(data) as *mut ffi::sqlite3_vtab;
+            ffi::SQLITE_OK
+        }
+        Err(error) => {
+            set_error_message(&mut *err, &error);
+            error.extended_code().unwrap_or(ffi::SQLITE_ERROR)
+        }
+    }
+}
+
+unsafe extern "C" fn x_disconnect<T: VTab>(vtab: *mut ffi::sqlite3_vtab) -> c_int {
+    let data = Box::from_raw(vtab as *mut VTabData<T>);
+    if !data.base.zErrMsg.is_null() {
+        ffi::sqlite3_free(data.base.zErrMsg as *mut c_void);
+    }
+    let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| drop(data)));
+    ffi::SQLITE_OK
+}
+
+unsafe extern "C" fn x_best_index<T: VTab>(
+    vtab: *mut ffi::sqlite3_vtab,
+    info: *mut ffi::sqlite3_index_info,
+) -> c_int {
+    let data = &*(vtab as *const VTabData<T>);
+    let mut info = IndexInfo { info: &mut *info };
+    match catch_panic(|| data.vtab.best_index(&mut info)) {
+        Ok(()) => ffi::SQLITE_OK,
+        Err(error) => vtab_error(vtab, error),
+    }
+}
+
+unsafe extern "C" fn x_open<T: VTab>(
+    vtab: *mut ffi::sqlite3_vtab,
+    cursor: *mut *mut ffi::sqlite3_vtab_cursor,
+) -> c_int {
+    let data = &*(vtab as *const VTabData<T>);
+    match catch_panic(|| data.vtab.open()) {
+        Ok(opened) => {
+            let data = Box::new(CursorData {
+                base: std::mem::zeroed(),
+                cursor: opened,
+                failed: false,
+            });
+            *cursor = Box::into_raw(data) as *mut ffi::sqlite3_vtab_cursor;
+            ffi::SQLITE_OK
+        }
+        Err(error) => vtab_error(vtab, error),
+    }
+}
+
+unsafe extern "C" fn x_close<T: VTab>(cursor: *mut ffi::sqlite3_vtab_cursor) -> c_int {
+    let data = Box::from_raw(cursor as *mut CursorData<T::Cursor>);
+    let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| drop(data)));
+    ffi::SQLITE_OK
+}
+
+unsafe fn cursor_result(cursor: *mut ffi::sqlite3_vtab_cursor, result: Result<()>) -> c_int {
+    match result {
+        Ok(()) => ffi::SQLITE_OK,
+        Err(error) => vtab_error((*cursor).pVtab, error),
+    }
+}
+
+unsafe extern "C" fn x_filter<T: VTab>(
+    cursor: *mut ffi::sqlite3_vtab_cursor,
+    idx_num: c_int,
+    idx_str: *const c_char,
+    argc: c_int,
+    argv: *mut *mut ffi::sqlite3_value,
+) -> c_int {
+    let data = &mut *(cursor as *mut CursorData<T::Cursor>);
+    let idx_str = match idx_str.is_null() {
+        true => None,
+        false => CStr::from_ptr(idx_str).to_str().ok(),
+    };
+    let values = match argc {
+        0 => &[],
+        argc => std::slice::from_raw_parts(argv, argc as usize),
+    };
+    if data.failed {
+        return ffi::SQLITE_ERROR;
+    }
+    let result = catch_panic(|| data.cursor.filter(idx_num, idx_str, &Values { values }));
+    cursor_result(cursor, result)
+}
+
+unsafe extern "C" fn x_next<T: VTab>(cursor: *mut ffi::sqlite3_vtab_cursor) -> c_int {
+    let data = &mut *(cursor as *mut CursorData<T::Cursor>);
+    if data.failed {
+        return ffi::SQLITE_ERROR;
+    }
+    let result = catch_panic(|| data.cursor.next());
+    cursor_result(cursor, result)
+}
+
+unsafe extern "C" fn x_eof<T: VTab>(cursor: *mut ffi::sqlite3_vtab_cursor) -> c_int {
+    let data = &mut *(cursor as *mut CursorData<T::Cursor>);
+    match catch_panic(|| Ok(data.cursor.eof())) {
+        Ok(eof) => eof as c_int,
+        Err(error) => {
+            // xEof can not fail and ending the scan would return truncated rows,
+            // so sqlite is told a row follows and the next call on the cursor fails instead
+            vtab_error((*cursor).pVtab, error);
+            data.failed = true;
+            0
+        }
+    }
+}
+
+unsafe extern "C" fn x_column<T: VTab>(
+    cursor: *mut ffi::sqlite3_vtab_cursor,
+    context: *mut ffi::sqlite3_context,
+    column: c_int,
+) -> c_int {
+    let data = &*(cursor as *const CursorData<T::Cursor>);
+    if data.failed {
+        return ffi::SQLITE_ERROR;
+    }
+    let mut context = Context {
+        context,
+        _marker: PhantomData,
+    };
+    let result = catch_panic(|| data.cursor.column(&mut context, column));
+    cursor_result(cursor, result)
+}
+
+unsafe extern "C" fn x_rowid<T: VTab>(
+    cursor: *mut ffi::sqlite3_vtab_cursor,
+    rowid: *mut ffi::sqlite3_int64,
+) -> c_int {
+    let data = &*(cursor as *const CursorData<T::Cursor>);
+    if data.failed {
+        return ffi::SQLITE_ERROR;
+    }
+    match catch_panic(|| data.cursor.rowid()) {
+        Ok(id) => {
+            *rowid = id;
+            ffi::SQLITE_OK
+        }
+        Err(error) => vtab_error((*cursor).pVtab, error),
+    }
+}
+
+/// A row produced by a table function, see `Database::create_table_function`
+pub trait TableRow {
+    /// sets the value of `column` on `context`
+    fn column(&self, context: &mut Context, column: i32);
+}
+
+impl<T: ToResult + Clone> TableRow for T {
+    fn column(&self, context: &mut Context, _column: i32) {
+        context.set(self.clone())
+    }
+}
+
+macro_rules! table_row_tuple {
+    ($($name:ident as $idx:tt),+) => (
+        impl<$($name),+> TableRow for ($($name,)+)
+        where
+            $($name: ToResult + Clone,)+
+        {
+            fn column(&self, context: &mut Context, column: i32) {
+                match column {
+                    $($idx => context.set(self.$idx.clone()),)+
+                    _ => context.set(()),
+                }
+            }
+        }
+    );
+}
+
+table_row_tuple!(A as 0);
+table_row_tuple!(A as 0, B as 1);
+table_row_tuple!(A as 0, B as 1, C as 2);
+table_row_tuple!(A as 0, B as 1, C as 2, D as 3);
+table_row_tuple!(A as 0, B as 1, C as 2, D as 3, E as 4);
+table_row_tuple!(A as 0, B as 1, C as 2, D as 3, E as 4, F as 5);
+
+type RowFactory<R> = dyn Fn(&Values) -> Result<Box<dyn Iterator<Item = R>>> + Send + Sync;
+
+struct TableFunctionData<R> {
+    name: String,
+    columns: Vec<String>,
+    parameters: Vec<String>,
+    factory: Box<RowFactory<R>>,
+}
+
+struct TableFunction<R: 'static> {
+    data: std::sync::Arc<TableFunctionData<R>>,
+}
+
+struct TableFunctionCursor<R: 'static> {
+    data: std::sync::Arc<TableFunctionData<R>>,
+    arguments: Vec<*mut ffi::sqlite3_value>,
+    rows: Option<Box<dyn Iterator<Item = R>>>,
+    row: Option<R>,
+    rowid: i64,
+}
+
+impl Database {
+    /// registers an eponymous table function, like `generate_series`
+    ///
+    /// `columns` are the names of the output columns and `parameters` the names of
+    /// the hidden columns taking the function arguments, every argument must be given.
+    /// `factory` gets the arguments and returns the rows, either single values or tuples.
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// # let database = Database::open(":memory:")?;
+    /// database.create_table_function("range", &["value"], &["start", "stop"], |args| {
+    ///     Ok(args.get::<i64>(0)?..args.get::<i64>(1)?)
+    /// })?;
+    /// let sum: i32 = database.collect("select sum(value) from range(1, 5)", ())?;
+    /// assert!(sum == 10);
+    ///
+    /// database.create_table_function("split", &["part", "position"], &["text"], |args| {
+    ///     let text: String = args.get(0)?;
+    ///     let parts = text.split(',').map(str::to_owned).collect::<Vec<_>>();
+    ///     Ok(parts.into_iter().zip(1..))
+    /// })?;
+    /// let parts: Vec<(String, i32)> = database.collect_vec("select * from split('a,b')", ())?;
+    /// assert!(parts == vec![("a".to_owned(), 1), ("b".to_owned(), 2)]);
+    ///
+    /// // a panic is reported as an error of the query
+    /// database.create_table_function("fail", &["value"], &[], |_| -> Result<Vec<i64>> {
+    ///     panic!("no rows today")
+    /// })?;
+    /// assert!(database.collect::<i64>("select value from fail", ()).is_err());
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn create_table_function<R, I, F>(
+        &self,
+        name: &str,
+        columns: &[&str],
+        parameters: &[&str],
+        factory: F,
+    ) -> Result<()>
+    where
+        R: TableRow + 'static,
+        I: IntoIterator<Item = R>,
+        I::IntoIter: 'static,
+        F: Fn(&Values) -> Result<I> + Send + Sync + 'static,
+    {
+        let data = TableFunctionData {
+            name: name.to_owned(),
+            columns: columns.iter().map(|&c| c.to_owned()).collect(),
+            parameters: parameters.iter().map(|&p| p.to_owned()).collect(),
+            factory: Box::new(move |args| {
+                let rows = factory(args)?.into_iter();
+                Ok(Box::new(rows) as Box<dyn Iterator<Item = R>>)
+            }),
+        };
+        let mut module = module::<TableFunction<R>>();
+        // eponymous-only, the function can not be used in `create virtual table`
+        module.xCreate = None;
+        self.create_module_v2::<TableFunction<R>>(name, module, std::sync::Arc::new(data))
+    }
+}
+
+impl<R: TableRow + 'static> VTab for TableFunction<R> {
+    type Aux = std::sync::Arc<TableFunctionData<R>>;
+    type Cursor = TableFunctionCursor<R>;
+
+    fn connect(aux: &Self::Aux, _args: &[String]) -> Result<(String, Self)> {
+        let names = aux
+            .columns
+            .iter()
+            .map(|c| super::quote_identifier(c))
+            .chain(
+                aux.parameters
+                    .iter()
+                    .map(|p| format!("{} hidden", super::quote_identifier(p))),
+            )
+            .collect::<Vec<_>>();
+        Ok((
+            format!("create table x({})", names.join(", ")),
+            TableFunction { data: aux.clone() },
+        ))
+    }
+
+    fn best_index(&self, info: &mut IndexInfo) -> Result<()> {
+        let constraints = info.constraints();
+        for (position, parameter) in self.data.parameters.iter().enumerate() {
+            let column = (self.data.columns.len() + position) as i32;
+            let mut found = None;
+            for (i, constraint) in constraints.iter().enumerate() {
+                if constraint.column == column && constraint.op == ffi::SQLITE_INDEX_CONSTRAINT_EQ {
+                    found = Some((i, constraint.usable));
+                    if constraint.usable {
+                        break;
+                    }
+                }
+            }
+            match found {
+                Some((i, true)) => info.set_argv_index(i, position as i32 + 1, true)?,
+                // the argument depends on another table, sqlite will try another plan
+                Some((_, false)) => return Err(ffi::SQLITE_CONSTRAINT.into()),
+                None => {
+                    return Err(RsqliteError::new(
+                        ffi::SQLITE_ERROR,
+                        ffi::SQLITE_ERROR,
+                        format!("{}: missing argument {}", self.data.name, parameter),
+                    ))
+                }
+            }
+        }
+        info.set_estimated_cost(1000.0);
+        Ok(())
+    }
+
+    fn open(&self) -> Result<Self::Cursor> {
+        Ok(TableFunctionCursor {
+            data: self.data.clone(),
+            arguments: Vec::new(),
+            rows: None,
+            row: None,
+            rowid: 0,
+        })
+    }
+}
+
+impl<R: 'static> TableFunctionCursor<R> {
+    fn free_arguments(&mut self) {
+        for argument in self.arguments.drain(..) {
+            unsafe { ffi::sqlite3_value_free(argument) };
+        }
+    }
+}
+
+impl<R: 'static> Drop for TableFunctionCursor<R> {
+    fn drop(&mut self) {
+        self.free_arguments();
+    }
+}
+
+impl<R: TableRow + 'static> VTabCursor for TableFunctionCursor<R> {
+    fn filter(&mut self, _: i32, _: Option<&str>, args: &Values) -> Result<()> {
+        self.free_arguments();
+        for &value in args.values {
+            let copy = unsafe { ffi::sqlite3_value_dup(value) };
+            if copy.is_null() {
+                return Err(ffi::SQLITE_NOMEM.into());
+            }
+            self.arguments.push(copy);
+        }
+        let mut rows = (self.data.factory)(args)?;
+        self.row = rows.next();
+        self.rows = Some(rows);
+        self.rowid = 1;
+        Ok(())
+    }
+
+    fn next(&mut self) -> Result<()> {
+        self.row = self.rows.as_mut().and_then(|rows| rows.next());
+        self.rowid += 1;
+        Ok(())
+    }
+
+    fn eof(&self) -> bool {
+        self.row.is_none()
+    }
+
+    fn column(&self, context: &mut Context, column: i32) -> Result<()> {
+        let columns = self.data.columns.len() as i32;
+        match (column < columns, &self.row) {
+            (true, Some(row)) => row.column(context, column),
+            (false, _) => match self.arguments.get((column - columns) as usize) {
+                Some(&argument) => unsafe { ffi::sqlite3_result_value(context.context, argument) },
+                None => context.set(()),
+            },
+            (true, None) => context.set(()),
+        }
+        Ok(())
+    }
+
+    fn rowid(&self) -> Result<i64> {
+        Ok(self.rowid)
+    }
+}
@@ -0,0 +1,166 @@
+//! Journal modes and WAL checkpoints
+use super::{Database, Result, RsqliteError};
+use core::ptr;
+use libc::c_int;
+use sqlite3_sys as ffi;
+
+/// The journal modes of `pragma journal_mode`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JournalMode {
+    Delete,
+    Truncate,
+    Persist,
+    Memory,
+    Wal,
+    Off,
+}
+
+/// How `wal_checkpoint` waits for readers and writers,
+/// see https://sqlite.org/c3ref/wal_checkpoint_v2.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckpointMode {
+    /// checkpoints as many frames as possible without waiting
+    Passive,
+    /// waits for writers, then checkpoints every frame
+    Full,
+    /// like `Full`, then waits for readers so the log restarts from the beginning
+    Restart,
+    /// like `Restart`, then truncates the log file to zero bytes
+    Truncate,
+}
+
+/// The outcome of `wal_checkpoint`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Checkpoint {
+    /// frames in the log
+    pub log_frames: i32,
+    /// frames in the log that were copied back into the database
+    pub checkpointed_frames: i32,
+}
+
+impl JournalMode {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            JournalMode::Delete => "delete",
+            JournalMode::Truncate => "truncate",
+            JournalMode::Persist => "persist",
+            JournalMode::Memory => "memory",
+            JournalMode::Wal => "wal",
+            JournalMode::Off => "off",
+        }
+    }
+
+    fn parse(mode: &str) -> Option<JournalMode> {
+        [
+            JournalMode::Delete,
+            JournalMode::Truncate,
+            JournalMode::Persist,
+            JournalMode::Memory,
+            JournalMode::Wal,
+            JournalMode::Off,
+        ]
+        .iter()
+        .copied()
+        .find(|journal_mode| journal_mode.as_str().eq_ignore_ascii_case(mode))
+    }
+}
+
+impl CheckpointMode {
+    fn as_raw(self) -> c_int {
+        match self {
+            CheckpointMode::Passive => ffi::SQLITE_CHECKPOINT_PASSIVE,
+            CheckpointMode::Full => ffi::SQLITE_CHECKPOINT_FULL,
+            CheckpointMode::Restart => ffi::SQLITE_CHECKPOINT_RESTART,
+            CheckpointMode::Truncate => ffi::SQLITE_CHECKPOINT_TRUNCATE,
+        }
+    }
+}
+
+impl Database {
+    /// the journal mode of the main database
+    pub fn journal_mode(&self) -> Result<JournalMode> {
+        let mode: String = self.collect("pragma journal_mode", ())?;
+        parse_journal_mode(&mode)
+    }
+
+    /// changes the journal mode of the main database, returning the mode now in effect,
+    /// which differs from `mode` when the change is not possible (e.g. WAL for in-memory databases)
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// use rsqlite::wal::{CheckpointMode, JournalMode};
+    ///
+    /// # let path = std::env::temp_dir().join("rsqlite-wal-checkpoint.db");
+    /// # let path = path.to_str().unwrap();
+    /// # let _ = std::fs::remove_file(path);
+    /// let database = Database::open(path)?;
+    /// assert!(database.set_journal_mode(JournalMode::Wal)? == JournalMode::Wal);
+    ///
+    /// database.execute("create table t(x integer)", ())?;
+    /// database.execute("insert into t values (1)", ())?;
+    /// let checkpoint = database.wal_checkpoint(CheckpointMode::Truncate)?;
+    /// assert!(checkpoint.log_frames == 0);
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn set_journal_mode(&self, mode: JournalMode) -> Result<JournalMode> {
+        let mode: String = self.collect(&format!("pragma journal_mode = {}", mode.as_str()), ())?;
+        parse_journal_mode(&mode)
+    }
+
+    /// checkpoints automatically once the write-ahead log holds `frames` frames after a commit,
+    /// 0 disables automatic checkpoints. The default is 1000 frames.
+    ///
+    /// This replaces any `wal_hook`.
+    ///
+    /// ```
+    /// # use rsqlite::*;
+    /// # let database = Database::open(":memory:")?;
+    /// database.wal_autocheckpoint(0)?;
+    /// assert!(database.pragma_get::<i32>("wal_autocheckpoint")? == 0);
+    /// database.wal_autocheckpoint(10_000)?;
+    /// assert!(database.pragma_get::<i32>("wal_autocheckpoint")? == 10_000);
+    /// # Ok::<(), RsqliteError>(())
+    /// ```
+    pub fn wal_autocheckpoint(&self, frames: u32) -> Result<()> {
+        let frames = frames.min(c_int::MAX as u32) as c_int;
+        match unsafe { ffi::sqlite3_wal_autocheckpoint(self.db, frames) } {
+            ffi::SQLITE_OK => {
+                self.wal_hook.set(None);
+                Ok(())
+            }
+            other => Err(self.error(other)),
+        }
+    }
+
+    /// checkpoints the write-ahead log of every attached database in WAL mode
+    pub fn wal_checkpoint(&self, mode: CheckpointMode) -> Result<Checkpoint> {
+        let mut log_frames = 0;
+        let mut checkpointed_frames = 0;
+        let ecode = unsafe {
+            ffi::sqlite3_wal_checkpoint_v2(
+                self.db,
+                ptr::null(),
+                mode.as_raw(),
+                &mut log_frames,
+                &mut checkpointed_frames,
+            )
+        };
+        match ecode {
+            ffi::SQLITE_OK => Ok(Checkpoint {
+                log_frames,
+                checkpointed_frames,
+            }),
+            other => Err(self.error(other)),
+        }
+    }
+}
+
+fn parse_journal_mode(mode: &str) -> Result<JournalMode> {
+    JournalMode::parse(mode).ok_or_else(|| {
+        RsqliteError::new(
+            ffi::SQLITE_ERROR,
+            ffi::SQLITE_ERROR,
+            format!("unknown journal mode {}", mode),
+        )
+    })
+}